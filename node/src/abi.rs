@@ -0,0 +1,539 @@
+//! Solidity ABI encoding of contract calls and decoding of their outputs and logs.
+//!
+//! Arguments and decoded values are JSON values: addresses, bytes and fixed bytes are
+//! `0x` prefixed hex strings, integers are decimal strings (numbers and `0x` prefixed
+//! hex strings are accepted as arguments), and arrays are JSON arrays. Tuples are not
+//! supported.
+//!
+//! Logs are decoded from their ABI encoded topics and data. Nothing in the node decodes
+//! them yet, because `pallet_ssvm` does not deposit the logs of contracts.
+
+use std::fmt;
+
+use serde::{Serialize, Deserialize};
+use serde_json::Value as Json;
+use sp_core::{H160, H256, U256, hashing::keccak_256, hexdisplay::HexDisplay};
+
+#[cfg(test)]
+mod tests;
+
+/// Size of an ABI word.
+const WORD: usize = 32;
+
+/// A parameter of a function, constructor or event.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Param {
+	/// Name of the parameter, empty for unnamed outputs.
+	#[serde(default)]
+	pub name: String,
+	/// Solidity type of the parameter.
+	#[serde(rename = "type")]
+	pub kind: String,
+	/// Whether the parameter of an event is a topic.
+	#[serde(default)]
+	pub indexed: bool,
+}
+
+/// An entry of a contract ABI.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Entry {
+	/// Kind of the entry: `function`, `constructor`, `event`, `fallback` or `receive`.
+	#[serde(rename = "type", default = "function_kind")]
+	pub kind: String,
+	/// Name of the function or event, empty for the constructor.
+	#[serde(default)]
+	pub name: String,
+	/// Parameters of the function, constructor or event.
+	#[serde(default)]
+	pub inputs: Vec<Param>,
+	/// Return values of the function.
+	#[serde(default)]
+	pub outputs: Vec<Param>,
+	/// Whether the event has no signature topic.
+	#[serde(default)]
+	pub anonymous: bool,
+}
+
+fn function_kind() -> String {
+	"function".into()
+}
+
+/// A decoded value with the parameter it belongs to.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DecodedParam {
+	/// Name of the parameter.
+	pub name: String,
+	/// Solidity type of the parameter.
+	#[serde(rename = "type")]
+	pub kind: String,
+	/// The value, in the JSON representation of the type.
+	pub value: Json,
+}
+
+/// A contract ABI, as emitted by the Solidity compiler.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Abi {
+	/// Functions, constructor and events of the contract.
+	pub entries: Vec<Entry>,
+}
+
+impl Abi {
+	/// Parse the JSON ABI of a contract.
+	pub fn parse(json: &str) -> Result<Self, String> {
+		serde_json::from_str(json)
+			.map(|entries| Abi { entries })
+			.map_err(|e| format!("Invalid ABI: {}", e))
+	}
+
+	/// Read the ABI of a contract from an already parsed JSON value.
+	pub fn from_json(json: Json) -> Result<Self, String> {
+		serde_json::from_value(json)
+			.map(|entries| Abi { entries })
+			.map_err(|e| format!("Invalid ABI: {}", e))
+	}
+
+	/// The function called `name`, which can also be a full signature such as
+	/// `transfer(address,uint256)` to pick one of several overloads.
+	pub fn function(&self, name: &str) -> Result<&Entry, String> {
+		let mut functions = self.entries.iter().filter(|entry| entry.kind == "function");
+		if name.contains('(') {
+			functions.find(|entry| entry.signature().ok().as_deref() == Some(name))
+				.ok_or_else(|| format!("No function with signature {}", name))
+		} else {
+			let matching = functions.filter(|entry| entry.name == name).collect::<Vec<_>>();
+			match matching.as_slice() {
+				[function] => Ok(function),
+				[] => Err(format!("No function called {}", name)),
+				_ => Err(format!("Function {} is overloaded, give its full signature", name)),
+			}
+		}
+	}
+
+	/// The constructor of the contract, if it declares one.
+	pub fn constructor(&self) -> Option<&Entry> {
+		self.entries.iter().find(|entry| entry.kind == "constructor")
+	}
+
+	/// The event whose signature hashes to `topic`.
+	// `pallet_ssvm` does not deposit the logs of contracts yet.
+	#[allow(dead_code)]
+	pub fn event(&self, topic: &H256) -> Option<&Entry> {
+		self.entries.iter()
+			.filter(|entry| entry.kind == "event" && !entry.anonymous)
+			.find(|entry| entry.signature().ok().map(|signature| keccak_256(signature.as_bytes()))
+				== Some(topic.to_fixed_bytes()))
+	}
+}
+
+impl Entry {
+	/// Canonical signature of the function or event, e.g. `balanceOf(address)`.
+	pub fn signature(&self) -> Result<String, String> {
+		let types = types_of(&self.inputs)?
+			.iter()
+			.map(ToString::to_string)
+			.collect::<Vec<_>>();
+		Ok(format!("{}({})", self.name, types.join(",")))
+	}
+
+	/// First four bytes of the hash of the signature, identifying the function in call data.
+	pub fn selector(&self) -> Result<[u8; 4], String> {
+		let hash = keccak_256(self.signature()?.as_bytes());
+		let mut selector = [0; 4];
+		selector.copy_from_slice(&hash[..4]);
+		Ok(selector)
+	}
+
+	/// Encode the arguments of a call, prefixed with the selector unless the entry is the
+	/// constructor, whose arguments are appended to the code as they are.
+	pub fn encode_input(&self, args: &[Json]) -> Result<Vec<u8>, String> {
+		if args.len() != self.inputs.len() {
+			return Err(format!("Expected {} arguments, got {}", self.inputs.len(), args.len()));
+		}
+		let types = types_of(&self.inputs)?;
+		let values = types.iter()
+			.zip(&self.inputs)
+			.zip(args)
+			.map(|((kind, param), arg)| kind.parse_value(arg)
+				.map_err(|e| format!("Invalid argument {}: {}", param.name, e)))
+			.collect::<Result<Vec<_>, _>>()?;
+
+		let mut data = if self.kind == "constructor" { Vec::new() } else { self.selector()?.to_vec() };
+		data.extend(encode(&types, &values));
+		Ok(data)
+	}
+
+	/// Decode the return values of a call.
+	pub fn decode_output(&self, data: &[u8]) -> Result<Vec<DecodedParam>, String> {
+		let types = types_of(&self.outputs)?;
+		let values = decode(&types, data)?;
+		Ok(named(&self.outputs, &types, values))
+	}
+
+	/// Decode a log emitted for this event. Indexed parameters of dynamic types are only
+	/// known through their hash, which is returned as `bytes32`.
+	// `pallet_ssvm` does not deposit the logs of contracts yet.
+	#[allow(dead_code)]
+	pub fn decode_log(&self, topics: &[H256], data: &[u8]) -> Result<Vec<DecodedParam>, String> {
+		let topics = if self.anonymous { topics } else { topics.get(1..).unwrap_or_default() };
+		let types = types_of(&self.inputs)?;
+		let indexed = self.inputs.iter().filter(|param| param.indexed).count();
+		if topics.len() != indexed {
+			return Err(format!("Expected {} indexed topics, got {}", indexed, topics.len()));
+		}
+
+		let data_types = types.iter()
+			.zip(&self.inputs)
+			.filter(|(_, param)| !param.indexed)
+			.map(|(kind, _)| kind.clone())
+			.collect::<Vec<_>>();
+		let mut data_values = decode(&data_types, data)?.into_iter();
+		let mut topics = topics.iter();
+
+		let mut decoded = Vec::new();
+		for (kind, param) in types.iter().zip(&self.inputs) {
+			let (kind, value) = if !param.indexed {
+				(kind.clone(), data_values.next().ok_or("Missing log data")?)
+			} else if kind.is_dynamic() || matches!(kind, ParamType::FixedArray(..)) {
+				let topic = topics.next().ok_or("Missing topic")?;
+				(ParamType::FixedBytes(WORD), Value::FixedBytes(topic.as_bytes().to_vec()))
+			} else {
+				let topic = topics.next().ok_or("Missing topic")?;
+				(kind.clone(), decode_param(kind, topic.as_bytes(), 0)?)
+			};
+			decoded.push(DecodedParam {
+				name: param.name.clone(),
+				kind: kind.to_string(),
+				value: value.to_json(),
+			});
+		}
+		Ok(decoded)
+	}
+}
+
+fn types_of(params: &[Param]) -> Result<Vec<ParamType>, String> {
+	params.iter().map(|param| param.kind.parse()).collect()
+}
+
+fn named(params: &[Param], types: &[ParamType], values: Vec<Value>) -> Vec<DecodedParam> {
+	params.iter().zip(types).zip(values)
+		.map(|((param, kind), value)| DecodedParam {
+			name: param.name.clone(),
+			kind: kind.to_string(),
+			value: value.to_json(),
+		})
+		.collect()
+}
+
+/// A Solidity type.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParamType {
+	/// `address`.
+	Address,
+	/// `uintN`, with its size in bits.
+	Uint(usize),
+	/// `intN`, with its size in bits.
+	Int(usize),
+	/// `bool`.
+	Bool,
+	/// `string`.
+	String,
+	/// `bytes`.
+	Bytes,
+	/// `bytesN`, with its size in bytes.
+	FixedBytes(usize),
+	/// `T[]`.
+	Array(Box<ParamType>),
+	/// `T[N]`.
+	FixedArray(Box<ParamType>, usize),
+}
+
+impl std::str::FromStr for ParamType {
+	type Err = String;
+
+	fn from_str(kind: &str) -> Result<Self, String> {
+		let invalid = || format!("Unsupported type {}", kind);
+		if kind.ends_with(']') {
+			let open = kind.rfind('[').ok_or_else(invalid)?;
+			let inner = Box::new(kind[..open].parse()?);
+			let size = &kind[open + 1..kind.len() - 1];
+			return if size.is_empty() {
+				Ok(ParamType::Array(inner))
+			} else {
+				size.parse().map(|size| ParamType::FixedArray(inner, size)).map_err(|_| invalid())
+			};
+		}
+
+		let bits = |size: &str| match size {
+			"" => Ok(256),
+			size => size.parse::<usize>().ok()
+				.filter(|bits| *bits > 0 && *bits <= 256 && bits % 8 == 0)
+				.ok_or_else(invalid),
+		};
+		match kind {
+			"address" => Ok(ParamType::Address),
+			"bool" => Ok(ParamType::Bool),
+			"string" => Ok(ParamType::String),
+			"bytes" => Ok(ParamType::Bytes),
+			_ if kind.starts_with("uint") => bits(&kind[4..]).map(ParamType::Uint),
+			_ if kind.starts_with("int") => bits(&kind[3..]).map(ParamType::Int),
+			_ if kind.starts_with("bytes") => kind[5..].parse::<usize>().ok()
+				.filter(|size| *size > 0 && *size <= WORD)
+				.map(ParamType::FixedBytes)
+				.ok_or_else(invalid),
+			_ => Err(invalid()),
+		}
+	}
+}
+
+impl fmt::Display for ParamType {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			ParamType::Address => write!(f, "address"),
+			ParamType::Uint(bits) => write!(f, "uint{}", bits),
+			ParamType::Int(bits) => write!(f, "int{}", bits),
+			ParamType::Bool => write!(f, "bool"),
+			ParamType::String => write!(f, "string"),
+			ParamType::Bytes => write!(f, "bytes"),
+			ParamType::FixedBytes(size) => write!(f, "bytes{}", size),
+			ParamType::Array(inner) => write!(f, "{}[]", inner),
+			ParamType::FixedArray(inner, size) => write!(f, "{}[{}]", inner, size),
+		}
+	}
+}
+
+impl ParamType {
+	/// Whether values of the type are encoded after the head, at an offset stored in it.
+	pub fn is_dynamic(&self) -> bool {
+		match self {
+			ParamType::String | ParamType::Bytes | ParamType::Array(_) => true,
+			ParamType::FixedArray(inner, _) => inner.is_dynamic(),
+			_ => false,
+		}
+	}
+
+	/// Size taken by a value of the type in the head of an encoding.
+	fn head_len(&self) -> usize {
+		match self {
+			ParamType::FixedArray(inner, size) if !self.is_dynamic() => inner.head_len() * size,
+			_ => WORD,
+		}
+	}
+
+	/// Read a value of the type from its JSON representation.
+	pub fn parse_value(&self, json: &Json) -> Result<Value, String> {
+		let invalid = || format!("{} is not a valid {}", json, self);
+		match (self, json) {
+			(ParamType::Address, Json::String(address)) => address.trim_start_matches("0x")
+				.parse()
+				.map(Value::Address)
+				.map_err(|_| invalid()),
+			(ParamType::Uint(bits), _) => parse_uint(json)
+				.filter(|value| value.bits() <= *bits)
+				.map(Value::Uint)
+				.ok_or_else(invalid),
+			(ParamType::Int(bits), _) => parse_int(json)
+				.filter(|value| fits_int(value, *bits))
+				.map(Value::Int)
+				.ok_or_else(invalid),
+			(ParamType::Bool, Json::Bool(value)) => Ok(Value::Bool(*value)),
+			(ParamType::String, Json::String(value)) => Ok(Value::String(value.clone())),
+			(ParamType::Bytes, Json::String(hex)) => sp_core::bytes::from_hex(hex)
+				.map(Value::Bytes)
+				.map_err(|_| invalid()),
+			(ParamType::FixedBytes(size), Json::String(hex)) => sp_core::bytes::from_hex(hex)
+				.ok()
+				.filter(|bytes| bytes.len() == *size)
+				.map(Value::FixedBytes)
+				.ok_or_else(invalid),
+			(ParamType::Array(inner), Json::Array(values)) => values.iter()
+				.map(|value| inner.parse_value(value))
+				.collect::<Result<_, _>>()
+				.map(Value::Array),
+			(ParamType::FixedArray(inner, size), Json::Array(values)) if values.len() == *size =>
+				values.iter()
+					.map(|value| inner.parse_value(value))
+					.collect::<Result<_, _>>()
+					.map(Value::Array),
+			_ => Err(invalid()),
+		}
+	}
+}
+
+fn parse_uint(json: &Json) -> Option<U256> {
+	match json {
+		Json::Number(number) => number.as_u64().map(U256::from),
+		Json::String(value) if value.starts_with("0x") => U256::from_str_radix(&value[2..], 16).ok(),
+		Json::String(value) => U256::from_dec_str(value).ok(),
+		_ => None,
+	}
+}
+
+/// Two's complement of `value`.
+fn negate(value: U256) -> U256 {
+	(!value).overflowing_add(U256::one()).0
+}
+
+fn parse_int(json: &Json) -> Option<U256> {
+	let (negative, magnitude) = match json {
+		Json::Number(number) => {
+			let number = number.as_i64()?;
+			(number < 0, Some(U256::from((number as i128).abs() as u128)))
+		},
+		Json::String(value) if value.starts_with('-') =>
+			(true, parse_uint(&Json::String(value[1..].into()))),
+		_ => (false, parse_uint(json)),
+	};
+	// The magnitude must leave room for the sign bit.
+	let magnitude = magnitude.filter(|magnitude| magnitude.bits() < 256)?;
+	Some(if negative { negate(magnitude) } else { magnitude })
+}
+
+/// Whether the two's complement `value` fits in an `int` of the given size in bits.
+fn fits_int(value: &U256, bits: usize) -> bool {
+	if value.bit(255) {
+		// The lowest value is -2^(bits - 1).
+		let magnitude = negate(*value);
+		magnitude.bits() < bits || magnitude == U256::one() << (bits - 1)
+	} else {
+		value.bits() < bits
+	}
+}
+
+/// A value of a Solidity type.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+	/// An `address`.
+	Address(H160),
+	/// A `uintN`.
+	Uint(U256),
+	/// An `intN`, in two's complement.
+	Int(U256),
+	/// A `bool`.
+	Bool(bool),
+	/// A `string`.
+	String(String),
+	/// A `bytes`.
+	Bytes(Vec<u8>),
+	/// A `bytesN`.
+	FixedBytes(Vec<u8>),
+	/// A `T[]` or a `T[N]`.
+	Array(Vec<Value>),
+}
+
+impl Value {
+	/// The JSON representation of the value.
+	pub fn to_json(&self) -> Json {
+		match self {
+			Value::Address(address) => Json::String(format!("{:?}", address)),
+			Value::Uint(value) => Json::String(value.to_string()),
+			Value::Int(value) if value.bit(255) => Json::String(format!("-{}", negate(*value))),
+			Value::Int(value) => Json::String(value.to_string()),
+			Value::Bool(value) => Json::Bool(*value),
+			Value::String(value) => Json::String(value.clone()),
+			Value::Bytes(bytes) | Value::FixedBytes(bytes) =>
+				Json::String(format!("0x{}", HexDisplay::from(bytes))),
+			Value::Array(values) => Json::Array(values.iter().map(Value::to_json).collect()),
+		}
+	}
+}
+
+fn word(value: U256) -> [u8; WORD] {
+	let mut word = [0; WORD];
+	value.to_big_endian(&mut word);
+	word
+}
+
+/// `bytes` padded with zeros up to a multiple of the word size.
+fn padded(bytes: &[u8]) -> Vec<u8> {
+	let mut padded = bytes.to_vec();
+	padded.resize((bytes.len() + WORD - 1) / WORD * WORD, 0);
+	padded
+}
+
+/// Encode `values` of the given `types` one after the other, as the arguments of a call.
+pub fn encode(types: &[ParamType], values: &[Value]) -> Vec<u8> {
+	let head_len = types.iter().map(ParamType::head_len).sum::<usize>();
+	let mut head = Vec::new();
+	let mut tail = Vec::new();
+	for (kind, value) in types.iter().zip(values) {
+		if kind.is_dynamic() {
+			head.extend_from_slice(&word(U256::from(head_len + tail.len())));
+			tail.extend(encode_param(kind, value));
+		} else {
+			head.extend(encode_param(kind, value));
+		}
+	}
+	head.extend(tail);
+	head
+}
+
+fn encode_param(kind: &ParamType, value: &Value) -> Vec<u8> {
+	match (kind, value) {
+		(_, Value::Address(address)) => word(U256::from_big_endian(address.as_bytes())).to_vec(),
+		(_, Value::Uint(value)) | (_, Value::Int(value)) => word(*value).to_vec(),
+		(_, Value::Bool(value)) => word(U256::from(*value as u8)).to_vec(),
+		(_, Value::FixedBytes(bytes)) => padded(bytes),
+		(_, Value::String(value)) => [&word(U256::from(value.len()))[..], &padded(value.as_bytes())].concat(),
+		(_, Value::Bytes(bytes)) => [&word(U256::from(bytes.len()))[..], &padded(bytes)].concat(),
+		(ParamType::Array(inner), Value::Array(values)) => {
+			let types = vec![(**inner).clone(); values.len()];
+			[&word(U256::from(values.len()))[..], &encode(&types, values)].concat()
+		},
+		(ParamType::FixedArray(inner, size), Value::Array(values)) =>
+			encode(&vec![(**inner).clone(); *size], values),
+		_ => Vec::new(),
+	}
+}
+
+fn read_word(data: &[u8], at: usize) -> Result<&[u8], String> {
+	data.get(at..at + WORD).ok_or_else(|| "Data too short".to_string())
+}
+
+fn read_usize(data: &[u8], at: usize) -> Result<usize, String> {
+	let value = U256::from_big_endian(read_word(data, at)?);
+	if value > U256::from(data.len()) {
+		return Err("Offset or length out of the data".into());
+	}
+	Ok(value.as_usize())
+}
+
+/// Decode values of the given `types` encoded one after the other.
+pub fn decode(types: &[ParamType], data: &[u8]) -> Result<Vec<Value>, String> {
+	decode_params(types, data, 0)
+}
+
+fn decode_params(types: &[ParamType], data: &[u8], base: usize) -> Result<Vec<Value>, String> {
+	let mut offset = base;
+	let mut values = Vec::new();
+	for kind in types {
+		let at = if kind.is_dynamic() { base + read_usize(data, offset)? } else { offset };
+		values.push(decode_param(kind, data, at)?);
+		offset += kind.head_len();
+	}
+	Ok(values)
+}
+
+fn decode_param(kind: &ParamType, data: &[u8], at: usize) -> Result<Value, String> {
+	match kind {
+		ParamType::Address => Ok(Value::Address(H160::from_slice(&read_word(data, at)?[12..]))),
+		ParamType::Uint(_) => Ok(Value::Uint(U256::from_big_endian(read_word(data, at)?))),
+		ParamType::Int(_) => Ok(Value::Int(U256::from_big_endian(read_word(data, at)?))),
+		ParamType::Bool => Ok(Value::Bool(read_word(data, at)?.iter().any(|byte| *byte != 0))),
+		ParamType::FixedBytes(size) => Ok(Value::FixedBytes(read_word(data, at)?[..*size].to_vec())),
+		ParamType::String | ParamType::Bytes => {
+			let len = read_usize(data, at)?;
+			let bytes = data.get(at + WORD..at + WORD + len).ok_or("Data too short")?.to_vec();
+			if *kind == ParamType::Bytes {
+				Ok(Value::Bytes(bytes))
+			} else {
+				String::from_utf8(bytes).map(Value::String).map_err(|_| "Invalid UTF-8 string".into())
+			}
+		},
+		ParamType::Array(inner) => {
+			let len = read_usize(data, at)?;
+			decode_params(&vec![(**inner).clone(); len], data, at + WORD).map(Value::Array)
+		},
+		ParamType::FixedArray(inner, size) =>
+			decode_params(&vec![(**inner).clone(); *size], data, at).map(Value::Array),
+	}
+}
@@ -0,0 +1,212 @@
+use super::*;
+use serde_json::json;
+
+/// ABI of `docs/erc20/erc20.sol`.
+const ERC20_ABI: &str = include_str!("../../../docs/erc20/erc20.abi.json");
+
+const ALICE: &str = "0x9621dde636de098b43efb0fa9b61facfe328f99d";
+const BOB: &str = "0x41dccbd49b26c50d34355ed86ff0fa9e489d1e01";
+
+fn erc20() -> Abi {
+	Abi::parse(ERC20_ABI).unwrap()
+}
+
+fn hex(data: &str) -> Vec<u8> {
+	sp_core::bytes::from_hex(&data.split_whitespace().collect::<String>()).unwrap()
+}
+
+fn uint_word(value: u64) -> String {
+	format!("{:064x}", value)
+}
+
+#[test]
+fn computes_erc20_selectors() {
+	let abi = erc20();
+	let selector = |name| HexDisplay::from(&abi.function(name).unwrap().selector().unwrap()).to_string();
+
+	assert_eq!(selector("totalSupply"), "18160ddd");
+	assert_eq!(selector("balanceOf"), "70a08231");
+	assert_eq!(selector("transfer"), "a9059cbb");
+	assert_eq!(selector("allowance"), "dd62ed3e");
+	assert_eq!(selector("approve"), "095ea7b3");
+	assert_eq!(selector("transferFrom"), "23b872dd");
+	assert_eq!(selector("increaseAllowance"), "39509351");
+	assert_eq!(selector("decreaseAllowance"), "a457c2d7");
+	assert_eq!(selector("name"), "06fdde03");
+	assert_eq!(selector("symbol"), "95d89b41");
+}
+
+#[test]
+fn encodes_erc20_calls_of_the_tutorial() {
+	let abi = erc20();
+
+	let balance_of = abi.function("balanceOf").unwrap().encode_input(&[json!(ALICE)]).unwrap();
+	assert_eq!(balance_of, hex("0x70a08231 0000000000000000000000009621dde636de098b43efb0fa9b61facfe328f99d"));
+
+	let transfer = abi.function("transfer(address,uint256)").unwrap()
+		.encode_input(&[json!(BOB), json!(3)])
+		.unwrap();
+	assert_eq!(transfer, hex(&format!(
+		"0xa9059cbb 00000000000000000000000041dccbd49b26c50d34355ed86ff0fa9e489d1e01 {}",
+		uint_word(3),
+	)));
+
+	// Integers can also be given as decimal or hex strings.
+	let approve = abi.function("approve").unwrap();
+	assert_eq!(
+		approve.encode_input(&[json!(BOB), json!("1000")]).unwrap(),
+		approve.encode_input(&[json!(BOB), json!("0x3e8")]).unwrap(),
+	);
+}
+
+#[test]
+fn rejects_invalid_erc20_arguments() {
+	let abi = erc20();
+	let transfer = abi.function("transfer").unwrap();
+
+	assert_eq!(
+		transfer.encode_input(&[json!(BOB)]),
+		Err("Expected 2 arguments, got 1".into()),
+	);
+	assert!(transfer.encode_input(&[json!("0x1234"), json!(3)]).is_err());
+	assert!(transfer.encode_input(&[json!(BOB), json!(-3)]).is_err());
+	assert!(transfer.encode_input(&[json!(BOB), json!(true)]).is_err());
+	assert_eq!(abi.function("mint"), Err("No function called mint".into()));
+}
+
+#[test]
+fn decodes_erc20_outputs() {
+	let abi = erc20();
+
+	let balance = abi.function("balanceOf").unwrap().decode_output(&hex(&uint_word(1000))).unwrap();
+	assert_eq!(balance, vec![DecodedParam { name: "".into(), kind: "uint256".into(), value: json!("1000") }]);
+
+	let success = abi.function("transfer").unwrap().decode_output(&hex(&uint_word(1))).unwrap();
+	assert_eq!(success[0].value, json!(true));
+
+	let name = abi.function("name").unwrap().decode_output(&hex(&format!(
+		"{} {} 4552433230546f6b656e44656d6f000000000000000000000000000000000000",
+		uint_word(32),
+		uint_word(14),
+	))).unwrap();
+	assert_eq!(name[0].kind, "string");
+	assert_eq!(name[0].value, json!("ERC20TokenDemo"));
+
+	// Outputs cut short by a failing contract are rejected.
+	assert!(abi.function("name").unwrap().decode_output(&hex(&uint_word(32))).is_err());
+}
+
+#[test]
+fn decodes_erc20_transfer_log() {
+	let mut abi = erc20();
+	abi.entries.push(serde_json::from_value(json!({
+		"type": "event",
+		"name": "Transfer",
+		"inputs": [
+			{ "name": "from", "type": "address", "indexed": true },
+			{ "name": "to", "type": "address", "indexed": true },
+			{ "name": "value", "type": "uint256", "indexed": false },
+		],
+	})).unwrap());
+
+	let topic = H256::from_slice(&hex("0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef"));
+	let event = abi.event(&topic).unwrap();
+	let topics = vec![
+		topic,
+		H256::from_slice(&hex("0x0000000000000000000000009621dde636de098b43efb0fa9b61facfe328f99d")),
+		H256::from_slice(&hex("0x00000000000000000000000041dccbd49b26c50d34355ed86ff0fa9e489d1e01")),
+	];
+
+	let decoded = event.decode_log(&topics, &hex(&uint_word(3))).unwrap();
+	let values = decoded.iter().map(|param| (param.name.as_str(), param.value.clone())).collect::<Vec<_>>();
+	assert_eq!(values, vec![("from", json!(ALICE)), ("to", json!(BOB)), ("value", json!("3"))]);
+	assert!(event.decode_log(&topics[..2], &hex(&uint_word(3))).is_err());
+}
+
+#[test]
+fn decodes_dynamic_log_parameters() {
+	let abi = Abi::from_json(json!([{
+		"type": "event",
+		"name": "Renamed",
+		"inputs": [
+			{ "name": "old", "type": "string", "indexed": true },
+			{ "name": "new", "type": "string", "indexed": false },
+		],
+	}])).unwrap();
+
+	let topic = H256(keccak_256(b"Renamed(string,string)"));
+	let old = H256(keccak_256(b"dave"));
+	let event = abi.event(&topic).unwrap();
+	let data = hex(&format!(
+		"{} {} 6576650000000000000000000000000000000000000000000000000000000000",
+		uint_word(0x20),
+		uint_word(3),
+	));
+
+	// Indexed strings are only known through their hash.
+	let decoded = event.decode_log(&[topic, old], &data).unwrap();
+	let values = decoded.iter().map(|param| (param.kind.as_str(), param.value.clone())).collect::<Vec<_>>();
+	let hash = format!("0x{}", HexDisplay::from(old.as_bytes()));
+	assert_eq!(values, vec![("bytes32", json!(hash)), ("string", json!("eve"))]);
+	assert!(event.decode_log(&[topic], &data).is_err());
+	assert!(event.decode_log(&[topic, old], &data[..WORD]).is_err());
+}
+
+#[test]
+fn encodes_dynamic_arguments() {
+	// Example of the Solidity ABI specification.
+	let entry: Entry = serde_json::from_value(json!({
+		"name": "sam",
+		"inputs": [{ "type": "bytes" }, { "type": "bool" }, { "type": "uint[]" }],
+	})).unwrap();
+	assert_eq!(entry.signature().unwrap(), "sam(bytes,bool,uint256[])");
+
+	let data = entry.encode_input(&[json!("0x64617665"), json!(true), json!([1, 2, 3])]).unwrap();
+	assert_eq!(data, hex(&format!(
+		"0xa5643bf2 {} {} {} {} 6461766500000000000000000000000000000000000000000000000000000000 {} {} {} {}",
+		uint_word(0x60),
+		uint_word(1),
+		uint_word(0xa0),
+		uint_word(4),
+		uint_word(3),
+		uint_word(1),
+		uint_word(2),
+		uint_word(3),
+	)));
+
+	let types = types_of(&entry.inputs).unwrap();
+	assert_eq!(
+		decode(&types, &data[4..]).unwrap(),
+		vec![
+			Value::Bytes(b"dave".to_vec()),
+			Value::Bool(true),
+			Value::Array(vec![Value::Uint(1.into()), Value::Uint(2.into()), Value::Uint(3.into())]),
+		],
+	);
+}
+
+#[test]
+fn round_trips_signed_and_nested_values() {
+	let types = vec!["int8".parse().unwrap(), "string[2]".parse().unwrap(), "bytes2".parse().unwrap()];
+	let args = vec![json!("-5"), json!(["a", "bc"]), json!("0x1234")];
+	let values = types.iter().zip(&args)
+		.map(|(kind, arg): (&ParamType, _)| kind.parse_value(arg).unwrap())
+		.collect::<Vec<_>>();
+
+	let decoded = decode(&types, &encode(&types, &values)).unwrap();
+	assert_eq!(decoded, values);
+	assert_eq!(decoded.iter().map(Value::to_json).collect::<Vec<_>>(), args);
+}
+
+#[test]
+fn rejects_integers_out_of_range() {
+	let int8 = "int8".parse::<ParamType>().unwrap();
+	let uint8 = "uint8".parse::<ParamType>().unwrap();
+
+	assert!(int8.parse_value(&json!(127)).is_ok());
+	assert!(int8.parse_value(&json!(-128)).is_ok());
+	assert!(int8.parse_value(&json!(128)).is_err());
+	assert!(int8.parse_value(&json!("-129")).is_err());
+	assert!(uint8.parse_value(&json!(255)).is_ok());
+	assert!(uint8.parse_value(&json!(256)).is_err());
+}
@@ -1,19 +1,63 @@
 use sp_core::{Pair, Public, sr25519};
 use ssvm_node_runtime::{
-    AccountId, AuraConfig, BalancesConfig, GenesisConfig, GrandpaConfig, SSVMAccount, SSVMConfig,
-    Sha3Hasher, Signature, SudoConfig, SystemConfig, WASM_BINARY,
+    AccountId, AuraConfig, BalancesConfig, BlockGasConfig, FeeDistributionConfig, GenesisConfig, GrandpaConfig,
+    Perbill, SSVMAccount, SSVMConfig, SessionConfig, Sha3Hasher, Signature, SudoConfig, SystemConfig, ValidatorSetConfig, WASM_BINARY,
+    opaque::SessionKeys,
 };
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
 use sp_finality_grandpa::AuthorityId as GrandpaId;
 use sp_runtime::traits::{Verify, IdentifyAccount};
 use sc_service::ChainType;
+use sc_chain_spec::ChainSpecExtension;
+use serde::{Serialize, Deserialize};
 use pallet_ssvm::{ConvertAccountId, HashTruncateConvertAccountId};
 
 // The URL for the telemetry server.
 // const STAGING_TELEMETRY_URL: &str = "wss://telemetry.polkadot.io/submit/";
 
+/// Node-side settings carried by the chain spec, next to the genesis state.
+#[derive(Debug, Default, Clone, Serialize, Deserialize, ChainSpecExtension)]
+#[serde(rename_all = "camelCase")]
+pub struct Extensions {
+	/// GRANDPA finality gadget settings.
+	#[serde(default)]
+	pub grandpa: GrandpaSettings,
+}
+
+impl Extensions {
+	/// Try to get the extensions from the given `ChainSpec`.
+	pub fn try_get(chain_spec: &dyn sc_service::ChainSpec) -> Option<&Self> {
+		sc_chain_spec::get_extension(chain_spec.extensions())
+	}
+}
+
+/// GRANDPA settings used by `service::new_full`. Missing fields take their default values.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct GrandpaSettings {
+	/// Duration of a gossip round, in milliseconds.
+	pub gossip_duration_millis: u64,
+	/// Number of blocks between two stored justifications.
+	pub justification_period: u32,
+	/// Never vote for blocks closer than this to the best block. `None` disables the rule.
+	pub vote_before_best_by: Option<u32>,
+	/// Never vote further than three quarters of the way into the unfinalized chain.
+	pub three_quarters_of_unfinalized_chain: bool,
+}
+
+impl Default for GrandpaSettings {
+	fn default() -> Self {
+		GrandpaSettings {
+			gossip_duration_millis: 333,
+			justification_period: 512,
+			vote_before_best_by: Some(2),
+			three_quarters_of_unfinalized_chain: true,
+		}
+	}
+}
+
 /// Specialized `ChainSpec`. This is a specialization of the general Substrate ChainSpec type.
-pub type ChainSpec = sc_service::GenericChainSpec<GenesisConfig>;
+pub type ChainSpec = sc_service::GenericChainSpec<GenesisConfig, Extensions>;
 
 /// Generate a crypto pair from seed.
 pub fn get_from_seed<TPublic: Public>(seed: &str) -> <TPublic::Pair as Pair>::Public {
@@ -31,14 +75,19 @@ pub fn get_account_id_from_seed<TPublic: Public>(seed: &str) -> AccountId where
 	AccountPublic::from(get_from_seed::<TPublic>(seed)).into_account()
 }
 
-/// Generate an Aura authority key.
-pub fn authority_keys_from_seed(s: &str) -> (AuraId, GrandpaId) {
+/// Generate a validator account together with its Aura and GRANDPA session keys.
+pub fn authority_keys_from_seed(s: &str) -> (AccountId, AuraId, GrandpaId) {
 	(
+		get_account_id_from_seed::<sr25519::Public>(s),
 		get_from_seed::<AuraId>(s),
 		get_from_seed::<GrandpaId>(s),
 	)
 }
 
+fn session_keys(aura: AuraId, grandpa: GrandpaId) -> SessionKeys {
+	SessionKeys { aura, grandpa }
+}
+
 pub fn development_config() -> Result<ChainSpec, String> {
 	let wasm_binary = WASM_BINARY.ok_or("Development wasm binary not available".to_string())?;
 
@@ -74,7 +123,7 @@ pub fn development_config() -> Result<ChainSpec, String> {
 		// Properties
 		None,
 		// Extensions
-		None,
+		Extensions::default(),
 	))
 }
 
@@ -122,14 +171,14 @@ pub fn local_testnet_config() -> Result<ChainSpec, String> {
 		// Properties
 		None,
 		// Extensions
-		None,
+		Extensions::default(),
 	))
 }
 
 /// Configure initial storage state for FRAME modules.
 fn testnet_genesis(
 	wasm_binary: &[u8],
-	initial_authorities: Vec<(AuraId, GrandpaId)>,
+	initial_authorities: Vec<(AccountId, AuraId, GrandpaId)>,
 	root_key: AccountId,
 	endowed_accounts: Vec<AccountId>,
 	_enable_println: bool,
@@ -144,11 +193,30 @@ fn testnet_genesis(
 			// Configure endowed accounts with initial balance of 1 << 60.
 			balances: endowed_accounts.iter().cloned().map(|k|(k, 1 << 60)).collect(),
 		}),
+		pallet_validator_set: Some(ValidatorSetConfig {
+			validators: initial_authorities.iter().map(|x| x.0.clone()).collect(),
+		}),
+		pallet_session: Some(SessionConfig {
+			keys: initial_authorities.iter().map(|x| (
+				x.0.clone(),
+				x.0.clone(),
+				session_keys(x.1.clone(), x.2.clone()),
+			)).collect(),
+		}),
+		// Aura and GRANDPA authorities are set up by the session pallet.
 		pallet_aura: Some(AuraConfig {
-			authorities: initial_authorities.iter().map(|x| (x.0.clone())).collect(),
+			authorities: vec![],
 		}),
 		pallet_grandpa: Some(GrandpaConfig {
-			authorities: initial_authorities.iter().map(|x| (x.1.clone(), 1)).collect(),
+			authorities: vec![],
+		}),
+		pallet_fee_distribution: Some(FeeDistributionConfig {
+			// The block author gets 20% of fees and gas, the treasury the rest.
+			author_share: Perbill::from_percent(20),
+		}),
+		pallet_block_gas: Some(BlockGasConfig {
+			// Room for about ten contract calls with the gas limits of the tutorials.
+			block_gas_limit: 500_000_000,
 		}),
 		pallet_sudo: Some(SudoConfig {
 			// Assign network admin rights.
@@ -1,5 +1,17 @@
+use std::{net::{Ipv4Addr, SocketAddr}, path::PathBuf};
+
 use structopt::StructOpt;
-use sc_cli::RunCmd;
+
+mod contract;
+mod execution;
+mod replay_block;
+mod run_contract;
+mod ssvm_state;
+
+pub use contract::ContractCmd;
+pub use replay_block::ReplayBlockCmd;
+pub use run_contract::RunContractCmd;
+pub use ssvm_state::{ExportSsvmStateCmd, ImportSsvmStateCmd};
 
 #[derive(Debug, StructOpt)]
 pub struct Cli {
@@ -10,6 +22,53 @@ pub struct Cli {
 	pub run: RunCmd,
 }
 
+#[derive(Debug, StructOpt)]
+pub struct RunCmd {
+	#[structopt(flatten)]
+	pub base: sc_cli::RunCmd,
+
+	/// Run the GRANDPA observer instead of the full voter on a non-authority node.
+	///
+	/// The node follows finality from the commit messages of the voters but does not
+	/// take part in vote gossip. Ignored on authority nodes.
+	#[structopt(long)]
+	pub grandpa_observer: bool,
+
+	/// Export Prometheus metrics about SSVM executions.
+	///
+	/// The SSVM does not report what it executes, so the imported blocks containing SSVM
+	/// extrinsics are executed a second time to observe them. Requires the Prometheus
+	/// exporter.
+	#[structopt(long)]
+	pub ssvm_metrics: bool,
+
+	/// Index the best chain into the SQLite database at the given path.
+	///
+	/// Blocks, extrinsics, events, SSVM receipts and created contracts are written as they
+	/// are imported. Rows of blocks retracted by a reorg are deleted. Requires
+	/// `--pruning archive`. Ignored on light nodes.
+	#[structopt(long, value_name = "PATH")]
+	pub indexer_db: Option<PathBuf>,
+
+	/// Serve the read-only block explorer REST API on the given TCP port.
+	///
+	/// The API serves the database of the indexer, so `--indexer-db` is required.
+	#[structopt(long, value_name = "PORT")]
+	pub explorer_port: Option<u16>,
+
+	/// Listen to all network interfaces for the explorer API, instead of the local one only.
+	#[structopt(long)]
+	pub explorer_external: bool,
+}
+
+impl RunCmd {
+	/// Address the explorer API listens to, if it is enabled.
+	pub fn explorer_address(&self) -> Option<SocketAddr> {
+		let interface = if self.explorer_external { Ipv4Addr::UNSPECIFIED } else { Ipv4Addr::LOCALHOST };
+		self.explorer_port.map(|port| SocketAddr::new(interface.into(), port))
+	}
+}
+
 #[derive(Debug, StructOpt)]
 pub enum Subcommand {
 	/// Build a chain specification.
@@ -33,6 +92,21 @@ pub enum Subcommand {
 	/// Revert the chain to a previous state.
 	Revert(sc_cli::RevertCmd),
 
+	/// Re-execute a block with the native and the wasm runtime and report the differences.
+	ReplayBlock(ReplayBlockCmd),
+
+	/// Deploy and call EWASM code on top of the state of a block, without touching the chain.
+	RunContract(RunContractCmd),
+
+	/// Export the SSVM accounts of a given block as JSON.
+	ExportSsvmState(ExportSsvmStateCmd),
+
+	/// Add SSVM accounts exported as JSON to the genesis of a chain spec.
+	ImportSsvmState(ImportSsvmStateCmd),
+
+	/// Deploy or call a contract through the RPC of a running node.
+	Contract(ContractCmd),
+
 	/// The custom benchmark subcommmand benchmarking runtime pallets.
 	#[structopt(name = "benchmark", about = "Benchmark runtime pallets.")]
 	Benchmark(frame_benchmarking_cli::BenchmarkCmd),
@@ -0,0 +1,332 @@
+//! The `contract` subcommand, deploying and calling SSVM contracts through a node's RPC.
+//!
+//! The extrinsic is built with the `SignedExtra` of the runtime, signed with the given
+//! secret URI and submitted to the node, which is then polled until a block includes it.
+//! The outcome is read from the events of the extrinsic in that block.
+
+use std::{fs, path::PathBuf, thread, time::{Duration, Instant}};
+
+use codec::{Encode, Decode};
+use futures::compat::Future01CompatExt;
+use jsonrpc_core_client::{RpcError, transports::http};
+use sc_rpc_api::{author::AuthorClient, chain::ChainClient, state::StateClient};
+use serde_json::Value as Json;
+use sp_core::{
+	Bytes, H160, Pair, U256, hashing::twox_128, hexdisplay::HexDisplay, sr25519,
+	storage::StorageKey,
+};
+use sp_rpc::{list::ListOrValue, number::NumberOrHex};
+use sp_runtime::{generic, traits::{Header as HeaderT, IdentifyAccount, Verify}};
+use ssvm_node_runtime::{
+	AccountId, BlockNumber, Call, Event, Hash, Header, Index, Signature, SignedBlock, SignedExtra,
+	SignedPayload, UncheckedExtrinsic,
+};
+use structopt::StructOpt;
+use substrate_frame_rpc_system::SystemClient;
+
+use crate::abi::{Abi, Entry};
+
+/// The `contract` command used to deploy and call SSVM contracts.
+#[derive(Debug, StructOpt)]
+pub enum ContractCmd {
+	/// Deploy a contract and print its address.
+	Deploy(DeployCmd),
+
+	/// Call a contract and print its output.
+	Call(CallCmd),
+}
+
+/// Parameters of the transaction submitted to the node.
+#[derive(Debug, StructOpt)]
+pub struct TransactionParams {
+	/// Secret URI of the account signing the transaction, e.g. `//Alice`.
+	#[structopt(long)]
+	pub suri: String,
+
+	/// HTTP RPC endpoint of the node to submit the transaction to.
+	#[structopt(long, default_value = "http://127.0.0.1:9933")]
+	pub url: String,
+
+	/// Value transferred to the contract.
+	#[structopt(long, default_value = "0")]
+	pub value: u128,
+
+	/// Maximum amount of gas the execution may use.
+	#[structopt(long, default_value = "50000000")]
+	pub gas_limit: u32,
+
+	/// Price paid for each unit of gas.
+	#[structopt(long, default_value = "1")]
+	pub gas_price: u128,
+
+	/// Seconds to wait for the transaction to be included in a block.
+	#[structopt(long, default_value = "60")]
+	pub timeout: u64,
+}
+
+/// Deploy a contract.
+#[derive(Debug, StructOpt)]
+pub struct DeployCmd {
+	/// Path of the EWASM code deploying the contract, binary or hex encoded if it ends in `.hex`.
+	#[structopt(parse(from_os_str))]
+	pub code: PathBuf,
+
+	/// Hex encoded arguments of the constructor, appended to the code.
+	#[structopt(long)]
+	pub args: Option<Bytes>,
+
+	/// Path of the JSON ABI of the contract, used to encode `--params`.
+	#[structopt(long, parse(from_os_str))]
+	pub abi: Option<PathBuf>,
+
+	/// Arguments of the constructor as a JSON array, e.g. `["0x41dc…", "1000"]`.
+	#[structopt(long, conflicts_with = "args", requires = "abi")]
+	pub params: Option<String>,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub transaction: TransactionParams,
+}
+
+/// Call a contract.
+#[derive(Debug, StructOpt)]
+pub struct CallCmd {
+	/// Address of the contract.
+	#[structopt(parse(try_from_str = parse_address))]
+	pub address: H160,
+
+	/// Hex encoded input of the call.
+	#[structopt(long)]
+	pub input: Option<Bytes>,
+
+	/// Path of the JSON ABI of the contract, used to encode `--function` and decode its output.
+	#[structopt(long, parse(from_os_str))]
+	pub abi: Option<PathBuf>,
+
+	/// Name or signature of the function to call.
+	#[structopt(long, conflicts_with = "input", requires = "abi")]
+	pub function: Option<String>,
+
+	/// Arguments of the function as a JSON array, e.g. `["0x41dc…", 3]`.
+	#[structopt(long, requires = "function")]
+	pub params: Option<String>,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub transaction: TransactionParams,
+}
+
+fn parse_address(address: &str) -> Result<H160, String> {
+	address.trim_start_matches("0x").parse().map_err(|e| format!("Invalid address: {:?}", e))
+}
+
+fn rpc_error(e: RpcError) -> sc_cli::Error {
+	sc_cli::Error::Other(format!("RPC request failed: {:?}", e))
+}
+
+fn read_abi(path: &PathBuf) -> sc_cli::Result<Abi> {
+	Abi::parse(&fs::read_to_string(path)?).map_err(sc_cli::Error::Input)
+}
+
+fn parse_params(params: &Option<String>) -> sc_cli::Result<Vec<Json>> {
+	match params {
+		Some(params) => serde_json::from_str(params)
+			.map_err(|e| sc_cli::Error::Input(format!("Invalid parameters: {}", e))),
+		None => Ok(Vec::new()),
+	}
+}
+
+/// Read the EWASM code at `path`.
+fn read_code(path: &PathBuf) -> sc_cli::Result<Vec<u8>> {
+	if path.extension().map_or(false, |extension| extension == "hex") {
+		let hex = fs::read_to_string(path)?;
+		sp_core::bytes::from_hex(hex.trim())
+			.map_err(|e| sc_cli::Error::Input(format!("Invalid hex code: {:?}", e)))
+	} else {
+		Ok(fs::read(path)?)
+	}
+}
+
+impl ContractCmd {
+	/// Run the contract command.
+	pub fn run(&self) -> sc_cli::Result<()> {
+		let (call, transaction, function) = match self {
+			ContractCmd::Deploy(cmd) => {
+				let mut code = read_code(&cmd.code)?;
+				match (&cmd.abi, &cmd.params) {
+					(Some(abi), Some(_)) => {
+						let abi = read_abi(abi)?;
+						let constructor = abi.constructor()
+							.ok_or_else(|| sc_cli::Error::Input("The ABI has no constructor.".into()))?;
+						code.extend(constructor.encode_input(&parse_params(&cmd.params)?)
+							.map_err(sc_cli::Error::Input)?);
+					},
+					_ => code.extend(cmd.args.iter().flat_map(|args| args.0.iter().cloned())),
+				}
+				let call = pallet_ssvm::Call::create(
+					code,
+					U256::from(cmd.transaction.value),
+					cmd.transaction.gas_limit,
+					U256::from(cmd.transaction.gas_price),
+				);
+				(call, &cmd.transaction, None)
+			},
+			ContractCmd::Call(cmd) => {
+				let function = match (&cmd.abi, &cmd.function) {
+					(Some(abi), Some(function)) =>
+						Some(read_abi(abi)?.function(function).map_err(sc_cli::Error::Input)?.clone()),
+					_ => None,
+				};
+				let input = match &function {
+					Some(function) => function.encode_input(&parse_params(&cmd.params)?)
+						.map_err(sc_cli::Error::Input)?,
+					None => cmd.input.clone().map(|input| input.0).unwrap_or_default(),
+				};
+				let call = pallet_ssvm::Call::call(
+					cmd.address,
+					input,
+					U256::from(cmd.transaction.value),
+					cmd.transaction.gas_limit,
+					U256::from(cmd.transaction.gas_price),
+				);
+				(call, &cmd.transaction, function)
+			},
+		};
+
+		let events = futures::executor::block_on(submit(transaction, Call::SSVM(call)))?;
+		let mut success = true;
+		for event in events {
+			match event {
+				Event::pallet_ssvm(pallet_ssvm::Event::Create(address)) =>
+					println!("Contract created at {:?}", address),
+				Event::pallet_ssvm(pallet_ssvm::Event::Output(output)) => {
+					println!("Output: 0x{}", HexDisplay::from(&output));
+					if let Some(function) = &function {
+						print_decoded(function, &output);
+					}
+				},
+				Event::pallet_contract_revert(pallet_contract_revert::Event::ContractReverted(_, reason)) =>
+					println!("Reverted: {}", String::from_utf8_lossy(&reason)),
+				Event::frame_system(frame_system::Event::ExtrinsicFailed(error, _)) => {
+					println!("Failed: {:?}", error);
+					success = false;
+				},
+				_ => {},
+			}
+		}
+
+		if success {
+			Ok(())
+		} else {
+			Err(sc_cli::Error::Other("The transaction failed.".into()))
+		}
+	}
+}
+
+/// Print the return values of `function` encoded in `output`.
+fn print_decoded(function: &Entry, output: &[u8]) {
+	match function.decode_output(output) {
+		Ok(values) => for value in values {
+			println!("  {} {}: {}", value.kind, value.name, value.value);
+		},
+		Err(e) => println!("  unable to decode the output: {}", e),
+	}
+}
+
+/// Sign `call`, submit it and return the events it emitted once included in a block.
+async fn submit(params: &TransactionParams, call: Call) -> sc_cli::Result<Vec<Event>> {
+	let pair = sr25519::Pair::from_string(&params.suri, None)
+		.map_err(|e| sc_cli::Error::Input(format!("Invalid secret URI: {:?}", e)))?;
+	let signer: AccountId = <Signature as Verify>::Signer::from(pair.public()).into_account();
+
+	let author = http::connect::<AuthorClient<Hash, Hash>>(&params.url).compat().await.map_err(rpc_error)?;
+	let chain = http::connect::<ChainClient<BlockNumber, Hash, Header, SignedBlock>>(&params.url)
+		.compat().await.map_err(rpc_error)?;
+	let state = http::connect::<StateClient<Hash>>(&params.url).compat().await.map_err(rpc_error)?;
+	let system = http::connect::<SystemClient<AccountId, Index>>(&params.url)
+		.compat().await.map_err(rpc_error)?;
+
+	let genesis_hash = match chain.block_hash(Some(ListOrValue::Value(NumberOrHex::Number(0))))
+		.compat().await.map_err(rpc_error)?
+	{
+		ListOrValue::Value(Some(hash)) => hash,
+		_ => return Err(sc_cli::Error::Other("The node has no genesis block.".into())),
+	};
+	let version = state.runtime_version(None).compat().await.map_err(rpc_error)?;
+	let nonce = system.nonce(signer.clone()).compat().await.map_err(rpc_error)?;
+
+	let extra: SignedExtra = (
+		frame_system::CheckSpecVersion::new(),
+		frame_system::CheckTxVersion::new(),
+		frame_system::CheckGenesis::new(),
+		frame_system::CheckEra::from(generic::Era::Immortal),
+		frame_system::CheckNonce::from(nonce),
+		frame_system::CheckWeight::new(),
+		pallet_transaction_payment::ChargeTransactionPayment::from(0),
+		pallet_block_gas::CheckBlockGas::new(),
+		ssvm_node_runtime::ssvm::ChargeGas,
+		ssvm_node_runtime::ssvm::ReportRevert,
+		ssvm_node_runtime::ssvm::NoteDeployer,
+	);
+	let additional = (
+		version.spec_version,
+		version.transaction_version,
+		genesis_hash,
+		genesis_hash,
+		(), (), (), (), (), (), (),
+	);
+	let payload = SignedPayload::from_raw(call, extra, additional);
+	let signature = payload.using_encoded(|payload| pair.sign(payload));
+	let (call, extra, _) = payload.deconstruct();
+	let extrinsic = UncheckedExtrinsic::new_signed(call, signer.into(), signature.into(), extra);
+	let encoded = extrinsic.encode();
+
+	let start = chain.header(None).compat().await.map_err(rpc_error)?
+		.map(|header| *header.number())
+		.unwrap_or_default();
+	let hash = author.submit_extrinsic(encoded.clone().into()).compat().await.map_err(rpc_error)?;
+	println!("Submitted extrinsic {:?}", hash);
+
+	// Look for the extrinsic in every new block until it shows up.
+	let deadline = Instant::now() + Duration::from_secs(params.timeout);
+	let mut next = start + 1;
+	while Instant::now() < deadline {
+		let best = chain.header(None).compat().await.map_err(rpc_error)?
+			.map(|header| *header.number())
+			.unwrap_or_default();
+		while next <= best {
+			let block_hash = match chain.block_hash(Some(ListOrValue::Value(NumberOrHex::Number(next.into()))))
+				.compat().await.map_err(rpc_error)?
+			{
+				ListOrValue::Value(Some(hash)) => hash,
+				_ => break,
+			};
+			let block = chain.block(Some(block_hash)).compat().await.map_err(rpc_error)?;
+			let index = block.and_then(|block| block.block.extrinsics.iter()
+				.position(|extrinsic| extrinsic.encode() == encoded));
+			if let Some(index) = index {
+				println!("Included in block #{} ({:?})", next, block_hash);
+				return events_of(&state, block_hash, index as u32).await;
+			}
+			next += 1;
+		}
+		thread::sleep(Duration::from_secs(1));
+	}
+
+	Err(sc_cli::Error::Other("The transaction was not included in time.".into()))
+}
+
+/// The events emitted by the extrinsic at `index` in block `at`.
+async fn events_of(state: &StateClient<Hash>, at: Hash, index: u32) -> sc_cli::Result<Vec<Event>> {
+	let key = StorageKey([twox_128(b"System"), twox_128(b"Events")].concat());
+	let events = match state.storage(key, Some(at)).compat().await.map_err(rpc_error)? {
+		Some(events) => Vec::<frame_system::EventRecord<Event, Hash>>::decode(&mut &events.0[..])
+			.map_err(|e| sc_cli::Error::Other(format!("Unable to decode the events: {}", e)))?,
+		None => Vec::new(),
+	};
+
+	Ok(events.into_iter()
+		.filter(|record| record.phase == frame_system::Phase::ApplyExtrinsic(index))
+		.map(|record| record.event)
+		.collect())
+}
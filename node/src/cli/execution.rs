@@ -0,0 +1,114 @@
+//! Offline execution of runtime calls, shared by the subcommands inspecting contracts.
+
+use std::collections::BTreeMap;
+
+use codec::Decode;
+use sc_executor::NativeExecutor;
+use sc_service::{Configuration, SpawnTaskHandle};
+use sp_core::{hexdisplay::HexDisplay, traits::RuntimeCode};
+use sp_externalities::Extensions;
+use sp_runtime::traits::{BlakeTwo256, NumberFor};
+use sp_state_machine::{Backend as StateBackend, ExecutionStrategy, OverlayedChanges, StateMachine};
+use ssvm_node_runtime::opaque::Block;
+
+use crate::service::Executor;
+
+/// Storage changes, `None` values being deletions.
+pub type Changes = BTreeMap<Vec<u8>, Option<Vec<u8>>>;
+
+/// Outcome of a runtime call.
+#[derive(PartialEq)]
+pub struct Step {
+	/// Encoded return value of the call.
+	pub output: Vec<u8>,
+	/// Storage changes made by the call.
+	pub changes: Changes,
+}
+
+/// Runs runtime calls one after the other on top of a state, with the same executor.
+/// Changes made by a call are seen by the next ones but never written to the database.
+pub struct Execution<'a, S> {
+	state: &'a S,
+	executor: &'a NativeExecutor<Executor>,
+	runtime_code: &'a RuntimeCode<'a>,
+	strategy: ExecutionStrategy,
+	spawn_handle: SpawnTaskHandle,
+	overlay: OverlayedChanges,
+}
+
+impl<'a, S: StateBackend<BlakeTwo256>> Execution<'a, S> {
+	/// Create an execution on top of `state`, running `runtime_code` with `strategy`.
+	pub fn new(
+		state: &'a S,
+		executor: &'a NativeExecutor<Executor>,
+		runtime_code: &'a RuntimeCode<'a>,
+		strategy: ExecutionStrategy,
+		spawn_handle: SpawnTaskHandle,
+	) -> Self {
+		Execution {
+			state,
+			executor,
+			runtime_code,
+			strategy,
+			spawn_handle,
+			overlay: Default::default(),
+		}
+	}
+
+	/// All the changes made so far.
+	fn changes(&self) -> Changes {
+		self.overlay.changes()
+			.map(|(key, value)| (key.clone(), value.value().cloned()))
+			.collect()
+	}
+
+	/// Call the runtime `method` with the encoded arguments `data`.
+	pub fn call(&mut self, method: &str, data: &[u8]) -> sc_cli::Result<Step> {
+		let before = self.changes();
+		let output = StateMachine::<_, _, NumberFor<Block>, _>::new(
+			self.state,
+			None,
+			&mut self.overlay,
+			self.executor,
+			method,
+			data,
+			Extensions::default(),
+			self.runtime_code,
+			Box::new(self.spawn_handle.clone()),
+		)
+			.execute(self.strategy)
+			.map_err(|e| sc_cli::Error::Other(format!("Calling {} failed: {}", method, e)))?;
+		let changes = self.changes()
+			.into_iter()
+			.filter(|(key, value)| before.get(key) != Some(value))
+			.collect();
+
+		Ok(Step { output, changes })
+	}
+}
+
+/// The executor configured for the node.
+pub fn executor(config: &Configuration) -> NativeExecutor<Executor> {
+	NativeExecutor::<Executor>::new(
+		config.wasm_method,
+		config.default_heap_pages,
+		config.max_runtime_instances,
+	)
+}
+
+/// Decode the return value of a call.
+pub fn decode<T: Decode>(step: &Step, what: &str) -> sc_cli::Result<T> {
+	T::decode(&mut &step.output[..])
+		.map_err(|e| sc_cli::Error::Other(format!("Unable to decode {}: {}", what, e)))
+}
+
+/// Print storage changes, hex encoded.
+pub fn print_changes(changes: &Changes) {
+	println!("  storage changes: {}", changes.len());
+	for (key, value) in changes {
+		match value {
+			Some(value) => println!("    0x{} = 0x{}", HexDisplay::from(key), HexDisplay::from(value)),
+			None => println!("    0x{} deleted", HexDisplay::from(key)),
+		}
+	}
+}
@@ -0,0 +1,172 @@
+//! The `replay-block` subcommand.
+//!
+//! The block is executed again on top of the state of its parent, once with the native
+//! runtime and once with the wasm runtime. Extrinsics are applied through `ReplayApi`,
+//! which applies them exactly like block import does while also reporting their events.
+//! The storage changes of every extrinsic are recorded so that a divergence between the
+//! two executors can be pinned down to the extrinsic that caused it.
+//!
+//! The native runtime is only used when it has the same version as the wasm runtime of the
+//! parent block: the executor would otherwise silently fall back to wasm, and the replay
+//! would compare the wasm runtime with itself.
+
+use std::{collections::BTreeSet, sync::Arc};
+
+use codec::{Encode, Decode};
+use sc_cli::{BlockNumberOrHash, CliConfiguration, ImportParams, SharedParams};
+use sc_client_api::{Backend, BlockBackend};
+use sc_service::{Configuration, SpawnTaskHandle};
+use sp_blockchain::HeaderBackend;
+use sp_core::hexdisplay::HexDisplay;
+use sp_runtime::{generic::BlockId, traits::Header as HeaderT};
+use sp_state_machine::{ExecutionStrategy, backend::BackendRuntimeCode};
+use sp_version::RuntimeVersion;
+use ssvm_node_runtime::{
+	UncheckedExtrinsic, apis::ExtrinsicReplay, native_version, opaque::{Block, Header}, ssvm::GasParams,
+};
+use structopt::StructOpt;
+
+use super::execution::{Execution, Step, decode, executor, print_changes};
+
+/// The `replay-block` command used to re-execute a block with the native and the wasm runtime.
+#[derive(Debug, StructOpt)]
+pub struct ReplayBlockCmd {
+	/// Block hash or number.
+	#[structopt(value_name = "HASH or NUMBER")]
+	pub input: BlockNumberOrHash,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub import_params: ImportParams,
+}
+
+/// Print the differences between the native and wasm outcomes of the same call.
+/// Returns whether they diverged.
+fn check_divergence(native: &Step, wasm: &Step) -> bool {
+	if native == wasm {
+		return false;
+	}
+
+	println!("  DIVERGENCE between native and wasm execution");
+	if native.output != wasm.output {
+		println!("    native output: 0x{}", HexDisplay::from(&native.output));
+		println!("    wasm output:   0x{}", HexDisplay::from(&wasm.output));
+	}
+	let keys = native.changes.keys().chain(wasm.changes.keys()).collect::<BTreeSet<_>>();
+	for key in keys {
+		let (native, wasm) = (native.changes.get(key), wasm.changes.get(key));
+		if native != wasm {
+			println!("    0x{}: native {:?}, wasm {:?}", HexDisplay::from(key), native, wasm);
+		}
+	}
+	true
+}
+
+impl ReplayBlockCmd {
+	/// Run the replay-block command.
+	pub fn run<B, C>(
+		&self,
+		config: &Configuration,
+		client: Arc<C>,
+		backend: Arc<B>,
+		spawn_handle: SpawnTaskHandle,
+	) -> sc_cli::Result<()>
+	where
+		B: Backend<Block>,
+		C: HeaderBackend<Block> + BlockBackend<Block>,
+	{
+		let id = self.input.parse::<Block>().map_err(sc_cli::Error::Input)?;
+		let header = client.header(id)?.ok_or("Unknown block")?;
+		let extrinsics = client.block_body(&id)?.ok_or("Unknown block")?;
+
+		let state = backend.state_at(BlockId::Hash(*header.parent_hash()))?;
+		let runtime_code = BackendRuntimeCode::new(&state);
+		let runtime_code = runtime_code.runtime_code()?;
+		let executor = executor(config);
+		let replay = |strategy| Execution::new(
+			&state,
+			&executor,
+			&runtime_code,
+			strategy,
+			spawn_handle.clone(),
+		);
+		let mut native = replay(ExecutionStrategy::AlwaysNative);
+		let mut wasm = replay(ExecutionStrategy::AlwaysWasm);
+		let mut diverged = false;
+
+		let onchain: RuntimeVersion = decode(&wasm.call("Core_version", &[])?, "the runtime version")?;
+		let native_version = native_version().runtime_version;
+		if !native_version.can_call_with(&onchain) || native_version.spec_version != onchain.spec_version {
+			return Err(sc_cli::Error::Other(format!(
+				"The native runtime ({}) cannot replay a block of the wasm runtime ({})",
+				native_version,
+				onchain,
+			)));
+		}
+
+		println!("Replaying block #{} ({:?})", header.number(), header.hash());
+		native.call("Core_initialize_block", &header.encode())?;
+		wasm.call("Core_initialize_block", &header.encode())?;
+
+		for (index, extrinsic) in extrinsics.into_iter().enumerate() {
+			let data = extrinsic.encode();
+			let native_step = native.call("ReplayApi_replay_extrinsic", &data)?;
+			let wasm_step = wasm.call("ReplayApi_replay_extrinsic", &data)?;
+			let outcome: ExtrinsicReplay = decode(&wasm_step, "the extrinsic replay")?;
+
+			println!("Extrinsic #{}", index);
+			if let Ok(extrinsic) = UncheckedExtrinsic::decode(&mut &data[..]) {
+				if let Some(gas) = GasParams::of(&extrinsic.function) {
+					println!(
+						"  SSVM gas limit: {} (the gas used is not reported), gas price: {}",
+						gas.gas_limit,
+						gas.gas_price,
+					);
+				}
+			}
+			println!("  result: {:?}", outcome.result);
+			println!("  events:");
+			for event in outcome.events {
+				println!("    {:?}", event);
+			}
+			print_changes(&wasm_step.changes);
+			diverged |= check_divergence(&native_step, &wasm_step);
+		}
+
+		let native_step = native.call("BlockBuilder_finalize_block", &[])?;
+		let wasm_step = wasm.call("BlockBuilder_finalize_block", &[])?;
+		diverged |= check_divergence(&native_step, &wasm_step);
+
+		// Replaying the block must lead to the state it commits to.
+		let replayed: Header = decode(&wasm_step, "the block header")?;
+		if replayed.state_root() != header.state_root() {
+			println!(
+				"State root mismatch: block has {:?}, replay computed {:?}",
+				header.state_root(),
+				replayed.state_root(),
+			);
+			diverged = true;
+		}
+
+		if diverged {
+			Err(sc_cli::Error::Other("The replayed block diverged.".into()))
+		} else {
+			println!("Native and wasm execution match the block.");
+			Ok(())
+		}
+	}
+}
+
+impl CliConfiguration for ReplayBlockCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn import_params(&self) -> Option<&ImportParams> {
+		Some(&self.import_params)
+	}
+}
@@ -0,0 +1,210 @@
+//! The `run-contract` subcommand.
+//!
+//! The EWASM code is deployed and called through the runtime of the chain, in a block
+//! built on top of the given one, so that the execution matches what would happen
+//! on-chain. Nothing is written to the database and the network is not started.
+//!
+//! With `--empty-state`, the state only holds the code of the native runtime, so the
+//! contract runs as on a chain where nothing happened yet and the database is not read.
+
+use std::{fs, path::PathBuf, sync::Arc};
+
+use codec::Encode;
+use pallet_ssvm::ConvertAccountId;
+use sc_cli::{BlockNumberOrHash, CliConfiguration, ImportParams, SharedParams};
+use sc_client_api::Backend;
+use sc_service::{Configuration, SpawnTaskHandle};
+use sp_blockchain::HeaderBackend;
+use sp_core::{
+	Bytes, U256, crypto::Ss58Codec, hexdisplay::HexDisplay, sr25519, storage::{Storage, well_known_keys},
+};
+use sp_runtime::{generic::BlockId, traits::{BlakeTwo256, Block as BlockT, Header as HeaderT, NumberFor, One}};
+use sp_state_machine::{
+	Backend as StateBackend, ExecutionStrategy, InMemoryBackend, backend::BackendRuntimeCode,
+};
+use ssvm_node_runtime::{
+	AccountId, Runtime, WASM_BINARY,
+	apis::{CallResult, CreateResult},
+	opaque::{Block, Header},
+};
+use structopt::StructOpt;
+
+use super::execution::{Execution, decode, executor, print_changes};
+use crate::chain_spec::get_account_id_from_seed;
+
+/// Conversion of accounts to SSVM addresses used by the runtime.
+type ConvertAccountIdOf = <Runtime as pallet_ssvm::Trait>::ConvertAccountId;
+
+/// The `run-contract` command used to execute EWASM code offline.
+#[derive(Debug, StructOpt)]
+pub struct RunContractCmd {
+	/// Path of the EWASM code deploying the contract.
+	#[structopt(long, parse(from_os_str))]
+	pub code: PathBuf,
+
+	/// Hex encoded input of a call made to the contract once deployed.
+	#[structopt(long)]
+	pub input: Option<Bytes>,
+
+	/// Block whose state the contract runs against, the genesis block if not given.
+	#[structopt(long, value_name = "HASH or NUMBER", conflicts_with = "empty-state")]
+	pub state: Option<BlockNumberOrHash>,
+
+	/// Run the contract against an empty state rather than the state of a block.
+	#[structopt(long)]
+	pub empty_state: bool,
+
+	/// SS58 address of the account deploying and calling the contract, Alice if not given.
+	#[structopt(long, parse(try_from_str = parse_account))]
+	pub from: Option<AccountId>,
+
+	/// Value transferred to the contract when calling it.
+	#[structopt(long, default_value = "0")]
+	pub value: u128,
+
+	/// Maximum amount of gas the deployment and the call may use, each.
+	#[structopt(long, default_value = "50000000")]
+	pub gas_limit: u32,
+
+	/// Price paid for each unit of gas.
+	#[structopt(long, default_value = "0")]
+	pub gas_price: u128,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub import_params: ImportParams,
+}
+
+fn parse_account(address: &str) -> Result<AccountId, String> {
+	AccountId::from_ss58check(address).map_err(|e| format!("Invalid address: {:?}", e))
+}
+
+impl RunContractCmd {
+	/// Run the run-contract command.
+	pub fn run<B, C>(
+		&self,
+		config: &Configuration,
+		client: Arc<C>,
+		backend: Arc<B>,
+		spawn_handle: SpawnTaskHandle,
+	) -> sc_cli::Result<()>
+	where
+		B: Backend<Block>,
+		C: HeaderBackend<Block>,
+	{
+		if self.empty_state {
+			let code = WASM_BINARY.ok_or("The native runtime has no wasm code")?;
+			let mut storage = Storage::default();
+			storage.top.insert(well_known_keys::CODE.to_vec(), code.to_vec());
+			let state = InMemoryBackend::<BlakeTwo256>::from(storage);
+			return self.execute(config, &state, 0, Default::default(), spawn_handle);
+		}
+
+		let id = match &self.state {
+			Some(state) => state.parse::<Block>().map_err(sc_cli::Error::Input)?,
+			None => BlockId::Number(0),
+		};
+		let parent = client.header(id)?.ok_or("Unknown block")?;
+		let state = backend.state_at(BlockId::Hash(parent.hash()))?;
+		self.execute(config, &state, *parent.number(), parent.hash(), spawn_handle)
+	}
+
+	/// Deploy and call the contract on top of `state`, the state of the given block.
+	fn execute<S: StateBackend<BlakeTwo256>>(
+		&self,
+		config: &Configuration,
+		state: &S,
+		parent_number: NumberFor<Block>,
+		parent_hash: <Block as BlockT>::Hash,
+		spawn_handle: SpawnTaskHandle,
+	) -> sc_cli::Result<()> {
+		let code = fs::read(&self.code)?;
+		let from = self.from.clone()
+			.unwrap_or_else(|| get_account_id_from_seed::<sr25519::Public>("Alice"));
+
+		let runtime_code = BackendRuntimeCode::new(state);
+		let runtime_code = runtime_code.runtime_code()?;
+		let executor = executor(config);
+		let mut execution = Execution::new(
+			state,
+			&executor,
+			&runtime_code,
+			ExecutionStrategy::NativeElseWasm,
+			spawn_handle,
+		);
+
+		// Events are only deposited once a block is being built.
+		let header = Header::new(
+			parent_number + One::one(),
+			Default::default(),
+			Default::default(),
+			parent_hash,
+			Default::default(),
+		);
+		execution.call("Core_initialize_block", &header.encode())?;
+
+		println!(
+			"Running as {} (SSVM address {:?}) on top of block #{}",
+			from,
+			ConvertAccountIdOf::convert_account_id(&from),
+			parent_number,
+		);
+		println!(
+			"Gas limit {} at price {}, the SSVM does not report the gas used",
+			self.gas_limit,
+			self.gas_price,
+		);
+
+		let step = execution.call(
+			"SSVMApi_create",
+			&(&from, code, U256::zero(), self.gas_limit, U256::from(self.gas_price)).encode(),
+		)?;
+		let created: CreateResult = decode(&step, "the creation result")?;
+		println!("Create");
+		println!("  output: 0x{}", HexDisplay::from(&created.output));
+		if let Some(error) = created.error {
+			println!("  error: {:?}", error);
+		}
+		print_changes(&step.changes);
+
+		let address = created.address
+			.ok_or_else(|| sc_cli::Error::Other("The contract was not created.".into()))?;
+		println!("Contract created at {:?}", address);
+
+		let input = match &self.input {
+			Some(input) => input.0.clone(),
+			None => return Ok(()),
+		};
+		let value = U256::from(self.value);
+		let step = execution.call(
+			"SSVMApi_call",
+			&(&from, address, input, value, self.gas_limit, U256::from(self.gas_price)).encode(),
+		)?;
+		let called: CallResult = decode(&step, "the call result")?;
+		println!("Call");
+		println!("  output: 0x{}", HexDisplay::from(&called.output));
+		if let Some(reason) = called.revert_reason {
+			println!("  revert reason: {}", String::from_utf8_lossy(&reason));
+		}
+		if let Some(error) = called.error {
+			println!("  error: {:?}", error);
+		}
+		print_changes(&step.changes);
+
+		Ok(())
+	}
+}
+
+impl CliConfiguration for RunContractCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn import_params(&self) -> Option<&ImportParams> {
+		Some(&self.import_params)
+	}
+}
@@ -0,0 +1,264 @@
+//! The `export-ssvm-state` and `import-ssvm-state` subcommands.
+//!
+//! SSVM accounts are exported in an Ethereum like JSON format, one object per account
+//! with its nonce, balance, code and storage, and can be imported into the genesis of
+//! another chain spec to fork the contract state of a chain.
+//!
+//! The accounts are read from and written to the raw storage of `pallet_ssvm`, which
+//! keeps them in the `Accounts`, `AccountCodes` and `AccountStorages` maps of its `SSVM`
+//! storage prefix, all keyed with `blake2_128_concat`.
+
+use std::{collections::BTreeMap, fs, io::Write, path::PathBuf, sync::Arc};
+
+use codec::{Encode, Decode};
+use sc_cli::{BlockNumberOrHash, CliConfiguration, ImportParams, SharedParams};
+use sc_client_api::Backend;
+use sc_service::ChainSpec;
+use serde::{Serialize, Deserialize};
+use sp_blockchain::HeaderBackend;
+use sp_core::{
+	Bytes, H160, H256, Hasher, U256,
+	hashing::{blake2_128, twox_128},
+	storage::Storage,
+};
+use sp_runtime::generic::BlockId;
+use sp_state_machine::Backend as StateBackend;
+use ssvm_node_runtime::{SSVMAccount, Sha3Hasher, opaque::Block};
+use structopt::StructOpt;
+
+#[cfg(test)]
+mod tests;
+
+/// Storage prefix of `pallet_ssvm`.
+const PALLET_PREFIX: &[u8] = b"SSVM";
+/// Map from addresses to nonces and balances.
+const ACCOUNTS: &[u8] = b"Accounts";
+/// Map from addresses to contract codes.
+const ACCOUNT_CODES: &[u8] = b"AccountCodes";
+/// Double map from addresses and storage indices to storage values.
+const ACCOUNT_STORAGES: &[u8] = b"AccountStorages";
+
+/// An SSVM account with its contract, if any.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountState {
+	/// Address of the account.
+	pub address: H160,
+	/// Nonce of the account.
+	pub nonce: U256,
+	/// Balance of the account.
+	pub balance: U256,
+	/// Keccak-256 hash of the code, informative only.
+	pub code_hash: H256,
+	/// Code of the contract, empty for accounts that are not contracts.
+	pub code: Bytes,
+	/// Storage of the contract.
+	pub storage: BTreeMap<H256, H256>,
+}
+
+fn map_prefix(item: &[u8]) -> Vec<u8> {
+	[twox_128(PALLET_PREFIX), twox_128(item)].concat()
+}
+
+fn blake2_128_concat(data: &[u8]) -> Vec<u8> {
+	[&blake2_128(data)[..], data].concat()
+}
+
+fn map_key(item: &[u8], address: &H160) -> Vec<u8> {
+	[map_prefix(item), blake2_128_concat(address.as_bytes())].concat()
+}
+
+fn storage_key(address: &H160, index: &H256) -> Vec<u8> {
+	[map_key(ACCOUNT_STORAGES, address), blake2_128_concat(index.as_bytes())].concat()
+}
+
+/// The address at the end of a `blake2_128_concat` key of a map, right after `prefix`.
+fn address_of_key(prefix: &[u8], key: &[u8]) -> Option<H160> {
+	let address = key.get(prefix.len() + 16..prefix.len() + 16 + 20)?;
+	Some(H160::from_slice(address))
+}
+
+/// Read all the SSVM accounts of `state`, in address order.
+pub fn read_accounts<S: StateBackend<sp_runtime::traits::BlakeTwo256>>(
+	state: &S,
+) -> Result<Vec<AccountState>, String> {
+	let read = |key: &[u8]| state.storage(key).map_err(|e| format!("Unable to read the state: {}", e));
+	let decode_error = |e: codec::Error| format!("Unable to decode the SSVM state: {}", e);
+
+	let mut addresses = Vec::new();
+	for item in &[ACCOUNTS, ACCOUNT_CODES] {
+		let prefix = map_prefix(item);
+		addresses.extend(state.keys(&prefix).iter().filter_map(|key| address_of_key(&prefix, key)));
+	}
+	addresses.sort();
+	addresses.dedup();
+
+	addresses.into_iter().map(|address| {
+		let account = match read(&map_key(ACCOUNTS, &address))? {
+			Some(account) => SSVMAccount::decode(&mut &account[..]).map_err(decode_error)?,
+			None => SSVMAccount { nonce: U256::zero(), balance: U256::zero() },
+		};
+		let code = match read(&map_key(ACCOUNT_CODES, &address))? {
+			Some(code) => Vec::<u8>::decode(&mut &code[..]).map_err(decode_error)?,
+			None => Vec::new(),
+		};
+
+		let prefix = map_key(ACCOUNT_STORAGES, &address);
+		let mut storage = BTreeMap::new();
+		for key in state.keys(&prefix) {
+			let index = key.get(prefix.len() + 16..)
+				.filter(|index| index.len() == 32)
+				.map(H256::from_slice)
+				.ok_or_else(|| "Unexpected SSVM storage key.".to_string())?;
+			if let Some(value) = read(&key)? {
+				storage.insert(index, H256::decode(&mut &value[..]).map_err(decode_error)?);
+			}
+		}
+
+		Ok(AccountState {
+			address,
+			nonce: account.nonce,
+			balance: account.balance,
+			code_hash: Sha3Hasher::hash(&code),
+			code: code.into(),
+			storage,
+		})
+	}).collect()
+}
+
+/// Write `account` into the genesis `storage`, replacing what was there for its address.
+pub fn insert_account(storage: &mut Storage, account: &AccountState) {
+	let top = &mut storage.top;
+	let storage_prefix = map_key(ACCOUNT_STORAGES, &account.address);
+	top.retain(|key, _| !key.starts_with(&storage_prefix));
+
+	let state = SSVMAccount { nonce: account.nonce, balance: account.balance };
+	top.insert(map_key(ACCOUNTS, &account.address), state.encode());
+	if account.code.is_empty() {
+		top.remove(&map_key(ACCOUNT_CODES, &account.address));
+	} else {
+		top.insert(map_key(ACCOUNT_CODES, &account.address), account.code.0.encode());
+	}
+	for (index, value) in &account.storage {
+		top.insert(storage_key(&account.address, index), value.encode());
+	}
+}
+
+/// Parse accounts from a JSON array or from JSON lines.
+pub fn parse_accounts(input: &str) -> Result<Vec<AccountState>, String> {
+	let parse_error = |e: serde_json::Error| format!("Invalid SSVM state: {}", e);
+	if input.trim_start().starts_with('[') {
+		serde_json::from_str(input).map_err(parse_error)
+	} else {
+		input.lines()
+			.filter(|line| !line.trim().is_empty())
+			.map(|line| serde_json::from_str(line).map_err(parse_error))
+			.collect()
+	}
+}
+
+/// The `export-ssvm-state` command used to export the SSVM accounts of a block as JSON.
+#[derive(Debug, StructOpt)]
+pub struct ExportSsvmStateCmd {
+	/// Block hash or number, the best block if not given.
+	#[structopt(value_name = "HASH or NUMBER")]
+	pub input: Option<BlockNumberOrHash>,
+
+	/// File to write the accounts to, the standard output if not given.
+	#[structopt(long, parse(from_os_str))]
+	pub output: Option<PathBuf>,
+
+	/// Write one account per line instead of a JSON array.
+	#[structopt(long)]
+	pub jsonl: bool,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub import_params: ImportParams,
+}
+
+impl ExportSsvmStateCmd {
+	/// Run the export-ssvm-state command.
+	pub fn run<B, C>(&self, client: Arc<C>, backend: Arc<B>) -> sc_cli::Result<()>
+	where
+		B: Backend<Block>,
+		C: HeaderBackend<Block>,
+	{
+		let id = match &self.input {
+			Some(input) => input.parse::<Block>().map_err(sc_cli::Error::Input)?,
+			None => BlockId::Hash(client.info().best_hash),
+		};
+		let state = backend.state_at(id)?;
+		let accounts = read_accounts(&state).map_err(sc_cli::Error::Other)?;
+
+		let json_error = |e: serde_json::Error| sc_cli::Error::Other(e.to_string());
+		let mut json = if self.jsonl {
+			accounts.iter()
+				.map(|account| serde_json::to_string(account).map_err(json_error))
+				.collect::<Result<Vec<_>, _>>()?
+				.join("\n")
+		} else {
+			serde_json::to_string_pretty(&accounts).map_err(json_error)?
+		};
+		json.push('\n');
+
+		match &self.output {
+			Some(path) => fs::write(path, json)?,
+			None => std::io::stdout().write_all(json.as_bytes())?,
+		}
+		eprintln!("Exported {} SSVM accounts.", accounts.len());
+		Ok(())
+	}
+}
+
+impl CliConfiguration for ExportSsvmStateCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn import_params(&self) -> Option<&ImportParams> {
+		Some(&self.import_params)
+	}
+}
+
+/// The `import-ssvm-state` command used to add exported SSVM accounts to the genesis of a
+/// chain spec. The resulting raw chain spec is written to the standard output.
+#[derive(Debug, StructOpt)]
+pub struct ImportSsvmStateCmd {
+	/// File with the accounts, as written by `export-ssvm-state`.
+	#[structopt(parse(from_os_str))]
+	pub accounts: PathBuf,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: SharedParams,
+}
+
+impl ImportSsvmStateCmd {
+	/// Run the import-ssvm-state command.
+	pub fn run(&self, mut spec: Box<dyn ChainSpec>) -> sc_cli::Result<()> {
+		let accounts = parse_accounts(&fs::read_to_string(&self.accounts)?)
+			.map_err(sc_cli::Error::Other)?;
+
+		let mut storage = spec.as_storage_builder().build_storage().map_err(sc_cli::Error::Other)?;
+		for account in &accounts {
+			insert_account(&mut storage, account);
+		}
+		spec.set_storage(storage);
+
+		let json = sc_service::chain_ops::build_spec(&*spec, true)?;
+		std::io::stdout().write_all(json.as_bytes())?;
+		eprintln!("Imported {} SSVM accounts.", accounts.len());
+		Ok(())
+	}
+}
+
+impl CliConfiguration for ImportSsvmStateCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+}
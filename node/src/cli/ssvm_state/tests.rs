@@ -0,0 +1,57 @@
+use super::*;
+use sp_core::sr25519;
+use sp_runtime::{BuildStorage, traits::Dispatchable};
+use ssvm_node_runtime::{Call, Origin};
+use crate::chain_spec::{development_config, get_account_id_from_seed};
+
+/// The ERC20 contract of the tutorials, which mints 1000 tokens to its deployer.
+const ERC20: &[u8] = include_bytes!("../../../../docs/erc20/erc20.wasm");
+
+fn address(hex: &str) -> H160 {
+	hex.trim_start_matches("0x").parse().unwrap()
+}
+
+/// The state of the development chain after Alice deployed the ERC20 contract.
+fn deployed_erc20() -> sp_io::TestExternalities {
+	let storage = development_config().unwrap().build_storage().unwrap();
+	let mut ext = sp_io::TestExternalities::new(storage);
+	ext.execute_with(|| {
+		let alice = get_account_id_from_seed::<sr25519::Public>("Alice");
+		let create = Call::SSVM(pallet_ssvm::Call::create(ERC20.to_vec(), 0.into(), 50_000_000, 0.into()));
+		create.dispatch(Origin::signed(alice)).unwrap();
+	});
+	ext
+}
+
+#[test]
+fn accounts_written_by_the_pallet_are_read() {
+	let accounts = read_accounts(&deployed_erc20().as_backend()).unwrap();
+
+	// Alice is endowed at genesis.
+	let alice = accounts.iter()
+		.find(|account| account.address == address("0x9621dde636de098b43efb0fa9b61facfe328f99d"))
+		.unwrap();
+	assert!(alice.code.is_empty());
+	assert!(!alice.balance.is_zero());
+
+	// The contract holds the 1000 tokens of Alice.
+	let erc20 = accounts.iter()
+		.find(|account| account.address == address("0xe2a313e210a6ec1d5a9c0806545670f2e6264f86"))
+		.unwrap();
+	assert!(!erc20.code.is_empty());
+	assert_eq!(erc20.code_hash, Sha3Hasher::hash(&erc20.code));
+	assert!(erc20.storage.values().any(|value| *value == H256::from_low_u64_be(1000)));
+}
+
+#[test]
+fn imported_accounts_are_read_back() {
+	let accounts = read_accounts(&deployed_erc20().as_backend()).unwrap();
+
+	let mut storage = Storage::default();
+	for account in &accounts {
+		insert_account(&mut storage, account);
+	}
+	let imported = sp_io::TestExternalities::new(storage);
+
+	assert_eq!(read_accounts(&imported.as_backend()).unwrap(), accounts);
+}
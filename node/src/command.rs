@@ -114,6 +114,34 @@ pub fn run() -> sc_cli::Result<()> {
 				Ok((cmd.run(client, backend), task_manager))
 			})
 		},
+		Some(Subcommand::ReplayBlock(cmd)) => {
+			let runner = cli.create_runner(cmd)?;
+			runner.sync_run(|config| {
+				let PartialComponents { client, backend, task_manager, ..}
+					= service::new_partial(&config)?;
+				cmd.run(&config, client, backend, task_manager.spawn_handle())
+			})
+		},
+		Some(Subcommand::RunContract(cmd)) => {
+			let runner = cli.create_runner(cmd)?;
+			runner.sync_run(|config| {
+				let PartialComponents { client, backend, task_manager, ..}
+					= service::new_partial(&config)?;
+				cmd.run(&config, client, backend, task_manager.spawn_handle())
+			})
+		},
+		Some(Subcommand::ExportSsvmState(cmd)) => {
+			let runner = cli.create_runner(cmd)?;
+			runner.sync_run(|config| {
+				let PartialComponents { client, backend, ..} = service::new_partial(&config)?;
+				cmd.run(client, backend)
+			})
+		},
+		Some(Subcommand::ImportSsvmState(cmd)) => {
+			let runner = cli.create_runner(cmd)?;
+			runner.sync_run(|config| cmd.run(config.chain_spec))
+		},
+		Some(Subcommand::Contract(cmd)) => cmd.run(),
 		Some(Subcommand::Benchmark(cmd)) => {
 			if cfg!(feature = "runtime-benchmarks") {
 				let runner = cli.create_runner(cmd)?;
@@ -125,10 +153,15 @@ pub fn run() -> sc_cli::Result<()> {
 			}
 		},
 		None => {
-			let runner = cli.create_runner(&cli.run)?;
+			let runner = cli.create_runner(&cli.run.base)?;
 			runner.run_node_until_exit(|config| match config.role {
 				Role::Light => service::new_light(config),
-				_ => service::new_full(config),
+				_ => service::new_full(config, service::FullOptions {
+					grandpa_observer: cli.run.grandpa_observer,
+					ssvm_metrics: cli.run.ssvm_metrics,
+					indexer_db: cli.run.indexer_db.clone(),
+					explorer: cli.run.explorer_address(),
+				}),
 			})
 		}
 	}
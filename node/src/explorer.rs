@@ -0,0 +1,423 @@
+//! Read-only block explorer REST API, enabled with `--explorer-port`.
+//!
+//! The API serves the database written by the indexer, so `--indexer-db` is required, and
+//! lets explorers show the chain without decoding SCALE themselves. Every endpoint answers
+//! `GET` requests with JSON. Hashes, addresses and binary data are `0x` prefixed hex
+//! strings, accounts are SS58 strings and amounts are decimal strings.
+//!
+//! - `/blocks/latest`, `/blocks/<number>` and `/blocks/<hash>`: a block and its extrinsics.
+//! - `/extrinsics/<hash>`: the most recent extrinsic with this hash, with its events and
+//!   SSVM receipts.
+//! - `/accounts/<account>/extrinsics`: extrinsics signed by an account, with its account or
+//!   with a name resolving to it, most recent first. Extrinsics dispatched on its behalf by
+//!   proxies or multisigs are not listed.
+//! - `/contracts/<address>`: code hash, deployer and creation block of a contract.
+//! - `/tokens/<address>/transfers`: ERC20 transfers of a token made by extrinsics, most
+//!   recent first.
+//!
+//! Lists are paginated with the `limit` (25 by default, at most 100) and `offset` query
+//! parameters.
+//!
+//! `pallet_ssvm` does not deposit the logs of contracts yet, so token transfers are decoded
+//! from the call data of the `transfer` and `transferFrom` calls that succeeded and returned
+//! `true`, or nothing, instead of from `Transfer` logs. The sender of a `transfer` is the SSVM
+//! address of the account the call is dispatched from, which is the signer of the extrinsic
+//! unless it is proxied, derivative or multisig. This is not the whole transfer history of a
+//! token: transfers made by other contracts, by scheduled calls or by extrinsics dispatching
+//! several SSVM calls, whose outputs the indexer cannot tell apart, are not listed, and
+//! neither are mints and burns.
+//!
+//! SQLite blocks the thread it runs on, so requests are answered on the blocking threads of
+//! the runtime rather than on the ones driving the node.
+
+use std::{convert::Infallible, fmt, net::TcpListener, path::PathBuf, sync::Arc};
+
+use hyper::{
+	Body, Method, Request, Response, Server, StatusCode, header::CONTENT_TYPE,
+	service::{make_service_fn, service_fn},
+};
+use rusqlite::{Connection, OpenFlags, OptionalExtension, Row, params};
+use serde_json::{Value as Json, json};
+use sp_core::{H160, U256, bytes::from_hex, crypto::Ss58Codec, hexdisplay::HexDisplay};
+use ssvm_node_runtime::{AccountId, ssvm::address_of};
+
+use crate::abi::{ParamType, Value, decode};
+
+#[cfg(test)]
+mod tests;
+
+/// Selector of ERC20's `transfer(address,uint256)`.
+const TRANSFER: [u8; 4] = [0xa9, 0x05, 0x9c, 0xbb];
+/// Selector of ERC20's `transferFrom(address,address,uint256)`.
+const TRANSFER_FROM: [u8; 4] = [0x23, 0xb8, 0x72, 0xdd];
+/// Size of the input of a `transfer` call: the selector and two words.
+const TRANSFER_SIZE: usize = 4 + 2 * 32;
+/// Size of the input of a `transferFrom` call: the selector and three words.
+const TRANSFER_FROM_SIZE: usize = 4 + 3 * 32;
+
+/// Number of items returned by default by the endpoints returning lists.
+const DEFAULT_LIMIT: u32 = 25;
+/// Maximum number of items returned by the endpoints returning lists.
+const MAX_LIMIT: u32 = 100;
+
+const BLOCK: &str = "SELECT hash, number, parent_hash, state_root, extrinsics_root, finalized FROM blocks";
+const EXTRINSIC: &str = "SELECT e.block_hash, b.number, e.idx, e.hash, e.signer, e.module, e.function, \
+	e.success FROM extrinsics e JOIN blocks b ON b.hash = e.block_hash";
+
+/// Why a request failed.
+#[derive(Debug)]
+pub enum ApiError {
+	/// The request is invalid.
+	BadRequest(String),
+	/// The endpoint or what it was asked for does not exist.
+	NotFound(String),
+	/// Only `GET` requests are served.
+	MethodNotAllowed,
+	/// The database could not be read.
+	Database(rusqlite::Error),
+	/// Answering the request panicked or met data the indexer could not have written.
+	Internal(String),
+}
+
+impl ApiError {
+	fn status(&self) -> StatusCode {
+		match self {
+			ApiError::BadRequest(_) => StatusCode::BAD_REQUEST,
+			ApiError::NotFound(_) => StatusCode::NOT_FOUND,
+			ApiError::MethodNotAllowed => StatusCode::METHOD_NOT_ALLOWED,
+			ApiError::Database(_) | ApiError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+		}
+	}
+}
+
+impl fmt::Display for ApiError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			ApiError::BadRequest(message) | ApiError::NotFound(message) => write!(f, "{}", message),
+			ApiError::MethodNotAllowed => write!(f, "Only GET requests are served"),
+			ApiError::Database(e) => write!(f, "Unable to read the database: {}", e),
+			ApiError::Internal(e) => write!(f, "Unable to answer the request: {}", e),
+		}
+	}
+}
+
+impl From<rusqlite::Error> for ApiError {
+	fn from(e: rusqlite::Error) -> Self {
+		ApiError::Database(e)
+	}
+}
+
+/// Serve the API on `listener` from the indexer database at `database`.
+pub async fn run(listener: TcpListener, database: PathBuf) {
+	let database = Arc::new(database);
+	let service = make_service_fn(move |_| {
+		let database = database.clone();
+		async move {
+			Ok::<_, Infallible>(service_fn(move |request| serve(database.clone(), request)))
+		}
+	});
+
+	let result = match Server::from_tcp(listener) {
+		Ok(server) => server.serve(service).await,
+		Err(e) => Err(e),
+	};
+	if let Err(e) = result {
+		log::error!(target: "explorer", "Explorer API stopped: {}", e);
+	}
+}
+
+async fn serve(database: Arc<PathBuf>, request: Request<Body>) -> Result<Response<Body>, Infallible> {
+	let result = if request.method() == Method::GET {
+		let (path, query) = (request.uri().path().to_owned(), request.uri().query().map(String::from));
+		tokio::task::spawn_blocking(move || {
+			Connection::open_with_flags(&*database, OpenFlags::SQLITE_OPEN_READ_ONLY)
+				.map_err(ApiError::from)
+				.and_then(|connection| route(&connection, &path, query.as_deref()))
+		})
+			.await
+			.unwrap_or_else(|e| Err(ApiError::Internal(e.to_string())))
+	} else {
+		Err(ApiError::MethodNotAllowed)
+	};
+	let (status, body) = match result {
+		Ok(body) => (StatusCode::OK, body),
+		Err(e) => (e.status(), json!({ "error": e.to_string() })),
+	};
+
+	Ok(Response::builder()
+		.status(status)
+		.header(CONTENT_TYPE, "application/json")
+		.body(Body::from(body.to_string()))
+		.expect("The status and the header are valid; qed"))
+}
+
+/// Answer a request for `path` with the given query string.
+pub fn route(connection: &Connection, path: &str, query: Option<&str>) -> Result<Json, ApiError> {
+	let page = Page::parse(query)?;
+	let segments = path.trim_matches('/').split('/').collect::<Vec<_>>();
+	match segments.as_slice() {
+		["blocks", id] => block(connection, id),
+		["extrinsics", hash] => extrinsic(connection, hash),
+		["accounts", account, "extrinsics"] => account_extrinsics(connection, account, page),
+		["contracts", address] => contract(connection, address),
+		["tokens", address, "transfers"] => token_transfers(connection, address, page),
+		_ => Err(ApiError::NotFound("Unknown endpoint".into())),
+	}
+}
+
+/// Pagination of the endpoints returning lists.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Page {
+	limit: u32,
+	offset: u32,
+}
+
+impl Page {
+	fn parse(query: Option<&str>) -> Result<Self, ApiError> {
+		let mut page = Page { limit: DEFAULT_LIMIT, offset: 0 };
+		let pairs = query.unwrap_or_default().split('&').filter(|pair| !pair.is_empty());
+		for pair in pairs {
+			let mut parts = pair.splitn(2, '=');
+			let (name, value) = (parts.next().unwrap_or_default(), parts.next().unwrap_or_default());
+			let value = || value.parse::<u32>()
+				.map_err(|_| ApiError::BadRequest(format!("Invalid {}", name)));
+			match name {
+				"limit" => page.limit = value()?.min(MAX_LIMIT),
+				"offset" => page.offset = value()?,
+				_ => {}
+			}
+		}
+		Ok(page)
+	}
+}
+
+fn parse_hash(hash: &str) -> Result<String, ApiError> {
+	let valid = hash.len() == 66 && hash.starts_with("0x") && hash[2..].chars().all(|c| c.is_ascii_hexdigit());
+	if valid {
+		Ok(hash.to_lowercase())
+	} else {
+		Err(ApiError::BadRequest("Invalid hash".into()))
+	}
+}
+
+fn parse_address(address: &str) -> Result<H160, ApiError> {
+	address.trim_start_matches("0x").parse().map_err(|_| ApiError::BadRequest("Invalid address".into()))
+}
+
+fn parse_account(account: &str) -> Result<AccountId, ApiError> {
+	AccountId::from_ss58check(account).map_err(|_| ApiError::BadRequest("Invalid account".into()))
+}
+
+fn block_json(row: &Row) -> rusqlite::Result<Json> {
+	Ok(json!({
+		"hash": row.get::<_, String>(0)?,
+		"number": row.get::<_, i64>(1)?,
+		"parentHash": row.get::<_, String>(2)?,
+		"stateRoot": row.get::<_, String>(3)?,
+		"extrinsicsRoot": row.get::<_, String>(4)?,
+		"finalized": row.get::<_, bool>(5)?,
+	}))
+}
+
+/// Summary of an extrinsic selected with `EXTRINSIC`.
+fn extrinsic_json(row: &Row) -> rusqlite::Result<Json> {
+	Ok(json!({
+		"blockHash": row.get::<_, String>(0)?,
+		"blockNumber": row.get::<_, i64>(1)?,
+		"index": row.get::<_, i64>(2)?,
+		"hash": row.get::<_, String>(3)?,
+		"signer": row.get::<_, Option<String>>(4)?,
+		"module": row.get::<_, String>(5)?,
+		"function": row.get::<_, String>(6)?,
+		"success": row.get::<_, bool>(7)?,
+	}))
+}
+
+fn block(connection: &Connection, id: &str) -> Result<Json, ApiError> {
+	let block = if id == "latest" {
+		connection.query_row(&format!("{} ORDER BY number DESC LIMIT 1", BLOCK), params![], block_json)
+	} else if id.starts_with("0x") {
+		connection.query_row(&format!("{} WHERE hash = ?1", BLOCK), params![parse_hash(id)?], block_json)
+	} else {
+		let number = id.parse::<i64>()
+			.map_err(|_| ApiError::BadRequest("Invalid block number or hash".into()))?;
+		connection.query_row(&format!("{} WHERE number = ?1", BLOCK), params![number], block_json)
+	};
+	let mut block = block.optional()?.ok_or_else(|| ApiError::NotFound("Block not found".into()))?;
+
+	let extrinsics = connection
+		.prepare(&format!("{} WHERE e.block_hash = ?1 ORDER BY e.idx", EXTRINSIC))?
+		.query_map(params![block["hash"].as_str()], extrinsic_json)?
+		.collect::<rusqlite::Result<Vec<_>>>()?;
+	block["extrinsics"] = Json::Array(extrinsics);
+	Ok(block)
+}
+
+fn extrinsic(connection: &Connection, hash: &str) -> Result<Json, ApiError> {
+	let mut extrinsic = connection
+		.query_row(
+			"SELECT e.block_hash, b.number, e.idx, e.hash, e.signer, e.module, e.function, e.success, \
+				e.call, e.error FROM extrinsics e JOIN blocks b ON b.hash = e.block_hash \
+				WHERE e.hash = ?1 ORDER BY b.number DESC LIMIT 1",
+			params![parse_hash(hash)?],
+			|row| {
+				let mut extrinsic = extrinsic_json(row)?;
+				extrinsic["call"] = row.get::<_, String>(8)?.into();
+				extrinsic["error"] = row.get::<_, Option<String>>(9)?.into();
+				Ok(extrinsic)
+			},
+		)
+		.optional()?
+		.ok_or_else(|| ApiError::NotFound("Extrinsic not found".into()))?;
+	let (block_hash, index) = (extrinsic["blockHash"].as_str().map(String::from), extrinsic["index"].as_i64());
+
+	let events = connection
+		.prepare(
+			"SELECT pallet_index, event_index, event FROM events WHERE block_hash = ?1 \
+				AND extrinsic_idx = ?2 ORDER BY idx",
+		)?
+		.query_map(params![block_hash, index], |row| Ok(json!({
+			"palletIndex": row.get::<_, i64>(0)?,
+			"eventIndex": row.get::<_, i64>(1)?,
+			"event": row.get::<_, String>(2)?,
+		})))?
+		.collect::<rusqlite::Result<Vec<_>>>()?;
+	let receipts = connection
+		.prepare(
+			"SELECT kind, contract, input, value, gas_limit, gas_price, success, output, revert_reason \
+				FROM receipts WHERE block_hash = ?1 AND extrinsic_idx = ?2 ORDER BY idx",
+		)?
+		.query_map(params![block_hash, index], |row| Ok(json!({
+			"kind": row.get::<_, String>(0)?,
+			"contract": row.get::<_, Option<String>>(1)?,
+			"input": row.get::<_, String>(2)?,
+			"value": row.get::<_, String>(3)?,
+			"gasLimit": row.get::<_, i64>(4)?,
+			"gasPrice": row.get::<_, String>(5)?,
+			"success": row.get::<_, bool>(6)?,
+			"output": row.get::<_, Option<String>>(7)?,
+			"revertReason": row.get::<_, Option<String>>(8)?,
+		})))?
+		.collect::<rusqlite::Result<Vec<_>>>()?;
+
+	extrinsic["events"] = Json::Array(events);
+	extrinsic["receipts"] = Json::Array(receipts);
+	Ok(extrinsic)
+}
+
+fn account_extrinsics(connection: &Connection, account: &str, page: Page) -> Result<Json, ApiError> {
+	let account = parse_account(account)?.to_ss58check();
+	let extrinsics = connection
+		.prepare(&format!(
+			"{} WHERE e.signer = ?1 ORDER BY b.number DESC, e.idx DESC LIMIT ?2 OFFSET ?3",
+			EXTRINSIC,
+		))?
+		.query_map(params![account, page.limit, page.offset], extrinsic_json)?
+		.collect::<rusqlite::Result<Vec<_>>>()?;
+	Ok(json!({ "account": account, "extrinsics": extrinsics }))
+}
+
+fn contract(connection: &Connection, address: &str) -> Result<Json, ApiError> {
+	let address = format!("{:?}", parse_address(address)?);
+	let mut contract = connection
+		.query_row(
+			"SELECT address, code_hash, deployer, block_hash, block_number, extrinsic_idx \
+				FROM contracts WHERE address = ?1",
+			params![address],
+			|row| Ok(json!({
+				"address": row.get::<_, String>(0)?,
+				"codeHash": row.get::<_, String>(1)?,
+				"deployer": row.get::<_, Option<String>>(2)?,
+				"creationBlockHash": row.get::<_, String>(3)?,
+				"creationBlockNumber": row.get::<_, i64>(4)?,
+				"creationExtrinsicIndex": row.get::<_, i64>(5)?,
+			})),
+		)
+		.optional()?
+		.ok_or_else(|| ApiError::NotFound("Contract not found".into()))?;
+	let calls = connection.query_row(
+		"SELECT COUNT(*) FROM receipts WHERE contract = ?1 AND kind = 'call'",
+		params![address],
+		|row| row.get::<_, i64>(0),
+	)?;
+	contract["calls"] = calls.into();
+	Ok(contract)
+}
+
+fn token_transfers(connection: &Connection, address: &str, page: Page) -> Result<Json, ApiError> {
+	let token = format!("{:?}", parse_address(address)?);
+	// Transfers are selected in full before being paginated: the input must decode, which
+	// takes a caller for `transfer`, and the output must be empty or decode to `true`, that
+	// is a first word that is not zero.
+	let transfers = connection
+		.prepare(
+			"SELECT r.block_hash, b.number, r.extrinsic_idx, r.caller, r.input FROM receipts r \
+				JOIN blocks b ON b.hash = r.block_hash \
+				WHERE r.contract = ?1 AND r.kind = 'call' AND r.success = 1 \
+					AND ((r.input LIKE ?2 AND length(r.input) >= ?3 AND r.caller IS NOT NULL) \
+						OR (r.input LIKE ?4 AND length(r.input) >= ?5)) \
+					AND (r.output = '0x' OR (length(r.output) >= 66 AND substr(r.output, 3, 64) != ?6)) \
+				ORDER BY b.number DESC, r.extrinsic_idx DESC, r.idx DESC LIMIT ?7 OFFSET ?8",
+		)?
+		.query_map(
+			params![
+				token,
+				format!("0x{}%", hex(&TRANSFER)),
+				(2 + 2 * TRANSFER_SIZE) as i64,
+				format!("0x{}%", hex(&TRANSFER_FROM)),
+				(2 + 2 * TRANSFER_FROM_SIZE) as i64,
+				"0".repeat(64),
+				page.limit,
+				page.offset,
+			],
+			|row| Ok((
+				row.get::<_, String>(0)?,
+				row.get::<_, i64>(1)?,
+				row.get::<_, i64>(2)?,
+				row.get::<_, Option<String>>(3)?,
+				row.get::<_, String>(4)?,
+			)),
+		)?
+		.collect::<rusqlite::Result<Vec<_>>>()?
+		.into_iter()
+		.map(|(block_hash, block_number, index, caller, input)| {
+			let (from, to, value) = from_hex(&input).ok()
+				.and_then(|input| decode_transfer(caller.as_deref(), &input))
+				.ok_or_else(|| ApiError::Internal(format!("Undecodable transfer in block {}", block_hash)))?;
+			Ok(json!({
+				"blockHash": block_hash,
+				"blockNumber": block_number,
+				"extrinsicIndex": index,
+				"from": format!("{:?}", from),
+				"to": format!("{:?}", to),
+				"value": value.to_string(),
+			}))
+		})
+		.collect::<Result<Vec<_>, ApiError>>()?;
+	Ok(json!({ "token": token, "transfers": transfers }))
+}
+
+/// Sender, recipient and amount of an ERC20 `transfer` or `transferFrom` call dispatched
+/// from the account `caller`.
+fn decode_transfer(caller: Option<&str>, input: &[u8]) -> Option<(H160, H160, U256)> {
+	let (selector, data) = (input.get(..4)?, input.get(4..)?);
+	if selector == TRANSFER {
+		let from = address_of(&AccountId::from_ss58check(caller?).ok()?);
+		match decode(&[ParamType::Address, ParamType::Uint(256)], data).ok()?.as_slice() {
+			[Value::Address(to), Value::Uint(value)] => Some((from, *to, *value)),
+			_ => None,
+		}
+	} else if selector == TRANSFER_FROM {
+		let types = [ParamType::Address, ParamType::Address, ParamType::Uint(256)];
+		match decode(&types, data).ok()?.as_slice() {
+			[Value::Address(from), Value::Address(to), Value::Uint(value)] => Some((*from, *to, *value)),
+			_ => None,
+		}
+	} else {
+		None
+	}
+}
+
+fn hex(data: &[u8]) -> String {
+	format!("{}", HexDisplay::from(&data))
+}
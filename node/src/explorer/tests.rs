@@ -0,0 +1,187 @@
+use super::*;
+
+const TOKEN: &str = "0x0101010101010101010101010101010101010101";
+const BOB: &str = "0x41dccbd49b26c50d34355ed86ff0fa9e489d1e01";
+const CHARLIE: &str = "0x9621dde636de098b43efb0fa9b61facfe328f99d";
+
+fn block_hash(number: i64) -> String {
+	format!("0x{:064x}", number)
+}
+
+fn signer() -> AccountId {
+	AccountId::from([1; 32])
+}
+
+fn word(hex: &str) -> String {
+	format!("{:0>64}", hex.trim_start_matches("0x"))
+}
+
+fn transfer(to: &str, value: u64) -> String {
+	format!("0xa9059cbb{}{}", word(to), word(&format!("{:x}", value)))
+}
+
+fn transfer_from(from: &str, to: &str, value: u64) -> String {
+	format!("0x23b872dd{}{}{}", word(from), word(to), word(&format!("{:x}", value)))
+}
+
+/// Output of a call returning `value`.
+fn returned(value: bool) -> String {
+	format!("0x{}", word(if value { "1" } else { "0" }))
+}
+
+/// A database with a block per call to `token`, each with the given input and success.
+/// Successful calls return `true`.
+fn database(calls: &[(String, bool)]) -> Connection {
+	let connection = Connection::open_in_memory().unwrap();
+	connection.execute_batch(crate::indexer::SCHEMA).unwrap();
+	for (number, (input, success)) in calls.iter().enumerate() {
+		let (number, hash) = (number as i64, block_hash(number as i64));
+		connection.execute(
+			"INSERT INTO blocks (hash, number, parent_hash, state_root, extrinsics_root, \
+				extrinsic_count, finalized) VALUES (?1, ?2, '0x', '0x', '0x', 1, 1)",
+			params![hash, number],
+		).unwrap();
+		connection.execute(
+			"INSERT INTO extrinsics (block_hash, idx, hash, signer, module, function, call, success) \
+				VALUES (?1, 0, ?1, ?2, 'SSVM', 'call', 'SSVM(call(..))', ?3)",
+			params![hash, signer().to_ss58check(), success],
+		).unwrap();
+		connection.execute(
+			"INSERT INTO receipts (block_hash, extrinsic_idx, idx, kind, sender, caller, contract, input, \
+				value, gas_limit, gas_price, success, output) \
+				VALUES (?1, 0, 0, 'call', ?2, ?2, ?3, ?4, '0', 100000, '1', ?5, ?6)",
+			params![hash, signer().to_ss58check(), TOKEN, input, success, Some(returned(true)).filter(|_| *success)],
+		).unwrap();
+	}
+	connection
+}
+
+#[test]
+fn blocks_are_served_with_their_extrinsics() {
+	let connection = database(&[(transfer(BOB, 1), true), (transfer(BOB, 2), true)]);
+
+	let latest = route(&connection, "/blocks/latest", None).unwrap();
+	assert_eq!(latest["number"], 1);
+	assert_eq!(latest["extrinsics"][0]["module"], "SSVM");
+	assert_eq!(route(&connection, "/blocks/0", None).unwrap()["hash"], block_hash(0));
+	assert_eq!(route(&connection, &format!("/blocks/{}", block_hash(1)), None).unwrap()["number"], 1);
+	assert!(matches!(route(&connection, "/blocks/2", None), Err(ApiError::NotFound(_))));
+	assert!(matches!(route(&connection, "/blocks/0xzz", None), Err(ApiError::BadRequest(_))));
+}
+
+#[test]
+fn extrinsics_are_served_with_their_receipts() {
+	let connection = database(&[(transfer(BOB, 1), false)]);
+
+	let extrinsic = route(&connection, &format!("/extrinsics/{}", block_hash(0)), None).unwrap();
+	assert_eq!(extrinsic["success"], false);
+	assert_eq!(extrinsic["receipts"][0]["contract"], TOKEN);
+	assert_eq!(extrinsic["receipts"][0]["gasLimit"], 100_000);
+}
+
+#[test]
+fn account_history_is_paginated() {
+	let connection = database(&[(transfer(BOB, 1), true), (transfer(BOB, 2), true), (transfer(BOB, 3), true)]);
+	let path = format!("/accounts/{}/extrinsics", signer().to_ss58check());
+
+	let history = route(&connection, &path, Some("limit=2&offset=1")).unwrap();
+	let numbers = history["extrinsics"].as_array().unwrap().iter()
+		.map(|extrinsic| extrinsic["blockNumber"].as_i64().unwrap())
+		.collect::<Vec<_>>();
+	assert_eq!(numbers, vec![1, 0]);
+	assert!(matches!(route(&connection, &path, Some("limit=many")), Err(ApiError::BadRequest(_))));
+	assert!(matches!(route(&connection, "/accounts/nobody/extrinsics", None), Err(ApiError::BadRequest(_))));
+}
+
+#[test]
+fn contracts_are_served_with_their_creation() {
+	let connection = database(&[(transfer(BOB, 1), true)]);
+	connection.execute(
+		"INSERT INTO contracts (address, block_hash, block_number, extrinsic_idx, deployer, code_hash) \
+			VALUES (?1, ?2, 0, 0, ?3, '0xc0de')",
+		params![TOKEN, block_hash(0), signer().to_ss58check()],
+	).unwrap();
+
+	let contract = route(&connection, &format!("/contracts/{}", TOKEN), None).unwrap();
+	assert_eq!(contract["codeHash"], "0xc0de");
+	assert_eq!(contract["deployer"], signer().to_ss58check());
+	assert_eq!(contract["creationBlockNumber"], 0);
+	assert_eq!(contract["calls"], 1);
+	assert!(matches!(route(&connection, &format!("/contracts/{}", BOB), None), Err(ApiError::NotFound(_))));
+}
+
+#[test]
+fn token_transfers_are_decoded_from_successful_calls() {
+	let connection = database(&[
+		(transfer(BOB, 10), true),
+		(transfer_from(BOB, CHARLIE, 20), true),
+		(transfer(CHARLIE, 30), false),
+		("0x095ea7b3".into(), true),
+	]);
+
+	let transfers = route(&connection, &format!("/tokens/{}/transfers", TOKEN), None).unwrap();
+	assert_eq!(transfers["transfers"], json!([
+		{
+			"blockHash": block_hash(1),
+			"blockNumber": 1,
+			"extrinsicIndex": 0,
+			"from": BOB,
+			"to": CHARLIE,
+			"value": "20",
+		},
+		{
+			"blockHash": block_hash(0),
+			"blockNumber": 0,
+			"extrinsicIndex": 0,
+			"from": format!("{:?}", address_of(&signer())),
+			"to": BOB,
+			"value": "10",
+		},
+	]));
+}
+
+#[test]
+fn only_transfers_returning_true_are_listed_and_paginated() {
+	let connection = database(&[
+		(transfer(BOB, 10), true),
+		(transfer(BOB, 20), true),
+		("0xa9059cbb".into(), true),
+		(transfer(BOB, 30), true),
+		(transfer(BOB, 40), true),
+		(transfer(BOB, 50), true),
+	]);
+	let set_output = |number: i64, output: Option<String>| connection.execute(
+		"UPDATE receipts SET output = ?1 WHERE block_hash = ?2",
+		params![output, block_hash(number)],
+	).unwrap();
+	// Tokens may return nothing, but not `false`, and the output of calls batched with other
+	// SSVM calls is not known.
+	set_output(0, Some("0x".into()));
+	set_output(1, Some(returned(false)));
+	set_output(4, None);
+
+	let values = |query| route(&connection, &format!("/tokens/{}/transfers", TOKEN), Some(query)).unwrap()
+		["transfers"].as_array().unwrap().iter()
+		.map(|transfer| transfer["value"].as_str().unwrap().to_owned())
+		.collect::<Vec<_>>();
+	assert_eq!(values("limit=2"), vec!["50", "30"]);
+	assert_eq!(values("limit=2&offset=2"), vec!["10"]);
+}
+
+#[test]
+fn transfers_are_sent_from_the_account_calls_are_dispatched_from() {
+	let connection = database(&[(transfer(BOB, 10), true)]);
+	// The signer called the token through a proxy of another account.
+	let proxied = AccountId::from([2; 32]);
+	connection.execute("UPDATE receipts SET caller = ?1", params![proxied.to_ss58check()]).unwrap();
+
+	let transfers = route(&connection, &format!("/tokens/{}/transfers", TOKEN), None).unwrap();
+	assert_eq!(transfers["transfers"][0]["from"], format!("{:?}", address_of(&proxied)));
+}
+
+#[test]
+fn unknown_endpoints_are_not_found() {
+	let connection = database(&[]);
+	assert!(matches!(route(&connection, "/", None), Err(ApiError::NotFound(_))));
+	assert!(matches!(route(&connection, "/blocks", None), Err(ApiError::NotFound(_))));
+}
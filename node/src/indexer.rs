@@ -0,0 +1,423 @@
+//! Indexer writing the best chain into a SQLite database, enabled with `--indexer-db`.
+//!
+//! Blocks, their extrinsics, `System` events, SSVM receipts and the contracts they create
+//! are written as the best chain grows, following the schema documented in `schema.sql`.
+//! When a reorg retracts blocks that are not finalized yet, their rows are deleted before
+//! the blocks of the new best chain are written. The database catches up with the chain
+//! when the node starts, in batches of `BATCH` blocks, so it can be enabled on a node that
+//! already synced.
+//!
+//! The events and the contract deployers are read from the state of each block, and the
+//! blocks signed with names are executed again to resolve them, so the indexer only runs on
+//! archive nodes: the state of the blocks it catches up with would be pruned otherwise.
+
+use std::{error::Error, path::Path, sync::Arc};
+
+use codec::{Encode, Decode};
+use futures::{future, StreamExt};
+use rusqlite::{Connection, OptionalExtension, Transaction, params};
+use sc_client_api::{Backend, BlockBackend, BlockchainEvents, StorageProvider};
+use sp_api::ProvideRuntimeApi;
+use sp_block_builder::BlockBuilder;
+use sp_blockchain::HeaderBackend;
+use sp_core::{
+	crypto::Ss58Codec, hashing::{blake2_128, blake2_256, twox_128}, hexdisplay::HexDisplay,
+	storage::StorageKey,
+};
+use sp_runtime::{generic::BlockId, traits::{Block as BlockT, Header as HeaderT}};
+use ssvm_node_runtime::{
+	AccountId, Address, Event, GetCallMetadata, Hash, UncheckedExtrinsic, apis::ReplayApi,
+	opaque::{Block, Header}, ssvm::{SsvmOutcome, ssvm_calls, ssvm_calls_from, ssvm_outcomes},
+};
+
+#[cfg(test)]
+mod tests;
+
+/// Schema of the database, creating the tables that do not exist yet.
+pub(crate) const SCHEMA: &str = include_str!("indexer/schema.sql");
+
+/// Number of blocks written per database transaction.
+const BATCH: u32 = 256;
+
+type Result<T> = std::result::Result<T, Box<dyn Error>>;
+type EventRecord = frame_system::EventRecord<Event, Hash>;
+
+/// Notifications the indexer follows.
+enum Notification {
+	/// A new best block was imported.
+	Imported(Hash),
+	/// A block was finalized.
+	Finalized(Hash),
+}
+
+/// Indexer of the best chain.
+pub struct Indexer {
+	connection: Connection,
+}
+
+impl Indexer {
+	/// Open the database at `path`, creating it and its tables if needed.
+	pub fn open(path: &Path) -> Result<Self> {
+		let connection = Connection::open(path)?;
+		// Readers such as the explorer API do not block the indexer in write-ahead logging mode.
+		connection.execute_batch("PRAGMA journal_mode = WAL; PRAGMA foreign_keys = ON;")?;
+		connection.execute_batch(SCHEMA)?;
+		Ok(Indexer { connection })
+	}
+
+	/// Catch up with the chain of `client`, then follow its best and finalized blocks.
+	pub async fn run<B, C>(mut self, client: Arc<C>)
+	where
+		B: Backend<Block>,
+		C: BlockchainEvents<Block> + BlockBackend<Block> + HeaderBackend<Block>,
+		C: StorageProvider<Block, B> + ProvideRuntimeApi<Block>,
+		C::Api: BlockBuilder<Block> + ReplayApi<Block>,
+	{
+		let info = client.info();
+		let notifications = futures::stream::iter(vec![
+			Notification::Imported(info.best_hash),
+			Notification::Finalized(info.finalized_hash),
+		]);
+		let imports = client.import_notification_stream()
+			.filter(|notification| future::ready(notification.is_new_best))
+			.map(|notification| Notification::Imported(notification.hash));
+		let finality = client.finality_notification_stream()
+			.map(|notification| Notification::Finalized(notification.hash));
+
+		notifications.chain(futures::stream::select(imports, finality))
+			.for_each(move |notification| {
+				let result = match notification {
+					Notification::Imported(hash) => self.follow(&*client, hash),
+					Notification::Finalized(hash) => self.finalize(&*client, hash),
+				};
+				if let Err(e) = result {
+					log::error!(target: "indexer", "Unable to index the chain: {}", e);
+				}
+				future::ready(())
+			})
+			.await
+	}
+
+	/// Make `best` the head of the indexed chain.
+	fn follow<B, C>(&mut self, client: &C, best: Hash) -> Result<()>
+	where
+		B: Backend<Block>,
+		C: BlockBackend<Block> + HeaderBackend<Block> + StorageProvider<Block, B> + ProvideRuntimeApi<Block>,
+		C::Api: BlockBuilder<Block> + ReplayApi<Block>,
+	{
+		let best = *client.header(BlockId::hash(best))?.ok_or("Unknown block")?.number();
+		let ancestor = common_ancestor(&self.connection, best, |number| client.hash(number))?;
+		let transaction = self.connection.transaction()?;
+		retract_above(&transaction, ancestor.map(i64::from))?;
+		transaction.commit()?;
+
+		// Blocks are looked up by number on the best chain, so that catching up with a long
+		// chain holds neither all its headers nor a single transaction for all of it.
+		let finalized = client.info().finalized_number;
+		let mut next = ancestor.map_or(0, |number| number + 1);
+		while next <= best {
+			let last = best.min(next.saturating_add(BATCH - 1));
+			let transaction = self.connection.transaction()?;
+			for number in next..=last {
+				let hash = client.hash(number)?.ok_or("Unknown block")?;
+				let header = client.header(BlockId::hash(hash))?.ok_or("Unknown block")?;
+				index_block(&transaction, client, header, finalized)?;
+			}
+			transaction.commit()?;
+			next = last + 1;
+		}
+		Ok(())
+	}
+
+	/// Mark the indexed blocks up to `finalized` as finalized.
+	fn finalize<C: HeaderBackend<Block>>(&mut self, client: &C, finalized: Hash) -> Result<()> {
+		let header = client.header(BlockId::hash(finalized))?.ok_or("Unknown block")?;
+		self.connection.execute(
+			"UPDATE blocks SET finalized = 1 WHERE number <= ?1 AND finalized = 0",
+			params![i64::from(*header.number())],
+		)?;
+		Ok(())
+	}
+}
+
+/// Delete the indexed blocks above the common ancestor of a reorg, with everything they
+/// contain. Finalized blocks cannot be retracted.
+fn retract_above(transaction: &Transaction, ancestor: Option<i64>) -> Result<()> {
+	transaction.execute(
+		"DELETE FROM blocks WHERE number > ?1 AND finalized = 0",
+		params![ancestor.unwrap_or(-1)],
+	)?;
+	Ok(())
+}
+
+/// Last indexed block, at most `best`, that is part of the best chain whose block hashes
+/// are given by `canonical`. Reorgs are short, so few blocks are compared.
+fn common_ancestor<F>(connection: &Connection, best: u32, canonical: F) -> Result<Option<u32>>
+where
+	F: Fn(u32) -> sp_blockchain::Result<Option<Hash>>,
+{
+	let head: Option<i64> = connection.query_row("SELECT MAX(number) FROM blocks", params![], |row| row.get(0))?;
+	let mut ancestor = head.map(|head| (head as u32).min(best));
+	while let Some(number) = ancestor {
+		let indexed: Option<String> = connection
+			.query_row("SELECT hash FROM blocks WHERE number = ?1", params![i64::from(number)], |row| row.get(0))
+			.optional()?;
+		if indexed.is_some() && indexed == canonical(number)?.map(|hash| format!("{:?}", hash)) {
+			break;
+		}
+		ancestor = number.checked_sub(1);
+	}
+	Ok(ancestor)
+}
+
+/// Write a block and everything it contains.
+fn index_block<B, C>(
+	transaction: &Transaction,
+	client: &C,
+	header: Header,
+	finalized: u32,
+) -> Result<()>
+where
+	B: Backend<Block>,
+	C: BlockBackend<Block> + StorageProvider<Block, B> + ProvideRuntimeApi<Block>,
+	C::Api: BlockBuilder<Block> + ReplayApi<Block>,
+{
+	let hash = header.hash();
+	let block_hash = format!("{:?}", hash);
+	let id = BlockId::hash(hash);
+	let body = client.block_body(&id)?.ok_or("Unknown block")?;
+	let extrinsics = body.iter()
+		.map(|extrinsic| UncheckedExtrinsic::decode(&mut &extrinsic.encode()[..]))
+		.collect::<std::result::Result<Vec<_>, _>>()?;
+	let events = match client.storage(&id, &storage_key(&[b"System", b"Events"], &[]))? {
+		Some(events) => Vec::<EventRecord>::decode(&mut &events.0[..])?,
+		None => Vec::new(),
+	};
+	let signers = signers(client, &header, body, &extrinsics)?;
+
+	transaction.execute(
+		"INSERT INTO blocks (hash, number, parent_hash, state_root, extrinsics_root, \
+			extrinsic_count, finalized) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+		params![
+			block_hash,
+			i64::from(*header.number()),
+			format!("{:?}", header.parent_hash()),
+			format!("{:?}", header.state_root()),
+			format!("{:?}", header.extrinsics_root()),
+			extrinsics.len() as i64,
+			*header.number() <= finalized,
+		],
+	)?;
+
+	for (index, record) in events.iter().enumerate() {
+		let extrinsic_index = match record.phase {
+			frame_system::Phase::ApplyExtrinsic(index) => Some(index),
+			_ => None,
+		};
+		// Events are encoded as the index of their pallet followed by the index of the event.
+		let encoded = record.event.encode();
+		transaction.execute(
+			"INSERT INTO events (block_hash, idx, extrinsic_idx, pallet_index, event_index, event, encoded) \
+				VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+			params![
+				block_hash,
+				index as i64,
+				extrinsic_index,
+				encoded[0],
+				encoded[1],
+				format!("{:?}", record.event),
+				hex(&encoded),
+			],
+		)?;
+	}
+
+	for (index, (extrinsic, signer)) in extrinsics.iter().zip(signers).enumerate() {
+		let events = events.iter()
+			.filter(|record| record.phase == frame_system::Phase::ApplyExtrinsic(index as u32))
+			.map(|record| record.event.clone())
+			.collect::<Vec<_>>();
+		let error = events.iter().find_map(|event| match event {
+			Event::frame_system(frame_system::Event::ExtrinsicFailed(error, _)) => Some(format!("{:?}", error)),
+			_ => None,
+		});
+		let call = extrinsic.function.get_call_metadata();
+		transaction.execute(
+			"INSERT INTO extrinsics (block_hash, idx, hash, signer, module, function, call, success, error) \
+				VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+			params![
+				block_hash,
+				index as i64,
+				hex(&blake2_256(&extrinsic.encode())),
+				signer.as_ref().map(Ss58Codec::to_ss58check),
+				call.pallet_name,
+				call.function_name,
+				format!("{:?}", extrinsic.function),
+				error.is_none(),
+				error,
+			],
+		)?;
+
+		index_receipts(transaction, client, &header, index, extrinsic, signer.as_ref(), error.is_some(), &events)?;
+	}
+	Ok(())
+}
+
+/// The accounts that signed `extrinsics`, whose encoding is `body`.
+///
+/// Extrinsics signed with a name are dispatched from the account the name resolved to when
+/// they were applied, which may have changed since, so the block is executed again to
+/// resolve them. Blocks whose extrinsics are all signed with accounts are not.
+fn signers<C>(
+	client: &C,
+	header: &Header,
+	body: Vec<<Block as BlockT>::Extrinsic>,
+	extrinsics: &[UncheckedExtrinsic],
+) -> Result<Vec<Option<AccountId>>>
+where
+	C: ProvideRuntimeApi<Block>,
+	C::Api: BlockBuilder<Block> + ReplayApi<Block>,
+{
+	let named = extrinsics.iter().any(|extrinsic| match &extrinsic.signature {
+		Some((Address::Id(_), _, _)) | None => false,
+		Some(_) => true,
+	});
+	if !named {
+		return Ok(extrinsics.iter()
+			.map(|extrinsic| match &extrinsic.signature {
+				Some((Address::Id(account), _, _)) => Some(account.clone()),
+				_ => None,
+			})
+			.collect());
+	}
+
+	let parent = BlockId::hash(*header.parent_hash());
+	let api = client.runtime_api();
+	api.initialize_block(&parent, header).map_err(|e| e.to_string())?;
+	body.into_iter()
+		.map(|extrinsic| -> Result<_> {
+			Ok(api.replay_extrinsic(&parent, extrinsic).map_err(|e| e.to_string())?.signer)
+		})
+		.collect()
+}
+
+/// Write the SSVM executions dispatched by an extrinsic and the contracts they created.
+///
+/// The executions that were not dispatched, such as the ones after the interrupted call of a
+/// batch or the call of a multisig approval below the threshold, are left out.
+#[allow(clippy::too_many_arguments)]
+fn index_receipts<B, C>(
+	transaction: &Transaction,
+	client: &C,
+	header: &Header,
+	index: usize,
+	extrinsic: &UncheckedExtrinsic,
+	sender: Option<&AccountId>,
+	failed: bool,
+	events: &[Event],
+) -> Result<()>
+where
+	B: Backend<Block>,
+	C: StorageProvider<Block, B>,
+{
+	// The account an execution is dispatched from is only known for signed extrinsics.
+	let calls = match sender {
+		Some(sender) => ssvm_calls_from(sender, &extrinsic.function)
+			.into_iter()
+			.map(|(caller, call)| (Some(caller), call))
+			.collect::<Vec<_>>(),
+		None => ssvm_calls(&extrinsic.function).into_iter().map(|call| (None, call)).collect(),
+	};
+	let executions = calls.into_iter()
+		.zip(ssvm_outcomes(&extrinsic.function, events, failed))
+		.filter(|(_, outcome)| *outcome != SsvmOutcome::NotDispatched)
+		.collect::<Vec<_>>();
+	let block_hash = format!("{:?}", header.hash());
+	// Only successful creations deposit an event.
+	let mut created = events.iter().filter_map(|event| match event {
+		Event::pallet_ssvm(pallet_ssvm::Event::Create(address)) => Some(*address),
+		_ => None,
+	});
+	// The output of successful executions cannot be told apart when there are several.
+	let output = events.iter()
+		.filter_map(|event| match event {
+			Event::pallet_ssvm(pallet_ssvm::Event::Output(output)) => Some(output.clone()),
+			_ => None,
+		})
+		.last()
+		.filter(|_| executions.len() == 1);
+
+	for (execution, ((caller, call), outcome)) in executions.iter().enumerate() {
+		let succeeded = *outcome == SsvmOutcome::Succeeded;
+		let (kind, contract, input, value, gas_limit, gas_price) = match call {
+			pallet_ssvm::Call::create(code, value, gas_limit, gas_price) => {
+				let contract = if succeeded { created.next() } else { None };
+				if let Some(address) = contract {
+					let deployer = client.storage(
+						&BlockId::hash(header.hash()),
+						&storage_key(&[b"ContractRegistry", b"Deployers"], &address.encode()),
+					)?.map(|deployer| AccountId::decode(&mut &deployer.0[..])).transpose()?;
+					transaction.execute(
+						"INSERT OR REPLACE INTO contracts (address, block_hash, block_number, \
+							extrinsic_idx, deployer, code_hash) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+						params![
+							format!("{:?}", address),
+							block_hash,
+							i64::from(*header.number()),
+							index as i64,
+							deployer.map(|deployer| deployer.to_ss58check()),
+							hex(&blake2_256(code)),
+						],
+					)?;
+				}
+				("create", contract, code, value, gas_limit, gas_price)
+			}
+			pallet_ssvm::Call::call(address, data, value, gas_limit, gas_price) =>
+				("call", Some(*address), data, value, gas_limit, gas_price),
+			_ => continue,
+		};
+		let (output, revert_reason) = match outcome {
+			SsvmOutcome::Failed(output) => {
+				let reason = pallet_contract_revert::decode_revert_reason(output)
+					.map(|reason| String::from_utf8(reason).unwrap_or_else(|e| hex(e.as_bytes())));
+				(Some(output.clone()), reason)
+			}
+			_ => (output.clone(), None),
+		};
+		transaction.execute(
+			"INSERT INTO receipts (block_hash, extrinsic_idx, idx, kind, sender, caller, contract, \
+				input, value, gas_limit, gas_price, success, output, revert_reason) \
+				VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
+			params![
+				block_hash,
+				index as i64,
+				execution as i64,
+				kind,
+				sender.map(Ss58Codec::to_ss58check),
+				caller.as_ref().map(Ss58Codec::to_ss58check),
+				contract.map(|contract| format!("{:?}", contract)),
+				hex(input),
+				value.to_string(),
+				i64::from(*gas_limit),
+				gas_price.to_string(),
+				succeeded,
+				output.as_deref().map(hex),
+				revert_reason,
+			],
+		)?;
+	}
+	Ok(())
+}
+
+/// Key of a storage value, or of a `blake2_128_concat` map entry if `key` is not empty.
+fn storage_key(prefix: &[&[u8]], key: &[u8]) -> StorageKey {
+	let mut storage_key = prefix.iter().flat_map(|name| twox_128(name).to_vec()).collect::<Vec<_>>();
+	if !key.is_empty() {
+		storage_key.extend_from_slice(&blake2_128(key));
+		storage_key.extend_from_slice(key);
+	}
+	StorageKey(storage_key)
+}
+
+fn hex(data: &[u8]) -> String {
+	format!("0x{}", HexDisplay::from(&data))
+}
@@ -0,0 +1,112 @@
+-- Schema of the database written by `ssvm-node --indexer-db <path>`.
+--
+-- Only the blocks of the best chain are indexed. Rows of blocks that are not finalized
+-- yet are deleted when a reorg retracts them, rows of finalized blocks are never deleted.
+-- Hashes and binary data are stored as 0x-prefixed hex strings, accounts as SS58 strings,
+-- SSVM addresses as 0x-prefixed hex strings and amounts as decimal strings.
+--
+-- `pallet_ssvm` does not deposit the logs of contracts, so no logs are indexed.
+
+-- Indexed blocks.
+CREATE TABLE IF NOT EXISTS blocks (
+	hash TEXT PRIMARY KEY,
+	number INTEGER NOT NULL UNIQUE,
+	parent_hash TEXT NOT NULL,
+	state_root TEXT NOT NULL,
+	extrinsics_root TEXT NOT NULL,
+	-- Number of extrinsics in the block.
+	extrinsic_count INTEGER NOT NULL,
+	-- 1 once the block is finalized, 0 before.
+	finalized INTEGER NOT NULL DEFAULT 0
+);
+
+-- Extrinsics of the indexed blocks.
+CREATE TABLE IF NOT EXISTS extrinsics (
+	block_hash TEXT NOT NULL REFERENCES blocks (hash) ON DELETE CASCADE,
+	-- Index of the extrinsic in its block.
+	idx INTEGER NOT NULL,
+	-- Blake2-256 hash of the encoded extrinsic.
+	hash TEXT NOT NULL,
+	-- Account that signed the extrinsic, with the name it was signed with resolved, NULL
+	-- for inherents and unsigned extrinsics.
+	signer TEXT,
+	-- Pallet and function of the call in the runtime metadata, e.g. `SSVM` and `call`.
+	module TEXT NOT NULL,
+	function TEXT NOT NULL,
+	-- The whole call, as printed by the runtime, meant to be read rather than parsed.
+	call TEXT NOT NULL,
+	-- 1 if the call was dispatched successfully, 0 otherwise.
+	success INTEGER NOT NULL,
+	-- Why the call failed, NULL if it succeeded.
+	error TEXT,
+	PRIMARY KEY (block_hash, idx)
+);
+CREATE INDEX IF NOT EXISTS extrinsics_by_hash ON extrinsics (hash);
+CREATE INDEX IF NOT EXISTS extrinsics_by_signer ON extrinsics (signer);
+
+-- Events deposited in the `System` events of the indexed blocks.
+CREATE TABLE IF NOT EXISTS events (
+	block_hash TEXT NOT NULL REFERENCES blocks (hash) ON DELETE CASCADE,
+	-- Index of the event in the events of its block.
+	idx INTEGER NOT NULL,
+	-- Index of the extrinsic that deposited the event, NULL for events deposited while
+	-- initializing or finalizing the block.
+	extrinsic_idx INTEGER,
+	-- Index of the pallet that deposited the event and index of the event among the events
+	-- of the pallet, as encoded, which the runtime metadata names.
+	pallet_index INTEGER NOT NULL,
+	event_index INTEGER NOT NULL,
+	-- The whole event, as printed by the runtime, meant to be read rather than parsed.
+	event TEXT NOT NULL,
+	-- The SCALE encoded event.
+	encoded TEXT NOT NULL,
+	PRIMARY KEY (block_hash, idx)
+);
+
+-- SSVM creates and calls dispatched by the extrinsics of the indexed blocks, including
+-- those nested in batches, proxied and multisig calls. Calls that were not dispatched, after
+-- the interrupted call of a batch or in a multisig approval below the threshold, are left
+-- out. Calls scheduled by `ContractScheduler` are not extrinsics and are not indexed.
+CREATE TABLE IF NOT EXISTS receipts (
+	block_hash TEXT NOT NULL REFERENCES blocks (hash) ON DELETE CASCADE,
+	extrinsic_idx INTEGER NOT NULL,
+	-- Index of the execution among the SSVM executions dispatched by the extrinsic.
+	idx INTEGER NOT NULL,
+	-- `create` or `call`.
+	kind TEXT NOT NULL,
+	-- Signer of the extrinsic.
+	sender TEXT,
+	-- Account the execution is dispatched from, which contracts see as the caller: the
+	-- signer, or the derivative, proxied or multisig account the signer dispatches for.
+	caller TEXT,
+	-- Called contract, or created contract if the creation succeeded.
+	contract TEXT,
+	-- Call data, or code of the created contract.
+	input TEXT NOT NULL,
+	value TEXT NOT NULL,
+	gas_limit INTEGER NOT NULL,
+	gas_price TEXT NOT NULL,
+	-- 1 if the execution succeeded, 0 if it failed or was rolled back.
+	success INTEGER NOT NULL,
+	-- Output reported by the SSVM: the revert data of a failed execution, and the output of
+	-- a successful one only for extrinsics with a single execution.
+	output TEXT,
+	-- Reason of a failed execution that reverted with `Error(string)`.
+	revert_reason TEXT,
+	PRIMARY KEY (block_hash, extrinsic_idx, idx)
+);
+CREATE INDEX IF NOT EXISTS receipts_by_contract ON receipts (contract);
+CREATE INDEX IF NOT EXISTS receipts_by_sender ON receipts (sender);
+
+-- Contracts created in the indexed blocks.
+CREATE TABLE IF NOT EXISTS contracts (
+	address TEXT PRIMARY KEY,
+	block_hash TEXT NOT NULL REFERENCES blocks (hash) ON DELETE CASCADE,
+	block_number INTEGER NOT NULL,
+	extrinsic_idx INTEGER NOT NULL,
+	-- Account recorded as the deployer in `ContractRegistry`.
+	deployer TEXT,
+	-- Blake2-256 hash of the code submitted by the creation.
+	code_hash TEXT NOT NULL
+);
+
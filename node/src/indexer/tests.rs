@@ -0,0 +1,100 @@
+use super::*;
+
+fn indexer() -> Indexer {
+	Indexer::open(Path::new(":memory:")).unwrap()
+}
+
+fn insert_block(indexer: &Indexer, number: i64, finalized: bool) {
+	let hash = format!("{:?}", Hash::repeat_byte(number as u8));
+	indexer.connection.execute(
+		"INSERT INTO blocks (hash, number, parent_hash, state_root, extrinsics_root, \
+			extrinsic_count, finalized) VALUES (?1, ?2, '0x', '0x', '0x', 1, ?3)",
+		params![hash, number, finalized],
+	).unwrap();
+	indexer.connection.execute(
+		"INSERT INTO extrinsics (block_hash, idx, hash, module, function, call, success) \
+			VALUES (?1, 0, '0x', 'Timestamp', 'set', 'Timestamp(set(0))', 1)",
+		params![hash],
+	).unwrap();
+}
+
+fn count(indexer: &Indexer, table: &str) -> i64 {
+	indexer.connection
+		.query_row(&format!("SELECT COUNT(*) FROM {}", table), params![], |row| row.get(0))
+		.unwrap()
+}
+
+#[test]
+fn schema_can_be_applied_again() {
+	let indexer = indexer();
+	indexer.connection.execute_batch(SCHEMA).unwrap();
+}
+
+#[test]
+fn retracted_blocks_are_deleted_with_their_rows() {
+	let mut indexer = indexer();
+	insert_block(&indexer, 1, true);
+	insert_block(&indexer, 2, false);
+	insert_block(&indexer, 3, false);
+
+	// A reorg with block 1 as common ancestor.
+	let transaction = indexer.connection.transaction().unwrap();
+	retract_above(&transaction, Some(1)).unwrap();
+	transaction.commit().unwrap();
+
+	assert_eq!(count(&indexer, "blocks"), 1);
+	assert_eq!(count(&indexer, "extrinsics"), 1);
+}
+
+#[test]
+fn finalized_blocks_are_never_retracted() {
+	let mut indexer = indexer();
+	insert_block(&indexer, 0, true);
+	insert_block(&indexer, 1, true);
+
+	let transaction = indexer.connection.transaction().unwrap();
+	retract_above(&transaction, None).unwrap();
+	transaction.commit().unwrap();
+
+	assert_eq!(count(&indexer, "blocks"), 2);
+}
+
+#[test]
+fn common_ancestor_is_the_last_indexed_block_of_the_best_chain() {
+	let indexer = indexer();
+	let canonical = |number: u32| -> sp_blockchain::Result<Option<Hash>> {
+		Ok(Some(Hash::repeat_byte(number as u8)))
+	};
+	assert_eq!(common_ancestor(&indexer.connection, 5, canonical).unwrap(), None);
+
+	for number in 0..4 {
+		insert_block(&indexer, number, number == 0);
+	}
+	assert_eq!(common_ancestor(&indexer.connection, 5, canonical).unwrap(), Some(3));
+	// The best chain is shorter than the indexed one.
+	assert_eq!(common_ancestor(&indexer.connection, 2, canonical).unwrap(), Some(2));
+	// A reorg replaced blocks 2 and 3.
+	let reorg = |number: u32| if number < 2 { canonical(number) } else { Ok(Some(Hash::repeat_byte(0xff))) };
+	assert_eq!(common_ancestor(&indexer.connection, 5, reorg).unwrap(), Some(1));
+}
+
+#[test]
+fn calls_are_named_after_the_runtime_metadata() {
+	let call = ssvm_node_runtime::Call::SSVM(pallet_ssvm::Call::call(
+		sp_core::H160::repeat_byte(1),
+		vec![],
+		0.into(),
+		100_000,
+		1.into(),
+	));
+	let metadata = call.get_call_metadata();
+	assert_eq!((metadata.pallet_name, metadata.function_name), ("SSVM", "call"));
+}
+
+#[test]
+fn map_keys_are_hashed_and_concatenated() {
+	let key = storage_key(&[b"ContractRegistry", b"Deployers"], &[1, 2]);
+	assert_eq!(key.0.len(), 16 + 16 + 16 + 2);
+	assert!(key.0.ends_with(&[1, 2]));
+	assert_eq!(storage_key(&[b"System", b"Events"], &[]).0.len(), 32);
+}
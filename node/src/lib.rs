@@ -1,3 +1,7 @@
 pub mod chain_spec;
 pub mod service;
 pub mod rpc;
+pub mod metrics;
+pub mod abi;
+pub mod indexer;
+pub mod explorer;
@@ -7,6 +7,10 @@ mod service;
 mod cli;
 mod command;
 mod rpc;
+mod metrics;
+mod indexer;
+mod explorer;
+mod abi;
 
 fn main() -> sc_cli::Result<()> {
 	command::run()
@@ -0,0 +1,247 @@
+//! Prometheus metrics specific to this node, registered next to Substrate's own metrics.
+
+use std::{sync::Arc, time::Instant};
+use codec::{Encode, Decode};
+use futures::{future, StreamExt};
+use sc_client_api::{BlockBackend, BlockchainEvents};
+use sp_api::ProvideRuntimeApi;
+use sp_block_builder::BlockBuilder;
+use sp_blockchain::HeaderBackend;
+use sp_consensus::BlockOrigin;
+use sp_runtime::{generic::BlockId, traits::{Block as BlockT, Header as HeaderT, UniqueSaturatedInto}};
+use ssvm_node_runtime::{
+	Event, UncheckedExtrinsic, apis::ReplayApi, opaque::Block, ssvm::{GasParams, ssvm_calls},
+};
+use substrate_prometheus_endpoint::{
+	register, Counter, CounterVec, Gauge, Histogram, HistogramOpts, HistogramVec, Opts,
+	PrometheusError, Registry, U64, prometheus::exponential_buckets,
+};
+
+/// Heights followed by a node running the GRANDPA observer, so the lag between the chain
+/// it has observed and the chain it has seen finalized can be monitored.
+#[derive(Clone)]
+pub struct GrandpaObserverMetrics {
+	observed_height: Gauge<U64>,
+	finalized_height: Gauge<U64>,
+	finality_lag: Gauge<U64>,
+}
+
+impl GrandpaObserverMetrics {
+	/// Register the metrics in the given registry.
+	pub fn register(registry: &Registry) -> Result<Self, PrometheusError> {
+		Ok(GrandpaObserverMetrics {
+			observed_height: register(
+				Gauge::new(
+					"grandpa_observer_observed_height",
+					"Height of the best block observed by the GRANDPA observer",
+				)?,
+				registry,
+			)?,
+			finalized_height: register(
+				Gauge::new(
+					"grandpa_observer_finalized_height",
+					"Height of the last block finalized by the GRANDPA observer",
+				)?,
+				registry,
+			)?,
+			finality_lag: register(
+				Gauge::new(
+					"grandpa_observer_finality_lag",
+					"Number of observed blocks that are not finalized yet",
+				)?,
+				registry,
+			)?,
+		})
+	}
+
+	/// Update the metrics from every block import and finality notification of `client`.
+	pub async fn run<Block, Client>(self, client: Arc<Client>)
+	where
+		Block: BlockT,
+		Client: BlockchainEvents<Block> + HeaderBackend<Block>,
+	{
+		let imports = client.import_notification_stream().map(|_| ());
+		let finality = client.finality_notification_stream().map(|_| ());
+
+		futures::stream::select(imports, finality)
+			.for_each(move |_| {
+				let info = client.info();
+				let observed: u64 = info.best_number.unique_saturated_into();
+				let finalized: u64 = info.finalized_number.unique_saturated_into();
+
+				self.observed_height.set(observed);
+				self.finalized_height.set(finalized);
+				self.finality_lag.set(observed.saturating_sub(finalized));
+
+				future::ready(())
+			})
+			.await
+	}
+}
+
+/// SSVM executions of imported blocks.
+///
+/// The blocks containing SSVM extrinsics are executed again on top of the state of their
+/// parent, timing the SSVM extrinsics and collecting the SSVM calls they dispatched and the
+/// events they deposited. Calls that were not dispatched, such as the ones of multisig
+/// approvals below their threshold, are not counted.
+///
+/// The SSVM reports neither the gas a contract used nor how long it ran, so these metrics
+/// do not measure them: the gas metrics are the gas limits of the executions, which is what
+/// the block gas limit accounts for, and the timing metric is the wall-time of re-executing
+/// the whole extrinsic, signature check, fees and events included.
+///
+/// Blocks imported during the initial sync are skipped. As executing blocks twice is not
+/// free, the metrics are only exported with `--ssvm-metrics`.
+#[derive(Clone)]
+pub struct SsvmMetrics {
+	executions: CounterVec<U64>,
+	executions_per_block: HistogramVec,
+	gas_limit: Histogram,
+	block_gas_limit: Histogram,
+	reverts: Counter<U64>,
+	code_size: Histogram,
+	replay_time: Histogram,
+}
+
+impl SsvmMetrics {
+	/// Register the metrics in the given registry.
+	pub fn register(registry: &Registry) -> Result<Self, PrometheusError> {
+		Ok(SsvmMetrics {
+			executions: register(
+				CounterVec::new(
+					Opts::new("ssvm_executions_total", "Number of SSVM creates and calls dispatched"),
+					&["kind"],
+				)?,
+				registry,
+			)?,
+			executions_per_block: register(
+				HistogramVec::new(
+					HistogramOpts::new(
+						"ssvm_executions_per_block",
+						"Number of SSVM creates and calls dispatched by blocks containing SSVM extrinsics",
+					).buckets(exponential_buckets(1.0, 2.0, 10)?),
+					&["kind"],
+				)?,
+				registry,
+			)?,
+			gas_limit: register(
+				Histogram::with_opts(
+					HistogramOpts::new("ssvm_gas_limit", "Gas limit of SSVM executions, not the gas they used")
+						.buckets(exponential_buckets(1_000.0, 4.0, 10)?),
+				)?,
+				registry,
+			)?,
+			block_gas_limit: register(
+				Histogram::with_opts(
+					HistogramOpts::new(
+						"ssvm_block_gas_limit",
+						"Total gas limit of the SSVM executions of blocks containing SSVM extrinsics",
+					).buckets(exponential_buckets(1_000.0, 4.0, 12)?),
+				)?,
+				registry,
+			)?,
+			reverts: register(
+				Counter::new("ssvm_reverts_total", "Number of SSVM calls that reverted")?,
+				registry,
+			)?,
+			code_size: register(
+				Histogram::with_opts(
+					HistogramOpts::new(
+						"ssvm_deployment_code_size_bytes",
+						"Size of the code of SSVM creates",
+					).buckets(exponential_buckets(256.0, 2.0, 10)?),
+				)?,
+				registry,
+			)?,
+			replay_time: register(
+				Histogram::with_opts(
+					HistogramOpts::new(
+						"ssvm_extrinsic_replay_seconds",
+						"Wall-time of re-executing whole SSVM extrinsics on import",
+					).buckets(exponential_buckets(0.0001, 4.0, 10)?),
+				)?,
+				registry,
+			)?,
+		})
+	}
+
+	/// Update the metrics from every block imported by `client`.
+	pub async fn run<Client>(self, client: Arc<Client>)
+	where
+		Client: BlockchainEvents<Block> + BlockBackend<Block> + HeaderBackend<Block>,
+		Client: ProvideRuntimeApi<Block> + Send + Sync,
+		Client::Api: BlockBuilder<Block> + ReplayApi<Block>,
+	{
+		client.import_notification_stream()
+			.filter(|notification| future::ready(notification.origin != BlockOrigin::NetworkInitialSync))
+			.for_each(move |notification| {
+				if let Err(e) = self.observe(&*client, notification.hash) {
+					log::debug!(target: "ssvm-metrics", "Unable to observe block {}: {}", notification.hash, e);
+				}
+				future::ready(())
+			})
+			.await
+	}
+
+	/// Execute the block again to observe its SSVM extrinsics.
+	fn observe<Client>(&self, client: &Client, hash: <Block as BlockT>::Hash) -> Result<(), String>
+	where
+		Client: BlockBackend<Block> + HeaderBackend<Block> + ProvideRuntimeApi<Block>,
+		Client::Api: BlockBuilder<Block> + ReplayApi<Block>,
+	{
+		let at = BlockId::hash(hash);
+		let header = client.header(at).map_err(|e| e.to_string())?.ok_or("Unknown block")?;
+		let extrinsics = client.block_body(&at).map_err(|e| e.to_string())?.ok_or("Unknown block")?;
+		// Only the extrinsics that may dispatch SSVM calls are replayed on their own.
+		let may_call_ssvm = extrinsics.iter()
+			.map(|extrinsic| UncheckedExtrinsic::decode(&mut &*extrinsic.encode())
+				.map_or(false, |extrinsic| !ssvm_calls(&extrinsic.function).is_empty()))
+			.collect::<Vec<_>>();
+		if !may_call_ssvm.contains(&true) {
+			return Ok(());
+		}
+
+		let parent = BlockId::hash(*header.parent_hash());
+		let api = client.runtime_api();
+		api.initialize_block(&parent, &header).map_err(|e| e.to_string())?;
+
+		let (mut creates, mut calls, mut block_gas_limit) = (0u64, 0u64, 0u64);
+		for (extrinsic, may_call_ssvm) in extrinsics.into_iter().zip(may_call_ssvm) {
+			if !may_call_ssvm {
+				api.apply_extrinsic(&parent, extrinsic).map_err(|e| e.to_string())?;
+				continue;
+			}
+
+			let started = Instant::now();
+			let replay = api.replay_extrinsic(&parent, extrinsic).map_err(|e| e.to_string())?;
+			self.replay_time.observe(started.elapsed().as_secs_f64());
+
+			for (_, call) in replay.ssvm_calls {
+				match &call {
+					pallet_ssvm::Call::create(code, ..) => {
+						self.code_size.observe(code.len() as f64);
+						creates += 1;
+					}
+					pallet_ssvm::Call::call(..) => calls += 1,
+					_ => {}
+				}
+				if let Some(gas) = GasParams::of(&ssvm_node_runtime::Call::SSVM(call)) {
+					self.gas_limit.observe(gas.gas_limit as f64);
+					block_gas_limit += gas.gas_limit as u64;
+				}
+			}
+			let reverts = replay.events.iter()
+				.filter(|event| matches!(event, Event::pallet_contract_revert(_)))
+				.count();
+			self.reverts.inc_by(reverts as u64);
+		}
+
+		self.executions.with_label_values(&["create"]).inc_by(creates);
+		self.executions.with_label_values(&["call"]).inc_by(calls);
+		self.executions_per_block.with_label_values(&["create"]).observe(creates as f64);
+		self.executions_per_block.with_label_values(&["call"]).observe(calls as f64);
+		self.block_gas_limit.observe(block_gas_limit as f64);
+		Ok(())
+	}
+}
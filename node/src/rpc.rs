@@ -14,6 +14,8 @@ use sp_block_builder::BlockBuilder;
 pub use sc_rpc_api::DenyUnsafe;
 use sp_transaction_pool::TransactionPool;
 
+pub mod fee;
+pub mod ssvm;
 
 /// Full client dependencies.
 pub struct FullDeps<C, P> {
@@ -34,11 +36,15 @@ pub fn create_full<C, P>(
 	C: Send + Sync + 'static,
 	C::Api: substrate_frame_rpc_system::AccountNonceApi<Block, AccountId, Index>,
 	C::Api: pallet_transaction_payment_rpc::TransactionPaymentRuntimeApi<Block, Balance>,
+	C::Api: ssvm_node_runtime::apis::FeeMultiplierApi<Block>,
+	C::Api: ssvm_node_runtime::apis::SSVMApi<Block>,
 	C::Api: BlockBuilder<Block>,
 	P: TransactionPool + 'static,
 {
 	use substrate_frame_rpc_system::{FullSystem, SystemApi};
 	use pallet_transaction_payment_rpc::{TransactionPayment, TransactionPaymentApi};
+	use fee::{FeeMultiplier, FeeMultiplierApi};
+	use ssvm::{SSVM, SSVMApi};
 
 	let mut io = jsonrpc_core::IoHandler::default();
 	let FullDeps {
@@ -55,6 +61,14 @@ pub fn create_full<C, P>(
 		TransactionPaymentApi::to_delegate(TransactionPayment::new(client.clone()))
 	);
 
+	io.extend_with(
+		FeeMultiplierApi::to_delegate(FeeMultiplier::new(client.clone()))
+	);
+
+	io.extend_with(
+		SSVMApi::to_delegate(SSVM::new(client.clone()))
+	);
+
 	// Extend this RPC with a custom API by using the following syntax.
 	// `YourRpcStruct` should have a reference to a client, which is needed
 	// to call into the runtime.
@@ -0,0 +1,56 @@
+//! RPC exposing the congestion based fee multiplier of the runtime.
+
+use std::{marker::PhantomData, sync::Arc};
+
+use jsonrpc_core::{Error as RpcError, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+use ssvm_node_runtime::apis::FeeMultiplierApi as FeeMultiplierRuntimeApi;
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_rpc::number::NumberOrHex;
+use sp_runtime::{generic::BlockId, traits::Block as BlockT, FixedPointNumber};
+
+/// Error code returned when the runtime API call fails.
+const RUNTIME_ERROR: i64 = 1;
+
+/// Fee multiplier RPC methods.
+#[rpc]
+pub trait FeeMultiplierApi<BlockHash> {
+	/// The multiplier applied to the weight fee of transactions in the block after `at`,
+	/// as the inner value of a fixed point number with 18 decimals.
+	#[rpc(name = "payment_nextFeeMultiplier")]
+	fn next_fee_multiplier(&self, at: Option<BlockHash>) -> Result<NumberOrHex>;
+}
+
+/// Implementation of `FeeMultiplierApi` on top of a full client.
+pub struct FeeMultiplier<C, B> {
+	client: Arc<C>,
+	_marker: PhantomData<B>,
+}
+
+impl<C, B> FeeMultiplier<C, B> {
+	/// Create a new `FeeMultiplier` with the given reference to the client.
+	pub fn new(client: Arc<C>) -> Self {
+		FeeMultiplier { client, _marker: Default::default() }
+	}
+}
+
+impl<C, Block> FeeMultiplierApi<<Block as BlockT>::Hash> for FeeMultiplier<C, Block>
+where
+	Block: BlockT,
+	C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync + 'static,
+	C::Api: FeeMultiplierRuntimeApi<Block>,
+{
+	fn next_fee_multiplier(&self, at: Option<<Block as BlockT>::Hash>) -> Result<NumberOrHex> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+		api.next_fee_multiplier(&at)
+			.map(|multiplier| multiplier.into_inner().into())
+			.map_err(|e| RpcError {
+				code: ErrorCode::ServerError(RUNTIME_ERROR),
+				message: "Unable to query the fee multiplier.".into(),
+				data: Some(format!("{:?}", e).into()),
+			})
+	}
+}
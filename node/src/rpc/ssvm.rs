@@ -0,0 +1,149 @@
+//! RPC calling SSVM contracts without submitting a transaction.
+
+use std::{marker::PhantomData, sync::Arc};
+
+use jsonrpc_core::{Error as RpcError, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+use serde::{Serialize, Deserialize};
+use serde_json::Value as Json;
+use ssvm_node_runtime::{AccountId, apis::SSVMApi as SSVMRuntimeApi};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::{Bytes, H160, U256};
+use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+
+use crate::abi::{Abi, DecodedParam};
+
+/// Error code returned when the runtime API call fails.
+const RUNTIME_ERROR: i64 = 1;
+/// Error code returned when the output does not match the ABI of the function.
+const DECODE_ERROR: i64 = 2;
+
+/// Gas limit of calls that do not specify one.
+const DEFAULT_GAS_LIMIT: u32 = 50_000_000;
+
+/// A contract call.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(deny_unknown_fields)]
+pub struct CallRequest {
+	/// Account making the call.
+	pub from: AccountId,
+	/// Address of the contract.
+	pub to: H160,
+	/// Input of the call, defaults to nothing. Not allowed along with `function`.
+	pub data: Option<Bytes>,
+	/// JSON ABI of the contract, required by `function`.
+	pub abi: Option<Json>,
+	/// Name or signature of the function to call, whose input is encoded from `args`.
+	pub function: Option<String>,
+	/// Arguments of `function`, in the JSON representation of their types.
+	pub args: Option<Vec<Json>>,
+	/// Value transferred to the contract, defaults to zero.
+	pub value: Option<U256>,
+	/// Maximum amount of gas the call may use.
+	pub gas_limit: Option<u32>,
+	/// Price paid for each unit of gas, defaults to zero.
+	pub gas_price: Option<U256>,
+}
+
+/// Outcome of a contract call.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallResponse {
+	/// Whether the call succeeded.
+	pub success: bool,
+	/// Output of the contract, which is the revert data if the call failed.
+	pub output: Bytes,
+	/// Reason given by the contract when it reverted with `Error(string)`.
+	pub revert_reason: Option<String>,
+	/// Why the call failed.
+	pub error: Option<String>,
+	/// Return values of `function`, if the call succeeded.
+	pub decoded: Option<Vec<DecodedParam>>,
+}
+
+/// SSVM RPC methods.
+#[rpc]
+pub trait SSVMApi<BlockHash> {
+	/// Execute a contract call on top of the state of block `at` and return its output.
+	/// The changes made by the call are discarded.
+	#[rpc(name = "ssvm_call")]
+	fn call(&self, request: CallRequest, at: Option<BlockHash>) -> Result<CallResponse>;
+}
+
+/// Implementation of `SSVMApi` on top of a full client.
+pub struct SSVM<C, B> {
+	client: Arc<C>,
+	_marker: PhantomData<B>,
+}
+
+impl<C, B> SSVM<C, B> {
+	/// Create a new `SSVM` with the given reference to the client.
+	pub fn new(client: Arc<C>) -> Self {
+		SSVM { client, _marker: Default::default() }
+	}
+}
+
+impl<C, Block> SSVMApi<<Block as BlockT>::Hash> for SSVM<C, Block>
+where
+	Block: BlockT,
+	C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync + 'static,
+	C::Api: SSVMRuntimeApi<Block>,
+{
+	fn call(&self, request: CallRequest, at: Option<<Block as BlockT>::Hash>) -> Result<CallResponse> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+		let CallRequest { from, to, data, abi, function, args, value, gas_limit, gas_price } = request;
+
+		let abi_function = match (function, abi) {
+			(None, _) => None,
+			(Some(_), None) => return Err(RpcError::invalid_params("`function` requires `abi`.")),
+			(Some(_), Some(_)) if data.is_some() =>
+				return Err(RpcError::invalid_params("`data` and `function` are exclusive.")),
+			(Some(function), Some(abi)) => {
+				let abi = Abi::from_json(abi).map_err(RpcError::invalid_params)?;
+				let function = abi.function(&function).map_err(RpcError::invalid_params)?.clone();
+				Some(function)
+			},
+		};
+		let data = match &abi_function {
+			Some(function) => function.encode_input(&args.unwrap_or_default())
+				.map_err(RpcError::invalid_params)?,
+			None => data.map(|data| data.0).unwrap_or_default(),
+		};
+
+		let result = api.call(
+			&at,
+			from,
+			to,
+			data,
+			value.unwrap_or_default(),
+			gas_limit.unwrap_or(DEFAULT_GAS_LIMIT),
+			gas_price.unwrap_or_default(),
+		).map_err(|e| RpcError {
+			code: ErrorCode::ServerError(RUNTIME_ERROR),
+			message: "Unable to call the contract.".into(),
+			data: Some(format!("{:?}", e).into()),
+		})?;
+
+		let decoded = match abi_function {
+			Some(function) if result.error.is_none() => Some(function.decode_output(&result.output)
+				.map_err(|e| RpcError {
+					code: ErrorCode::ServerError(DECODE_ERROR),
+					message: "Unable to decode the output of the contract.".into(),
+					data: Some(e.into()),
+				})?),
+			_ => None,
+		};
+
+		Ok(CallResponse {
+			success: result.error.is_none(),
+			output: result.output.into(),
+			revert_reason: result.revert_reason
+				.map(|reason| String::from_utf8_lossy(&reason).into_owned()),
+			error: result.error.map(|e| format!("{:?}", e)),
+			decoded,
+		})
+	}
+}
@@ -1,5 +1,7 @@
 //! Service and ServiceFactory implementation. Specialized wrapper over substrate service.
 
+use std::net::{SocketAddr, TcpListener};
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
 use sc_client_api::{ExecutorProvider, RemoteBackend};
@@ -9,7 +11,11 @@ use sp_inherents::InherentDataProviders;
 use sc_executor::native_executor_instance;
 pub use sc_executor::NativeExecutor;
 use sp_consensus_aura::sr25519::{AuthorityPair as AuraPair};
-use sc_finality_grandpa::{FinalityProofProvider as GrandpaFinalityProofProvider, SharedVoterState};
+use sc_finality_grandpa::{
+	FinalityProofProvider as GrandpaFinalityProofProvider, SharedVoterState, VotingRulesBuilder,
+	BeforeBestBlockBy, ThreeQuartersOfTheUnfinalizedChain,
+};
+use crate::chain_spec::{Extensions, GrandpaSettings};
 
 // Our native executor instance.
 native_executor_instance!(
@@ -19,6 +25,9 @@ native_executor_instance!(
 	frame_benchmarking::benchmarking::HostFunctions,
 );
 
+#[cfg(test)]
+mod tests;
+
 type FullClient = sc_service::TFullClient<Block, RuntimeApi, Executor>;
 type FullBackend = sc_service::TFullBackend<Block>;
 type FullSelectChain = sc_consensus::LongestChain<FullBackend, Block>;
@@ -79,8 +88,42 @@ pub fn new_partial(config: &Configuration) -> Result<sc_service::PartialComponen
 	})
 }
 
-/// Builds a new service for a full client.
-pub fn new_full(config: Configuration) -> Result<TaskManager, ServiceError> {
+/// Builds the GRANDPA voting rule described by the chain spec settings.
+fn grandpa_voting_rule(
+	settings: &GrandpaSettings,
+) -> impl sc_finality_grandpa::VotingRule<Block, FullClient> + Clone {
+	let mut builder = VotingRulesBuilder::new();
+	if let Some(blocks) = settings.vote_before_best_by {
+		builder = builder.add(BeforeBestBlockBy(blocks));
+	}
+	if settings.three_quarters_of_unfinalized_chain {
+		builder = builder.add(ThreeQuartersOfTheUnfinalizedChain);
+	}
+	builder.build()
+}
+
+/// Options of a full node that are not part of its `Configuration`.
+#[derive(Debug, Clone, Default)]
+pub struct FullOptions {
+	/// Run the GRANDPA observer instead of the full voter on a non-authority node.
+	pub grandpa_observer: bool,
+	/// Execute imported blocks containing SSVM extrinsics again to export metrics about them.
+	pub ssvm_metrics: bool,
+	/// Index the best chain into the SQLite database at this path.
+	pub indexer_db: Option<PathBuf>,
+	/// Serve the block explorer API from the indexer database on this address.
+	pub explorer: Option<SocketAddr>,
+}
+
+/// Builds a new service for a full client, with the features enabled in `options`.
+pub fn new_full(config: Configuration, options: FullOptions) -> Result<TaskManager, ServiceError> {
+	let FullOptions { grandpa_observer, ssvm_metrics, indexer_db, explorer } = options;
+	if indexer_db.is_some() && !config.pruning.is_archive() {
+		return Err(ServiceError::Other(
+			"The indexer reads the state of every block, --indexer-db requires --pruning archive.".into(),
+		));
+	}
+
 	let sc_service::PartialComponents {
 		client, backend, mut task_manager, import_queue, keystore, select_chain, transaction_pool,
 		inherent_data_providers,
@@ -113,7 +156,14 @@ pub fn new_full(config: Configuration) -> Result<TaskManager, ServiceError> {
 	let force_authoring = config.force_authoring;
 	let name = config.network.node_name.clone();
 	let enable_grandpa = !config.disable_grandpa;
+	let run_grandpa_observer = grandpa_observer && !role.is_authority();
+	if grandpa_observer && role.is_authority() {
+		log::warn!("Authority nodes always run the GRANDPA voter, ignoring --grandpa-observer.");
+	}
 	let prometheus_registry = config.prometheus_registry().cloned();
+	let grandpa_settings = Extensions::try_get(&*config.chain_spec)
+		.map(|extensions| extensions.grandpa.clone())
+		.unwrap_or_default();
 	let telemetry_connection_sinks = sc_service::TelemetryConnectionSinks::default();
 
 	let rpc_extensions_builder = {
@@ -144,6 +194,36 @@ pub fn new_full(config: Configuration) -> Result<TaskManager, ServiceError> {
 		backend, network_status_sinks, system_rpc_tx, config,
 	})?;
 
+	if ssvm_metrics {
+		let registry = prometheus_registry.as_ref().ok_or_else(|| ServiceError::Other(
+			"The SSVM metrics are exported with Prometheus, \
+				--ssvm-metrics conflicts with --no-prometheus.".into(),
+		))?;
+		// Blocks containing SSVM extrinsics are executed again to be observed, so keep it
+		// away from the async executor.
+		let metrics = crate::metrics::SsvmMetrics::register(registry)?;
+		task_manager.spawn_handle().spawn_blocking("ssvm-metrics", metrics.run(client.clone()));
+	}
+
+	if let Some(path) = &indexer_db {
+		let indexer = crate::indexer::Indexer::open(path).map_err(|e| ServiceError::Other(
+			format!("Unable to open the indexer database {}: {}", path.display(), e),
+		))?;
+		task_manager.spawn_handle().spawn_blocking("indexer", indexer.run(client.clone()));
+	}
+
+	if let Some(address) = explorer {
+		let database = indexer_db.ok_or_else(|| ServiceError::Other(
+			"The explorer API serves the indexer database, --explorer-port requires --indexer-db.".into(),
+		))?;
+		let listener = TcpListener::bind(address)
+			.and_then(|listener| listener.set_nonblocking(true).map(|_| listener))
+			.map_err(|e| ServiceError::Other(
+				format!("Unable to bind the explorer API to {}: {}", address, e),
+			))?;
+		task_manager.spawn_handle().spawn("explorer", crate::explorer::run(listener, database));
+	}
+
 	if role.is_authority() {
 		let proposer = sc_basic_authorship::ProposerFactory::new(
 			client.clone(),
@@ -181,29 +261,43 @@ pub fn new_full(config: Configuration) -> Result<TaskManager, ServiceError> {
 	};
 
 	let grandpa_config = sc_finality_grandpa::Config {
-		// FIXME #1578 make this available through chainspec
-		gossip_duration: Duration::from_millis(333),
-		justification_period: 512,
+		gossip_duration: Duration::from_millis(grandpa_settings.gossip_duration_millis),
+		justification_period: grandpa_settings.justification_period,
 		name: Some(name),
-		observer_enabled: false,
+		observer_enabled: run_grandpa_observer,
 		keystore,
 		is_authority: role.is_network_authority(),
 	};
 
-	if enable_grandpa {
+	if enable_grandpa && run_grandpa_observer {
+		// NOTE: the observer does not take part in vote gossip, so nodes running it
+		// do not help voters reach each other. Keep enough full voters on the network.
+		if let Some(registry) = prometheus_registry.as_ref() {
+			let metrics = crate::metrics::GrandpaObserverMetrics::register(registry)?;
+			task_manager.spawn_handle().spawn(
+				"grandpa-observer-metrics",
+				metrics.run(client.clone()),
+			);
+		}
+
+		// the GRANDPA observer task is considered infallible, i.e.
+		// if it fails we take down the service with it.
+		task_manager.spawn_essential_handle().spawn_blocking(
+			"grandpa-observer",
+			sc_finality_grandpa::run_grandpa_observer(grandpa_config, grandpa_link, network)?
+		);
+	} else if enable_grandpa {
 		// start the full GRANDPA voter
-		// NOTE: non-authorities could run the GRANDPA observer protocol, but at
-		// this point the full voter should provide better guarantees of block
-		// and vote data availability than the observer. The observer has not
-		// been tested extensively yet and having most nodes in a network run it
-		// could lead to finality stalls.
+		// NOTE: unless `--grandpa-observer` is given, non-authorities run the full
+		// voter too, which provides better guarantees of block and vote data
+		// availability than the observer.
 		let grandpa_config = sc_finality_grandpa::GrandpaParams {
 			config: grandpa_config,
 			link: grandpa_link,
 			network,
 			inherent_data_providers,
 			telemetry_on_connect: Some(telemetry_connection_sinks.on_connect_stream()),
-			voting_rule: sc_finality_grandpa::VotingRulesBuilder::default().build(),
+			voting_rule: grandpa_voting_rule(&grandpa_settings),
 			prometheus_registry,
 			shared_voter_state: SharedVoterState::empty(),
 		};
@@ -0,0 +1,84 @@
+use super::*;
+use codec::{Decode, Encode};
+use sc_executor::WasmExecutionMethod;
+use sp_core::{H160, storage::Storage, testing::TaskExecutor};
+use sp_keyring::Sr25519Keyring;
+use sp_runtime::{
+	BuildStorage, OpaqueExtrinsic,
+	generic::{BlockId, Era},
+	transaction_validity::TransactionSource,
+};
+use sp_transaction_pool::{InPoolTransaction, TransactionPool};
+use ssvm_node_runtime::{Call, SignedExtra, SignedPayload, UncheckedExtrinsic, ssvm};
+
+fn ssvm_call(gas_price: u64) -> Call {
+	Call::SSVM(pallet_ssvm::Call::call(H160::repeat_byte(1), vec![], 0.into(), 100_000, gas_price.into()))
+}
+
+/// `call` signed by Alice with nonce 0, for the chain whose genesis storage is `storage`.
+fn sign(storage: &Storage, call: Call) -> OpaqueExtrinsic {
+	let extra: SignedExtra = (
+		frame_system::CheckSpecVersion::new(),
+		frame_system::CheckTxVersion::new(),
+		frame_system::CheckGenesis::new(),
+		frame_system::CheckEra::from(Era::Immortal),
+		frame_system::CheckNonce::from(0),
+		frame_system::CheckWeight::new(),
+		pallet_transaction_payment::ChargeTransactionPayment::from(0),
+		pallet_block_gas::CheckBlockGas::new(),
+		ssvm::ChargeGas,
+		ssvm::ReportRevert,
+		ssvm::NoteDeployer,
+	);
+	// The signed payload includes the runtime version and the genesis hash the runtime checks.
+	let payload = sp_io::TestExternalities::new(storage.clone())
+		.execute_with(|| SignedPayload::new(call, extra))
+		.unwrap();
+	let signature = payload.using_encoded(|payload| Sr25519Keyring::Alice.sign(payload));
+	let (call, extra, _) = payload.deconstruct();
+	let extrinsic = UncheckedExtrinsic::new_signed(
+		call,
+		Sr25519Keyring::Alice.to_account_id().into(),
+		signature.into(),
+		extra,
+	);
+	OpaqueExtrinsic::decode(&mut &extrinsic.encode()[..]).unwrap()
+}
+
+#[test]
+fn pool_replaces_ssvm_transactions_with_higher_gas_price() {
+	let spec = crate::chain_spec::development_config().unwrap();
+	let storage = spec.build_storage().unwrap();
+	let executor = NativeExecutor::<Executor>::new(WasmExecutionMethod::Interpreted, None, 8);
+	let client = sc_service::client::new_in_mem::<_, Block, _, RuntimeApi>(
+		executor,
+		&spec,
+		None,
+		None,
+		Box::new(TaskExecutor::new()),
+		Default::default(),
+	).unwrap();
+	// The pool validates transactions with the `TaggedTransactionQueue` API of the runtime.
+	let pool = sc_transaction_pool::BasicPool::new_full(
+		Default::default(),
+		None,
+		TaskExecutor::new(),
+		Arc::new(client),
+	);
+
+	let submit = |call| futures::executor::block_on(
+		pool.submit_one(&BlockId::number(0), TransactionSource::External, sign(&storage, call)),
+	);
+	let ready = || pool.ready()
+		.map(|tx| UncheckedExtrinsic::decode(&mut &tx.data().encode()[..]).unwrap().function)
+		.collect::<Vec<_>>();
+
+	assert!(submit(ssvm_call(10)).is_ok());
+	// Same sender and nonce at a lower gas price.
+	assert!(submit(ssvm_call(1)).is_err());
+	assert_eq!(ready(), vec![ssvm_call(10)]);
+
+	// Same sender and nonce at a higher gas price.
+	assert!(submit(ssvm_call(20)).is_ok());
+	assert_eq!(ready(), vec![ssvm_call(20)]);
+}
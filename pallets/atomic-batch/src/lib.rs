@@ -0,0 +1,79 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Atomic batches of calls.
+//!
+//! `pallet_utility::batch` stops at the first failing call but keeps the changes made by
+//! the calls before it, which leaves flows such as approving then spending ERC20 tokens
+//! half done. `batch_all` dispatches its calls in a storage transaction instead: if any of
+//! them fails, the changes made by all of them, including those of SSVM contracts, are
+//! rolled back and the batch fails with the error of that call.
+//!
+//! `pallet_utility` only offers `batch_all` in releases after the 2.0 series that this
+//! runtime is built on, so it lives here until the runtime is upgraded.
+
+use frame_support::{
+	decl_module, decl_event, transactional,
+	dispatch::{DispatchResultWithPostInfo, Parameter},
+	weights::{GetDispatchInfo, PostDispatchInfo, Weight, extract_actual_weight},
+};
+use sp_runtime::traits::Dispatchable;
+use sp_std::prelude::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+/// Weight of a batch on top of the weights of its calls.
+const BASE_WEIGHT: Weight = 10_000;
+
+/// Configure the pallet by specifying the parameters and types on which it depends.
+pub trait Trait: frame_system::Trait {
+	/// Because this pallet emits events, it depends on the runtime's definition of an event.
+	type Event: From<Event> + Into<<Self as frame_system::Trait>::Event>;
+
+	/// The calls that can be batched.
+	type Call: Parameter
+		+ Dispatchable<Origin = Self::Origin, PostInfo = PostDispatchInfo>
+		+ GetDispatchInfo;
+}
+
+decl_event!(
+	pub enum Event {
+		/// All the calls of a batch succeeded. [calls]
+		BatchAllCompleted(u32),
+	}
+);
+
+decl_module! {
+	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+		fn deposit_event() = default;
+
+		/// Dispatch `calls` from the origin, one after the other. If one of them fails, the
+		/// changes made by all of them are discarded.
+		///
+		/// The weight is the sum of the weights of the calls. Only the actual weight of the
+		/// calls that were dispatched is charged.
+		#[weight = calls.iter()
+			.map(|call| call.get_dispatch_info().weight)
+			.fold(BASE_WEIGHT, |total: Weight, weight| total.saturating_add(weight))]
+		#[transactional]
+		pub fn batch_all(origin, calls: Vec<<T as Trait>::Call>) -> DispatchResultWithPostInfo {
+			let count = calls.len() as u32;
+			let mut weight = BASE_WEIGHT;
+			for call in calls {
+				let info = call.get_dispatch_info();
+				let result = call.dispatch(origin.clone());
+				weight = weight.saturating_add(extract_actual_weight(&result, &info));
+				result.map_err(|mut e| {
+					e.post_info.actual_weight = Some(weight);
+					e
+				})?;
+			}
+
+			Self::deposit_event(Event::BatchAllCompleted(count));
+			Ok(Some(weight).into())
+		}
+	}
+}
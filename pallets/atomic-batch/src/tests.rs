@@ -0,0 +1,73 @@
+use crate::mock::*;
+use frame_support::{assert_ok, assert_noop};
+
+fn transfer(to: u64, value: u64) -> Call {
+	Call::Balances(pallet_balances::Call::transfer(to, value))
+}
+
+#[test]
+fn dispatches_all_calls() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(AtomicBatch::batch_all(Origin::signed(ALICE), vec![transfer(BOB, 10), transfer(BOB, 20)]));
+
+		assert_eq!(Balances::free_balance(ALICE), 970);
+		assert_eq!(Balances::free_balance(BOB), 30);
+	});
+}
+
+#[test]
+fn failing_call_rolls_back_whole_batch() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			AtomicBatch::batch_all(Origin::signed(ALICE), vec![transfer(BOB, 10), transfer(BOB, 10_000)]),
+			pallet_balances::Error::<Test, pallet_balances::DefaultInstance>::InsufficientBalance,
+		);
+
+		assert_eq!(Balances::free_balance(ALICE), 1_000);
+		assert_eq!(Balances::free_balance(BOB), 0);
+	});
+}
+
+#[test]
+fn calls_keep_the_origin_of_the_batch() {
+	new_test_ext().execute_with(|| {
+		let set_storage = Call::System(frame_system::Call::set_storage(vec![(b"key".to_vec(), b"value".to_vec())]));
+
+		assert_noop!(
+			AtomicBatch::batch_all(Origin::signed(ALICE), vec![transfer(BOB, 10), set_storage.clone()]),
+			sp_runtime::DispatchError::BadOrigin,
+		);
+		assert_ok!(AtomicBatch::batch_all(Origin::root(), vec![set_storage]));
+		assert_eq!(sp_io::storage::get(b"key"), Some(b"value".to_vec()));
+	});
+}
+
+#[test]
+fn weight_adds_up_weights_of_calls() {
+	new_test_ext().execute_with(|| {
+		use frame_support::weights::GetDispatchInfo;
+
+		let calls = vec![transfer(BOB, 10), transfer(BOB, 20)];
+		let weights = calls.iter().map(|call| call.get_dispatch_info().weight).sum::<u64>();
+		let batch = crate::Call::<Test>::batch_all(calls);
+		assert_eq!(batch.get_dispatch_info().weight, crate::BASE_WEIGHT + weights);
+	});
+}
+
+#[test]
+fn only_dispatched_calls_are_charged() {
+	new_test_ext().execute_with(|| {
+		use frame_support::weights::GetDispatchInfo;
+
+		let weight = transfer(BOB, 10).get_dispatch_info().weight;
+		let calls = vec![transfer(BOB, 10), transfer(BOB, 10_000), transfer(BOB, 10)];
+		let batch = crate::Call::<Test>::batch_all(calls.clone());
+		assert_eq!(batch.get_dispatch_info().weight, crate::BASE_WEIGHT + 3 * weight);
+
+		let error = AtomicBatch::batch_all(Origin::signed(ALICE), calls).unwrap_err();
+		assert_eq!(error.post_info.actual_weight, Some(crate::BASE_WEIGHT + 2 * weight));
+
+		let result = AtomicBatch::batch_all(Origin::signed(ALICE), vec![transfer(BOB, 10)]);
+		assert_eq!(result.unwrap().actual_weight, Some(crate::BASE_WEIGHT + weight));
+	});
+}
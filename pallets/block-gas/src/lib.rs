@@ -0,0 +1,174 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! A per block cap on the gas of contract executions.
+//!
+//! Contract calls are cheap in weight compared to the time the SSVM may spend running
+//! them, so the weight limit alone does not bound how long a block takes to execute.
+//! This pallet keeps a gas budget for every block. The `CheckBlockGas` signed extension
+//! reserves the gas limit of each contract call from it when the extrinsic is applied,
+//! and rejects the extrinsic with `InvalidTransaction::ExhaustsResources` once the budget
+//! is used up. The block builder treats that error like a full block and leaves the call
+//! in the pool for a later block. The budget can be changed by Root.
+//!
+//! The budget is refilled in `on_finalize` rather than `on_initialize`, so that the calls
+//! dispatched from the `on_initialize` of other pallets, such as scheduled ones, count
+//! against the budget of their block whichever order the pallets are initialized in.
+
+use sp_std::{fmt, marker::PhantomData};
+use codec::{Encode, Decode};
+use frame_support::{decl_module, decl_storage, decl_event, dispatch, traits::Get, weights::Weight};
+use frame_system::ensure_root;
+use sp_runtime::{
+	traits::{DispatchInfoOf, SignedExtension},
+	transaction_validity::{
+		TransactionValidity, TransactionValidityError, ValidTransaction, InvalidTransaction,
+	},
+};
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+/// Amount of gas a call may use.
+pub type Gas = u64;
+
+/// Tells how much gas a call may use.
+pub trait GasLimitOf<AccountId, Call> {
+	/// The gas limit of `call` dispatched from the origin of `who`, or `None` if it does not
+	/// execute a contract.
+	fn gas_limit(who: &AccountId, call: &Call) -> Option<Gas>;
+}
+
+/// Configure the pallet by specifying the parameters and types on which it depends.
+pub trait Trait: frame_system::Trait {
+	/// Because this pallet emits events, it depends on the runtime's definition of an event.
+	type Event: From<Event> + Into<<Self as frame_system::Trait>::Event>;
+	/// Gas limit of the contract calls of the runtime.
+	type GasLimitOf: GasLimitOf<Self::AccountId, <Self as frame_system::Trait>::Call>;
+}
+
+decl_storage! {
+	trait Store for Module<T: Trait> as BlockGas {
+		/// Maximum amount of gas the contract calls of a block may use together.
+		BlockGasLimit get(fn block_gas_limit) config(): Gas;
+
+		/// Gas reserved by the contract calls of the current block so far.
+		BlockGasUsed get(fn block_gas_used): Gas;
+	}
+}
+
+decl_event!(
+	pub enum Event {
+		/// The block gas limit was changed. [limit]
+		BlockGasLimitSet(Gas),
+	}
+);
+
+decl_module! {
+	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+		fn deposit_event() = default;
+
+		fn on_initialize(_n: T::BlockNumber) -> Weight {
+			// Weight of `on_finalize`.
+			T::DbWeight::get().writes(1)
+		}
+
+		fn on_finalize(_n: T::BlockNumber) {
+			BlockGasUsed::kill();
+		}
+
+		/// Change the amount of gas the contract calls of a block may use. Takes effect
+		/// from the current block on. Must be called by Root.
+		#[weight = 10_000 + T::DbWeight::get().writes(1)]
+		pub fn set_block_gas_limit(origin, limit: Gas) -> dispatch::DispatchResult {
+			ensure_root(origin)?;
+
+			BlockGasLimit::put(limit);
+
+			Self::deposit_event(Event::BlockGasLimitSet(limit));
+			Ok(())
+		}
+	}
+}
+
+impl<T: Trait> Module<T> {
+	/// Reserve `gas` from the budget of the current block.
+	///
+	/// Fails without reserving anything if the budget left is not enough.
+	pub fn reserve(gas: Gas) -> Result<(), TransactionValidityError> {
+		let used = Self::block_gas_used()
+			.checked_add(gas)
+			.filter(|used| *used <= Self::block_gas_limit())
+			.ok_or(InvalidTransaction::ExhaustsResources)?;
+		BlockGasUsed::put(used);
+		Ok(())
+	}
+}
+
+/// Reserve the gas limit of contract calls from the gas budget of the block.
+///
+/// Calls whose gas limit exceeds the block gas limit can never be included and are
+/// rejected by the transaction pool. The whole gas limit is reserved as the execution
+/// does not report the gas it actually used.
+#[derive(Encode, Decode, Clone, Eq, PartialEq)]
+pub struct CheckBlockGas<T: Trait + Send + Sync>(PhantomData<T>);
+
+impl<T: Trait + Send + Sync> CheckBlockGas<T> {
+	/// Create new `SignedExtension` to check the block gas limit.
+	pub fn new() -> Self {
+		Self(PhantomData)
+	}
+}
+
+impl<T: Trait + Send + Sync> fmt::Debug for CheckBlockGas<T> {
+	#[cfg(feature = "std")]
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "CheckBlockGas")
+	}
+
+	#[cfg(not(feature = "std"))]
+	fn fmt(&self, _: &mut fmt::Formatter) -> fmt::Result {
+		Ok(())
+	}
+}
+
+impl<T: Trait + Send + Sync> SignedExtension for CheckBlockGas<T> {
+	const IDENTIFIER: &'static str = "CheckBlockGas";
+	type AccountId = T::AccountId;
+	type Call = <T as frame_system::Trait>::Call;
+	type AdditionalSigned = ();
+	type Pre = ();
+
+	fn additional_signed(&self) -> Result<(), TransactionValidityError> {
+		Ok(())
+	}
+
+	fn validate(
+		&self,
+		who: &Self::AccountId,
+		call: &Self::Call,
+		_info: &DispatchInfoOf<Self::Call>,
+		_len: usize,
+	) -> TransactionValidity {
+		match T::GasLimitOf::gas_limit(who, call) {
+			Some(gas) if gas > <Module<T>>::block_gas_limit() =>
+				Err(InvalidTransaction::ExhaustsResources.into()),
+			_ => Ok(ValidTransaction::default()),
+		}
+	}
+
+	fn pre_dispatch(
+		self,
+		who: &Self::AccountId,
+		call: &Self::Call,
+		_info: &DispatchInfoOf<Self::Call>,
+		_len: usize,
+	) -> Result<(), TransactionValidityError> {
+		match T::GasLimitOf::gas_limit(who, call) {
+			Some(gas) => <Module<T>>::reserve(gas),
+			None => Ok(()),
+		}
+	}
+}
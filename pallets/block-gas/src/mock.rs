@@ -0,0 +1,89 @@
+use crate::{Module, Trait, GenesisConfig, Gas, GasLimitOf};
+use sp_core::H256;
+use frame_support::{impl_outer_origin, impl_outer_dispatch, parameter_types, weights::Weight};
+use sp_runtime::{traits::{BlakeTwo256, IdentityLookup}, testing::Header, Perbill};
+use frame_system as system;
+
+impl_outer_origin! {
+	pub enum Origin for Test {}
+}
+
+impl_outer_dispatch! {
+	pub enum Call for Test where origin: Origin {
+		frame_system::System,
+	}
+}
+
+// Configure a mock runtime to test the pallet.
+
+#[derive(Clone, Eq, PartialEq)]
+pub struct Test;
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+	pub const MaximumBlockWeight: Weight = 1024;
+	pub const MaximumBlockLength: u32 = 2 * 1024;
+	pub const AvailableBlockRatio: Perbill = Perbill::from_percent(75);
+}
+
+impl system::Trait for Test {
+	type BaseCallFilter = ();
+	type Origin = Origin;
+	type Call = Call;
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = u64;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = ();
+	type BlockHashCount = BlockHashCount;
+	type MaximumBlockWeight = MaximumBlockWeight;
+	type DbWeight = ();
+	type BlockExecutionWeight = ();
+	type ExtrinsicBaseWeight = ();
+	type MaximumExtrinsicWeight = MaximumBlockWeight;
+	type MaximumBlockLength = MaximumBlockLength;
+	type AvailableBlockRatio = AvailableBlockRatio;
+	type Version = ();
+	type PalletInfo = ();
+	type AccountData = ();
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+}
+
+/// Remarks play the role of contract calls, using the length of the remark as gas limit.
+pub struct RemarkGas;
+impl GasLimitOf<u64, Call> for RemarkGas {
+	fn gas_limit(_who: &u64, call: &Call) -> Option<Gas> {
+		match call {
+			Call::System(system::Call::remark(remark)) => Some(remark.len() as Gas),
+			_ => None,
+		}
+	}
+}
+
+impl Trait for Test {
+	type Event = ();
+	type GasLimitOf = RemarkGas;
+}
+
+pub const BLOCK_GAS_LIMIT: Gas = 100;
+
+pub type System = system::Module<Test>;
+pub type BlockGas = Module<Test>;
+
+/// A contract call with the given gas limit.
+pub fn contract_call(gas_limit: Gas) -> Call {
+	Call::System(system::Call::remark(vec![0; gas_limit as usize]))
+}
+
+// Build genesis storage according to the mock runtime.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	GenesisConfig {
+		block_gas_limit: BLOCK_GAS_LIMIT,
+	}.assimilate_storage::<Test>(&mut t).unwrap();
+	t.into()
+}
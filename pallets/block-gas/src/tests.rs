@@ -0,0 +1,77 @@
+use crate::{mock::*, CheckBlockGas};
+use frame_support::{assert_ok, assert_noop, traits::OnFinalize, weights::DispatchInfo};
+use sp_runtime::{
+	DispatchError,
+	traits::SignedExtension,
+	transaction_validity::{InvalidTransaction, TransactionValidityError},
+};
+
+fn pre_dispatch(call: &Call) -> Result<(), TransactionValidityError> {
+	CheckBlockGas::<Test>::new().pre_dispatch(&1, call, &DispatchInfo::default(), 0)
+}
+
+#[test]
+fn contract_calls_reserve_their_gas_limit() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(pre_dispatch(&contract_call(30)));
+		assert_ok!(pre_dispatch(&contract_call(70)));
+		assert_eq!(BlockGas::block_gas_used(), BLOCK_GAS_LIMIT);
+	});
+}
+
+#[test]
+fn calls_over_the_remaining_budget_exhaust_resources() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(pre_dispatch(&contract_call(60)));
+		assert_eq!(pre_dispatch(&contract_call(60)), Err(InvalidTransaction::ExhaustsResources.into()));
+		// The rejected call reserved nothing, a smaller one still fits.
+		assert_eq!(BlockGas::block_gas_used(), 60);
+		assert_ok!(pre_dispatch(&contract_call(40)));
+	});
+}
+
+#[test]
+fn other_calls_are_not_limited() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(pre_dispatch(&contract_call(BLOCK_GAS_LIMIT)));
+		assert_ok!(pre_dispatch(&Call::System(frame_system::Call::fill_block(Default::default()))));
+	});
+}
+
+#[test]
+fn budget_is_refilled_every_block() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(pre_dispatch(&contract_call(BLOCK_GAS_LIMIT)));
+
+		BlockGas::on_finalize(1);
+
+		assert_eq!(BlockGas::block_gas_used(), 0);
+		assert_ok!(pre_dispatch(&contract_call(BLOCK_GAS_LIMIT)));
+	});
+}
+
+#[test]
+fn pool_rejects_calls_over_the_block_gas_limit() {
+	new_test_ext().execute_with(|| {
+		let info = DispatchInfo::default();
+		let check = CheckBlockGas::<Test>::new();
+
+		assert_ok!(check.validate(&1, &contract_call(BLOCK_GAS_LIMIT), &info, 0));
+		assert_eq!(
+			check.validate(&1, &contract_call(BLOCK_GAS_LIMIT + 1), &info, 0),
+			Err(InvalidTransaction::ExhaustsResources.into()),
+		);
+	});
+}
+
+#[test]
+fn root_sets_block_gas_limit() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(BlockGas::set_block_gas_limit(Origin::signed(1), 10), DispatchError::BadOrigin);
+
+		assert_ok!(BlockGas::set_block_gas_limit(Origin::root(), 10));
+
+		assert_eq!(BlockGas::block_gas_limit(), 10);
+		assert_eq!(pre_dispatch(&contract_call(11)), Err(InvalidTransaction::ExhaustsResources.into()));
+	});
+}
@@ -0,0 +1,378 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Upkeep of contracts by an offchain worker.
+//!
+//! The owner of a contract registers an upkeep for it: a read-only call to a predicate of
+//! the contract that returns `true` when the contract needs upkeep, and a call to its
+//! upkeep function. After each imported block, the offchain worker of the nodes runs the
+//! predicate of the upkeeps that are due and submits a `perform_upkeep` transaction for
+//! those that need it. The transaction is signed with the first `keep` key found in the
+//! local keystore, which pays its fee, and is unsigned if there is none; keys are added
+//! with the `author_insertKey` RPC.
+//!
+//! `perform_upkeep` checks the predicate again before calling the upkeep function from the
+//! origin of the owner, who pays for the gas of both calls. The predicate is checked on chain
+//! at most once every `UpkeepInterval` blocks, whether the upkeep is needed, not needed or
+//! the check fails, which bounds the gas keepers can make the owner pay and the number of
+//! unsigned transactions.
+//! Unsigned transactions are only valid while the predicate returns `true`.
+
+use codec::{Encode, Decode};
+use frame_support::{
+	debug, decl_module, decl_storage, decl_event, decl_error, dispatch, ensure, transactional,
+	traits::Get, weights::Weight,
+};
+use frame_system::{
+	ensure_none, ensure_signed,
+	offchain::{AppCrypto, CreateSignedTransaction, SendSignedTransaction, Signer, SubmitTransaction},
+};
+use sp_core::{H160, U256, crypto::KeyTypeId};
+use sp_runtime::{
+	DispatchError, DispatchResult, RuntimeDebug,
+	offchain::storage::StorageValueRef,
+	traits::Saturating,
+	transaction_validity::{
+		InvalidTransaction, TransactionPriority, TransactionSource, TransactionValidity, ValidTransaction,
+	},
+};
+use sp_std::prelude::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+/// Key type of the keys signing upkeep transactions.
+pub const KEY_TYPE: KeyTypeId = KeyTypeId(*b"keep");
+
+/// Keys signing upkeep transactions.
+pub mod crypto {
+	use super::KEY_TYPE;
+	use sp_core::sr25519::Signature as Sr25519Signature;
+	use sp_runtime::{
+		MultiSignature, MultiSigner,
+		app_crypto::{app_crypto, sr25519},
+		traits::Verify,
+	};
+
+	app_crypto!(sr25519, KEY_TYPE);
+
+	/// Signs upkeep transactions of runtimes with `MultiSignature` signatures.
+	pub struct KeeperId;
+
+	impl frame_system::offchain::AppCrypto<MultiSigner, MultiSignature> for KeeperId {
+		type RuntimeAppPublic = Public;
+		type GenericSignature = sp_core::sr25519::Signature;
+		type GenericPublic = sp_core::sr25519::Public;
+	}
+
+	impl frame_system::offchain::AppCrypto<<Sr25519Signature as Verify>::Signer, Sr25519Signature>
+		for KeeperId
+	{
+		type RuntimeAppPublic = Public;
+		type GenericSignature = sp_core::sr25519::Signature;
+		type GenericPublic = sp_core::sr25519::Public;
+	}
+}
+
+/// The contracts whose upkeep can be performed.
+pub trait Contracts<AccountId> {
+	/// Owner of the contract at `address`, who can register an upkeep for it.
+	fn owner(address: H160) -> Option<AccountId>;
+
+	/// Whether calling the contract at `address` with `input` from `from` returns `true`,
+	/// without keeping any change made by the call.
+	fn needs_upkeep(from: &AccountId, address: H160, input: Vec<u8>, gas_limit: u32) -> bool;
+
+	/// Like `needs_upkeep`, but `from` pays for the whole gas limit of the call at
+	/// `gas_price`, which counts against the gas the calls of the block may use.
+	fn check_upkeep(
+		from: &AccountId,
+		address: H160,
+		input: Vec<u8>,
+		gas_limit: u32,
+		gas_price: U256,
+	) -> Result<bool, DispatchError>;
+
+	/// Call the contract at `address` with `input` from `from`, which pays for the gas.
+	fn perform_upkeep(
+		from: &AccountId,
+		address: H160,
+		input: Vec<u8>,
+		gas_limit: u32,
+		gas_price: U256,
+	) -> DispatchResult;
+}
+
+/// Configure the pallet by specifying the parameters and types on which it depends.
+pub trait Trait: CreateSignedTransaction<Call<Self>> {
+	/// Keys signing upkeep transactions.
+	type AuthorityId: AppCrypto<Self::Public, Self::Signature>;
+
+	/// Because this pallet emits events, it depends on the runtime's definition of an event.
+	type Event: From<Event<Self>> + Into<<Self as frame_system::Trait>::Event>;
+
+	/// The overarching dispatch call type.
+	type Call: From<Call<Self>>;
+
+	/// The contracts whose upkeep can be performed.
+	type Contracts: Contracts<Self::AccountId>;
+
+	/// Minimum number of blocks between two upkeeps of a contract.
+	type UpkeepInterval: Get<Self::BlockNumber>;
+
+	/// Priority of unsigned upkeep transactions in the transaction pool.
+	type UnsignedPriority: Get<TransactionPriority>;
+
+	/// Maximum length of the inputs of the predicate and upkeep function, in bytes.
+	type MaxInputLength: Get<u32>;
+
+	/// Maximum gas limit of the predicate and upkeep function.
+	type MaxGasLimit: Get<u32>;
+
+	/// Weight of each unit of gas the predicate and the upkeep function may use.
+	type WeightPerGas: Get<Weight>;
+}
+
+/// Upkeep registered for a contract.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct Upkeep<AccountId, BlockNumber> {
+	/// Account the predicate and the upkeep function are called from, which pays for gas.
+	pub owner: AccountId,
+	/// Input of the call to the predicate, whose output is an ABI encoded `bool`.
+	pub check_input: Vec<u8>,
+	/// Input of the call to the upkeep function.
+	pub perform_input: Vec<u8>,
+	/// Gas limit of both calls.
+	pub gas_limit: u32,
+	/// Gas price of the call to the upkeep function.
+	pub gas_price: U256,
+	/// Block the upkeep was last performed at, if any.
+	pub last_performed: Option<BlockNumber>,
+	/// Block the predicate was last checked at by `perform_upkeep`, if any.
+	pub last_checked: Option<BlockNumber>,
+}
+
+decl_storage! {
+	trait Store for Module<T: Trait> as ContractKeeper {
+		/// Upkeeps registered for contracts.
+		Upkeeps get(fn upkeep):
+			map hasher(blake2_128_concat) H160 => Option<Upkeep<T::AccountId, T::BlockNumber>>;
+	}
+}
+
+decl_event!(
+	pub enum Event<T> where AccountId = <T as frame_system::Trait>::AccountId {
+		/// An upkeep was registered for a contract. [address, owner]
+		UpkeepRegistered(H160, AccountId),
+		/// The upkeep of a contract was cancelled. [address]
+		UpkeepCancelled(H160),
+		/// The upkeep function of a contract was called. [address, result]
+		UpkeepPerformed(H160, DispatchResult),
+		/// The predicate of a contract did not return `true`, so its upkeep function was
+		/// not called. [address]
+		UpkeepNotNeeded(H160),
+	}
+);
+
+decl_error! {
+	pub enum Error for Module<T: Trait> {
+		/// Only the owner of a contract can register an upkeep for it.
+		NotOwner,
+		/// No upkeep is registered for the contract.
+		NotRegistered,
+		/// An input is longer than `MaxInputLength`.
+		InputTooLong,
+		/// The predicate was checked less than `UpkeepInterval` blocks ago.
+		TooEarly,
+		/// The gas limit is above `MaxGasLimit`.
+		GasLimitTooHigh,
+	}
+}
+
+decl_module! {
+	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+		type Error = Error<T>;
+
+		const UpkeepInterval: T::BlockNumber = T::UpkeepInterval::get();
+		const MaxInputLength: u32 = T::MaxInputLength::get();
+		const MaxGasLimit: u32 = T::MaxGasLimit::get();
+		const WeightPerGas: Weight = T::WeightPerGas::get();
+
+		fn deposit_event() = default;
+
+		/// Register an upkeep for a contract owned by the origin, replacing the previous one.
+		#[weight = 10_000 + T::DbWeight::get().reads_writes(2, 1)]
+		pub fn register_upkeep(
+			origin,
+			address: H160,
+			check_input: Vec<u8>,
+			perform_input: Vec<u8>,
+			gas_limit: u32,
+			gas_price: U256,
+		) -> dispatch::DispatchResult {
+			let who = ensure_signed(origin)?;
+			ensure!(T::Contracts::owner(address).as_ref() == Some(&who), Error::<T>::NotOwner);
+			let max = T::MaxInputLength::get() as usize;
+			ensure!(check_input.len() <= max && perform_input.len() <= max, Error::<T>::InputTooLong);
+			ensure!(gas_limit <= T::MaxGasLimit::get(), Error::<T>::GasLimitTooHigh);
+
+			<Upkeeps<T>>::insert(address, Upkeep {
+				owner: who.clone(),
+				check_input,
+				perform_input,
+				gas_limit,
+				gas_price,
+				last_performed: None,
+				last_checked: None,
+			});
+			Self::deposit_event(RawEvent::UpkeepRegistered(address, who));
+			Ok(())
+		}
+
+		/// Cancel an upkeep registered by the origin.
+		#[weight = 10_000 + T::DbWeight::get().reads_writes(1, 1)]
+		pub fn cancel_upkeep(origin, address: H160) -> dispatch::DispatchResult {
+			let who = ensure_signed(origin)?;
+			let upkeep = Self::upkeep(address).ok_or(Error::<T>::NotRegistered)?;
+			ensure!(upkeep.owner == who, Error::<T>::NotOwner);
+
+			<Upkeeps<T>>::remove(address);
+			Self::deposit_event(RawEvent::UpkeepCancelled(address));
+			Ok(())
+		}
+
+		/// Check the predicate of a contract, and call its upkeep function if it returns
+		/// `true`. Submitted by keepers, signed or not.
+		#[weight = Module::<T>::upkeep(address)
+			.map_or(0, |upkeep| T::WeightPerGas::get().saturating_mul(2 * upkeep.gas_limit as Weight))
+			.saturating_add(100_000 + T::DbWeight::get().reads_writes(2, 2))]
+		pub fn perform_upkeep(origin, address: H160) -> dispatch::DispatchResult {
+			ensure_none(origin.clone()).or_else(|_| ensure_signed(origin).map(|_| ()))?;
+			let mut upkeep = Self::upkeep(address).ok_or(Error::<T>::NotRegistered)?;
+			let now = frame_system::Module::<T>::block_number();
+			ensure!(Self::is_due(&upkeep, now), Error::<T>::TooEarly);
+
+			// Recorded outside of the storage transaction of the calls, so that a failed check
+			// cannot be submitted again, for free if unsigned, before the interval elapsed.
+			upkeep.last_checked = Some(now);
+			<Upkeeps<T>>::insert(address, &upkeep);
+			Self::check_and_perform(address, upkeep, now)
+		}
+
+		fn offchain_worker(now: T::BlockNumber) {
+			// The transactions are included from the next block on.
+			let next = now.saturating_add(1u32.into());
+			for (address, upkeep) in <Upkeeps<T>>::iter() {
+				if !Self::is_due(&upkeep, next)
+					|| !T::Contracts::needs_upkeep(&upkeep.owner, address, upkeep.check_input, upkeep.gas_limit)
+					|| Self::submitted_recently(address, next)
+				{
+					continue;
+				}
+				if let Err(e) = Self::submit_upkeep(address) {
+					debug::warn!("Failed to submit the upkeep of {:?}: {}", address, e);
+				}
+			}
+		}
+	}
+}
+
+impl<T: Trait> Module<T> {
+	/// Check the predicate of the contract at `address`, and call its upkeep function if it
+	/// returns `true`. Nothing is kept if the check fails.
+	#[transactional]
+	fn check_and_perform(
+		address: H160,
+		mut upkeep: Upkeep<T::AccountId, T::BlockNumber>,
+		now: T::BlockNumber,
+	) -> DispatchResult {
+		let needed = T::Contracts::check_upkeep(
+			&upkeep.owner,
+			address,
+			upkeep.check_input.clone(),
+			upkeep.gas_limit,
+			upkeep.gas_price,
+		)?;
+		if !needed {
+			Self::deposit_event(RawEvent::UpkeepNotNeeded(address));
+			return Ok(());
+		}
+
+		let result = T::Contracts::perform_upkeep(
+			&upkeep.owner,
+			address,
+			upkeep.perform_input.clone(),
+			upkeep.gas_limit,
+			upkeep.gas_price,
+		);
+		upkeep.last_performed = Some(now);
+		<Upkeeps<T>>::insert(address, upkeep);
+		Self::deposit_event(RawEvent::UpkeepPerformed(address, result));
+		Ok(())
+	}
+
+	/// Whether the predicate of `upkeep` can be checked at block `now`.
+	fn is_due(upkeep: &Upkeep<T::AccountId, T::BlockNumber>, now: T::BlockNumber) -> bool {
+		upkeep.last_checked
+			.map_or(true, |last| now >= last.saturating_add(T::UpkeepInterval::get()))
+	}
+
+	/// Whether this node already submitted the upkeep of `address` within the last
+	/// `UpkeepInterval` blocks before `now`, and otherwise note that it submits it now.
+	fn submitted_recently(address: H160, now: T::BlockNumber) -> bool {
+		let key = (b"contract-keeper/submitted", address).encode();
+		let storage = StorageValueRef::persistent(&key);
+		let recent = storage.get::<T::BlockNumber>()
+			.flatten()
+			.map_or(false, |submitted| now < submitted.saturating_add(T::UpkeepInterval::get()));
+		if !recent {
+			storage.set(&now);
+		}
+		recent
+	}
+
+	/// Submit a transaction performing the upkeep of `address`, signed by a local keeper
+	/// key if there is one.
+	fn submit_upkeep(address: H160) -> Result<(), &'static str> {
+		let signer = Signer::<T, T::AuthorityId>::any_account();
+		if signer.can_sign() {
+			let (_, result) = signer
+				.send_signed_transaction(|_| Call::perform_upkeep(address))
+				.ok_or("no keeper account")?;
+			result.map_err(|()| "failed to submit signed transaction")
+		} else {
+			SubmitTransaction::<T, Call<T>>::submit_unsigned_transaction(Call::perform_upkeep(address).into())
+				.map_err(|()| "failed to submit unsigned transaction")
+		}
+	}
+}
+
+impl<T: Trait> frame_support::unsigned::ValidateUnsigned for Module<T> {
+	type Call = Call<T>;
+
+	/// Unsigned upkeeps are valid once due and while the predicate returns `true`, at most
+	/// once per check.
+	fn validate_unsigned(_source: TransactionSource, call: &Self::Call) -> TransactionValidity {
+		let address = match call {
+			Call::perform_upkeep(address) => *address,
+			_ => return InvalidTransaction::Call.into(),
+		};
+		let upkeep = Self::upkeep(address).ok_or(InvalidTransaction::Call)?;
+		let next = frame_system::Module::<T>::block_number().saturating_add(1u32.into());
+		if !Self::is_due(&upkeep, next) {
+			return InvalidTransaction::Future.into();
+		}
+		if !T::Contracts::needs_upkeep(&upkeep.owner, address, upkeep.check_input.clone(), upkeep.gas_limit) {
+			return InvalidTransaction::Stale.into();
+		}
+
+		ValidTransaction::with_tag_prefix("ContractKeeper")
+			.priority(T::UnsignedPriority::get())
+			.and_provides((address, upkeep.last_checked))
+			.longevity(5)
+			.propagate(true)
+			.build()
+	}
+}
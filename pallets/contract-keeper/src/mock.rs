@@ -0,0 +1,213 @@
+use crate::{Contracts, Module, Trait, crypto};
+use sp_core::{H160, H256, U256, sr25519};
+use frame_support::{impl_outer_origin, parameter_types, weights::Weight};
+use sp_runtime::{
+	DispatchError, DispatchResult, Perbill,
+	testing::{Header, TestXt},
+	traits::{BlakeTwo256, Extrinsic as ExtrinsicT, IdentifyAccount, IdentityLookup, Verify},
+};
+use sp_std::cell::RefCell;
+use frame_system as system;
+
+impl_outer_origin! {
+	pub enum Origin for Test {}
+}
+
+// Configure a mock runtime to test the pallet.
+
+#[derive(Clone, Eq, PartialEq)]
+pub struct Test;
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+	pub const MaximumBlockWeight: Weight = 1024;
+	pub const MaximumBlockLength: u32 = 2 * 1024;
+	pub const AvailableBlockRatio: Perbill = Perbill::from_percent(75);
+}
+
+type Signature = sr25519::Signature;
+pub type AccountId = <<Signature as Verify>::Signer as IdentifyAccount>::AccountId;
+
+impl system::Trait for Test {
+	type BaseCallFilter = ();
+	type Origin = Origin;
+	type Call = ();
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = AccountId;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = ();
+	type BlockHashCount = BlockHashCount;
+	type MaximumBlockWeight = MaximumBlockWeight;
+	type DbWeight = ();
+	type BlockExecutionWeight = ();
+	type ExtrinsicBaseWeight = ();
+	type MaximumExtrinsicWeight = MaximumBlockWeight;
+	type MaximumBlockLength = MaximumBlockLength;
+	type AvailableBlockRatio = AvailableBlockRatio;
+	type Version = ();
+	type PalletInfo = ();
+	type AccountData = ();
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+}
+
+pub type Extrinsic = TestXt<crate::Call<Test>, ()>;
+
+impl frame_system::offchain::SigningTypes for Test {
+	type Public = <Signature as Verify>::Signer;
+	type Signature = Signature;
+}
+
+impl<C> frame_system::offchain::SendTransactionTypes<C> for Test where
+	crate::Call<Test>: From<C>,
+{
+	type OverarchingCall = crate::Call<Test>;
+	type Extrinsic = Extrinsic;
+}
+
+impl<C> frame_system::offchain::CreateSignedTransaction<C> for Test where
+	crate::Call<Test>: From<C>,
+{
+	fn create_transaction<A: frame_system::offchain::AppCrypto<Self::Public, Self::Signature>>(
+		call: crate::Call<Test>,
+		_public: <Signature as Verify>::Signer,
+		_account: AccountId,
+		nonce: u64,
+	) -> Option<(crate::Call<Test>, <Extrinsic as ExtrinsicT>::SignaturePayload)> {
+		Some((call, (nonce, ())))
+	}
+}
+
+/// State of the contracts of the mock runtime.
+#[derive(Default)]
+pub struct ContractsState {
+	/// Owner of the contract at `contract()`.
+	pub owner: Option<AccountId>,
+	/// Whether the predicate of the contract returns `true`.
+	pub needs_upkeep: bool,
+	/// Whether checking the predicate fails, e.g. because the owner cannot pay for gas.
+	pub check_fails: bool,
+	/// Calls to the upkeep function of the contract, with their caller.
+	pub performed: Vec<(AccountId, Vec<u8>)>,
+	/// Calls to the predicate of the contract paying for gas, with their caller and gas cost.
+	pub checked: Vec<(AccountId, U256)>,
+}
+
+thread_local! {
+	pub static CONTRACTS: RefCell<ContractsState> = RefCell::new(Default::default());
+}
+
+pub fn set_needs_upkeep(needs_upkeep: bool) {
+	CONTRACTS.with(|contracts| contracts.borrow_mut().needs_upkeep = needs_upkeep);
+}
+
+pub fn set_check_fails(check_fails: bool) {
+	CONTRACTS.with(|contracts| contracts.borrow_mut().check_fails = check_fails);
+}
+
+pub fn performed() -> Vec<(AccountId, Vec<u8>)> {
+	CONTRACTS.with(|contracts| contracts.borrow().performed.clone())
+}
+
+pub fn checked() -> Vec<(AccountId, U256)> {
+	CONTRACTS.with(|contracts| contracts.borrow().checked.clone())
+}
+
+/// A single contract, at `contract()` and owned by `owner()`.
+pub struct MockContracts;
+
+impl Contracts<AccountId> for MockContracts {
+	fn owner(address: H160) -> Option<AccountId> {
+		if address == contract() {
+			CONTRACTS.with(|contracts| contracts.borrow().owner.clone())
+		} else {
+			None
+		}
+	}
+
+	fn needs_upkeep(_from: &AccountId, address: H160, input: Vec<u8>, _gas_limit: u32) -> bool {
+		address == contract() && input == CHECK_INPUT && CONTRACTS.with(|contracts| contracts.borrow().needs_upkeep)
+	}
+
+	fn check_upkeep(
+		from: &AccountId,
+		address: H160,
+		input: Vec<u8>,
+		gas_limit: u32,
+		gas_price: U256,
+	) -> Result<bool, DispatchError> {
+		if CONTRACTS.with(|contracts| contracts.borrow().check_fails) {
+			return Err(DispatchError::Other("cannot pay for gas"));
+		}
+		let cost = gas_price * U256::from(gas_limit);
+		CONTRACTS.with(|contracts| contracts.borrow_mut().checked.push((from.clone(), cost)));
+		Ok(Self::needs_upkeep(from, address, input, gas_limit))
+	}
+
+	fn perform_upkeep(
+		from: &AccountId,
+		_address: H160,
+		input: Vec<u8>,
+		_gas_limit: u32,
+		_gas_price: U256,
+	) -> DispatchResult {
+		CONTRACTS.with(|contracts| contracts.borrow_mut().performed.push((from.clone(), input)));
+		Ok(())
+	}
+}
+
+parameter_types! {
+	pub const UpkeepInterval: u64 = 10;
+	pub const UnsignedPriority: u64 = 1 << 20;
+	pub const MaxInputLength: u32 = 8;
+	pub const MaxGasLimit: u32 = 1_000_000;
+	pub const WeightPerGas: Weight = 2;
+}
+
+impl Trait for Test {
+	type AuthorityId = crypto::KeeperId;
+	type Event = ();
+	type Call = crate::Call<Test>;
+	type Contracts = MockContracts;
+	type UpkeepInterval = UpkeepInterval;
+	type UnsignedPriority = UnsignedPriority;
+	type MaxInputLength = MaxInputLength;
+	type MaxGasLimit = MaxGasLimit;
+	type WeightPerGas = WeightPerGas;
+}
+
+pub const CHECK_INPUT: &[u8] = b"check";
+pub const PERFORM_INPUT: &[u8] = b"perform";
+
+pub fn contract() -> H160 {
+	H160::repeat_byte(1)
+}
+
+pub fn owner() -> AccountId {
+	sr25519::Public::from_raw([1; 32])
+}
+
+pub fn other() -> AccountId {
+	sr25519::Public::from_raw([2; 32])
+}
+
+pub type System = system::Module<Test>;
+pub type ContractKeeper = Module<Test>;
+
+// Build genesis storage according to the mock runtime, at block 1 with `contract()` owned
+// by `owner()`.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let t = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	CONTRACTS.with(|contracts| *contracts.borrow_mut() = ContractsState {
+		owner: Some(owner()),
+		..Default::default()
+	});
+
+	let mut ext: sp_io::TestExternalities = t.into();
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
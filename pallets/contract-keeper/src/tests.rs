@@ -0,0 +1,212 @@
+use crate::{Error, Upkeep, mock::*};
+use codec::Decode;
+use frame_support::{assert_ok, assert_noop, traits::{Get, OffchainWorker}, unsigned::ValidateUnsigned};
+use sp_core::{
+	offchain::{OffchainExt, TransactionPoolExt, testing::{TestOffchainExt, TestTransactionPoolExt}},
+	testing::KeyStore,
+	traits::KeystoreExt,
+};
+use sp_runtime::{
+	DispatchError, RuntimeAppPublic,
+	transaction_validity::{InvalidTransaction, TransactionSource},
+};
+
+fn register(who: AccountId) -> frame_support::dispatch::DispatchResult {
+	ContractKeeper::register_upkeep(
+		Origin::signed(who),
+		contract(),
+		CHECK_INPUT.to_vec(),
+		PERFORM_INPUT.to_vec(),
+		100_000,
+		1.into(),
+	)
+}
+
+#[test]
+fn owner_registers_and_cancels_upkeep() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(register(other()), Error::<Test>::NotOwner);
+		assert_noop!(
+			ContractKeeper::register_upkeep(
+				Origin::signed(owner()),
+				contract(),
+				vec![0; 9],
+				vec![],
+				100_000,
+				1.into(),
+			),
+			Error::<Test>::InputTooLong,
+		);
+		assert_noop!(
+			ContractKeeper::register_upkeep(
+				Origin::signed(owner()),
+				contract(),
+				vec![],
+				vec![],
+				MaxGasLimit::get() + 1,
+				1.into(),
+			),
+			Error::<Test>::GasLimitTooHigh,
+		);
+
+		assert_ok!(register(owner()));
+		assert_eq!(ContractKeeper::upkeep(contract()), Some(Upkeep {
+			owner: owner(),
+			check_input: CHECK_INPUT.to_vec(),
+			perform_input: PERFORM_INPUT.to_vec(),
+			gas_limit: 100_000,
+			gas_price: 1.into(),
+			last_performed: None,
+			last_checked: None,
+		}));
+
+		assert_noop!(ContractKeeper::cancel_upkeep(Origin::signed(other()), contract()), Error::<Test>::NotOwner);
+		assert_ok!(ContractKeeper::cancel_upkeep(Origin::signed(owner()), contract()));
+		assert_eq!(ContractKeeper::upkeep(contract()), None);
+	});
+}
+
+#[test]
+fn upkeep_is_performed_from_owner_when_needed_and_due() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(ContractKeeper::perform_upkeep(Origin::none(), contract()), Error::<Test>::NotRegistered);
+		assert_ok!(register(owner()));
+
+		// The owner pays for checking the predicate even if the upkeep is not needed, and
+		// it is not checked again before the interval elapsed.
+		assert_ok!(ContractKeeper::perform_upkeep(Origin::none(), contract()));
+		assert!(performed().is_empty());
+		assert_eq!(checked(), vec![(owner(), 100_000.into())]);
+		let upkeep = ContractKeeper::upkeep(contract()).unwrap();
+		assert_eq!((upkeep.last_checked, upkeep.last_performed), (Some(1), None));
+		set_needs_upkeep(true);
+		assert_noop!(ContractKeeper::perform_upkeep(Origin::none(), contract()), Error::<Test>::TooEarly);
+
+		System::set_block_number(11);
+		assert_ok!(ContractKeeper::perform_upkeep(Origin::none(), contract()));
+		assert_eq!(performed(), vec![(owner(), PERFORM_INPUT.to_vec())]);
+		assert_eq!(checked().len(), 2);
+		assert_eq!(ContractKeeper::upkeep(contract()).unwrap().last_performed, Some(11));
+
+		// Keepers can sign their transactions as well.
+		System::set_block_number(20);
+		assert_noop!(ContractKeeper::perform_upkeep(Origin::signed(other()), contract()), Error::<Test>::TooEarly);
+		System::set_block_number(21);
+		assert_ok!(ContractKeeper::perform_upkeep(Origin::signed(other()), contract()));
+		assert_eq!(performed().len(), 2);
+		assert_noop!(ContractKeeper::perform_upkeep(Origin::root(), contract()), DispatchError::BadOrigin);
+	});
+}
+
+#[test]
+fn failed_checks_wait_for_the_interval() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(register(owner()));
+		set_needs_upkeep(true);
+		set_check_fails(true);
+
+		assert!(ContractKeeper::perform_upkeep(Origin::none(), contract()).is_err());
+		assert_eq!(ContractKeeper::upkeep(contract()).unwrap().last_checked, Some(1));
+		assert_eq!(
+			ContractKeeper::validate_unsigned(TransactionSource::External, &crate::Call::perform_upkeep(contract())),
+			InvalidTransaction::Future.into(),
+		);
+		assert_noop!(ContractKeeper::perform_upkeep(Origin::none(), contract()), Error::<Test>::TooEarly);
+
+		System::set_block_number(11);
+		set_check_fails(false);
+		assert_ok!(ContractKeeper::perform_upkeep(Origin::none(), contract()));
+		assert_eq!(performed(), vec![(owner(), PERFORM_INPUT.to_vec())]);
+	});
+}
+
+#[test]
+fn upkeep_weight_grows_with_gas_limit() {
+	new_test_ext().execute_with(|| {
+		use frame_support::weights::GetDispatchInfo;
+
+		let call = crate::Call::<Test>::perform_upkeep(contract());
+		let base = call.get_dispatch_info().weight;
+		assert_ok!(register(owner()));
+		assert_eq!(call.get_dispatch_info().weight, base + 2 * 100_000 * WeightPerGas::get());
+	});
+}
+
+#[test]
+fn unsigned_upkeeps_are_valid_once_due() {
+	new_test_ext().execute_with(|| {
+		let call = crate::Call::perform_upkeep(contract());
+		assert_eq!(
+			ContractKeeper::validate_unsigned(TransactionSource::External, &call),
+			InvalidTransaction::Call.into(),
+		);
+
+		assert_ok!(register(owner()));
+		assert_eq!(
+			ContractKeeper::validate_unsigned(TransactionSource::External, &call),
+			InvalidTransaction::Stale.into(),
+		);
+
+		set_needs_upkeep(true);
+		assert!(ContractKeeper::validate_unsigned(TransactionSource::External, &call).is_ok());
+		assert_ok!(ContractKeeper::perform_upkeep(Origin::none(), contract()));
+		assert_eq!(
+			ContractKeeper::validate_unsigned(TransactionSource::External, &call),
+			InvalidTransaction::Future.into(),
+		);
+
+		System::set_block_number(10);
+		assert!(ContractKeeper::validate_unsigned(TransactionSource::External, &call).is_ok());
+	});
+}
+
+#[test]
+fn offchain_worker_submits_upkeeps_that_are_needed() {
+	let (offchain, _) = TestOffchainExt::new();
+	let (pool, pool_state) = TestTransactionPoolExt::new();
+	let mut ext = new_test_ext();
+	ext.register_extension(OffchainExt::new(offchain));
+	ext.register_extension(TransactionPoolExt::new(pool));
+
+	ext.execute_with(|| {
+		assert_ok!(register(owner()));
+		ContractKeeper::offchain_worker(1);
+		assert!(pool_state.read().transactions.is_empty());
+
+		set_needs_upkeep(true);
+		ContractKeeper::offchain_worker(2);
+		// Not submitted again while the transaction is pending.
+		ContractKeeper::offchain_worker(3);
+
+		let transactions = &pool_state.read().transactions;
+		assert_eq!(transactions.len(), 1);
+		let tx = Extrinsic::decode(&mut &transactions[0][..]).unwrap();
+		assert_eq!(tx.signature, None);
+		assert_eq!(tx.call, crate::Call::perform_upkeep(contract()));
+	});
+}
+
+#[test]
+fn offchain_worker_signs_upkeeps_with_local_keeper_key() {
+	let (offchain, _) = TestOffchainExt::new();
+	let (pool, pool_state) = TestTransactionPoolExt::new();
+	let keystore = KeyStore::new();
+	keystore.write().sr25519_generate_new(crate::KEY_TYPE, Some("//Keeper")).unwrap();
+	let mut ext = new_test_ext();
+	ext.register_extension(OffchainExt::new(offchain));
+	ext.register_extension(TransactionPoolExt::new(pool));
+	ext.register_extension(KeystoreExt(keystore));
+
+	ext.execute_with(|| {
+		assert_eq!(crate::crypto::Public::all().len(), 1);
+		assert_ok!(register(owner()));
+		set_needs_upkeep(true);
+		ContractKeeper::offchain_worker(1);
+
+		let transactions = &pool_state.read().transactions;
+		assert_eq!(transactions.len(), 1);
+		let tx = Extrinsic::decode(&mut &transactions[0][..]).unwrap();
+		assert_eq!(tx.signature, Some((0, ())));
+		assert_eq!(tx.call, crate::Call::perform_upkeep(contract()));
+	});
+}
@@ -0,0 +1,211 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Registry of the metadata of SSVM contracts.
+//!
+//! The account that deployed a contract can attach to its address the hash of its ABI,
+//! the URL of its source and the version of the compiler that built it, so that explorers
+//! and tools can find out how to encode calls to the contract and decode their outputs.
+//! Registering reserves a deposit from the deployer, who becomes the owner of the entry
+//! and is the only one allowed to update or remove it. Removing an entry returns the
+//! deposit.
+//!
+//! Deployers are not known to `pallet_ssvm`, so the runtime reports them with
+//! `note_deployed` whenever a contract is created. Root can set the deployer of contracts
+//! the runtime did not see created, such as those deployed before this pallet was added.
+
+use codec::{Encode, Decode};
+use frame_support::{
+	decl_module, decl_storage, decl_event, decl_error, dispatch, ensure,
+	traits::{Currency, Get, ReservableCurrency},
+};
+use frame_system::{ensure_root, ensure_signed};
+use sp_core::{H160, H256};
+use sp_runtime::RuntimeDebug;
+use sp_std::prelude::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+type BalanceOf<T> =
+	<<T as Trait>::Currency as Currency<<T as frame_system::Trait>::AccountId>>::Balance;
+
+/// Configure the pallet by specifying the parameters and types on which it depends.
+pub trait Trait: frame_system::Trait {
+	/// Because this pallet emits events, it depends on the runtime's definition of an event.
+	type Event: From<Event<Self>> + Into<<Self as frame_system::Trait>::Event>;
+
+	/// The currency in which deposits are reserved.
+	type Currency: ReservableCurrency<Self::AccountId>;
+
+	/// Amount reserved from the owner of each registered contract.
+	type MetadataDeposit: Get<BalanceOf<Self>>;
+
+	/// Maximum length of source URLs and compiler versions, in bytes.
+	type MaxFieldLength: Get<u32>;
+}
+
+/// Metadata registered for a contract.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct ContractMetadata<AccountId, Balance> {
+	/// Account allowed to update the metadata.
+	pub owner: AccountId,
+	/// Amount reserved from the owner.
+	pub deposit: Balance,
+	/// Keccak-256 hash of the JSON ABI of the contract.
+	pub abi_hash: H256,
+	/// Where the source of the contract can be found.
+	pub source_url: Vec<u8>,
+	/// Version of the compiler that built the contract.
+	pub compiler_version: Vec<u8>,
+}
+
+decl_storage! {
+	trait Store for Module<T: Trait> as ContractRegistry {
+		/// Account that deployed each contract.
+		Deployers get(fn deployer): map hasher(blake2_128_concat) H160 => Option<T::AccountId>;
+
+		/// Metadata of registered contracts.
+		Metadata get(fn metadata):
+			map hasher(blake2_128_concat) H160 => Option<ContractMetadata<T::AccountId, BalanceOf<T>>>;
+	}
+}
+
+decl_event!(
+	pub enum Event<T> where AccountId = <T as frame_system::Trait>::AccountId {
+		/// Metadata was registered for a contract. [address, owner]
+		Registered(H160, AccountId),
+		/// The metadata of a contract was updated. [address]
+		Updated(H160),
+		/// The metadata of a contract was removed. [address]
+		Unregistered(H160),
+		/// The deployer of a contract was set by Root. [address, deployer]
+		DeployerSet(H160, AccountId),
+	}
+);
+
+decl_error! {
+	pub enum Error for Module<T: Trait> {
+		/// Only the account that deployed a contract can register it.
+		NotDeployer,
+		/// The contract is already registered.
+		AlreadyRegistered,
+		/// The contract is not registered.
+		NotRegistered,
+		/// Only the owner of the metadata can change it.
+		NotOwner,
+		/// The source URL or the compiler version is longer than `MaxFieldLength`.
+		FieldTooLong,
+	}
+}
+
+decl_module! {
+	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+		type Error = Error<T>;
+
+		const MetadataDeposit: BalanceOf<T> = T::MetadataDeposit::get();
+		const MaxFieldLength: u32 = T::MaxFieldLength::get();
+
+		fn deposit_event() = default;
+
+		/// Register the metadata of a contract deployed by the origin, reserving
+		/// `MetadataDeposit` from it.
+		#[weight = 10_000 + T::DbWeight::get().reads_writes(2, 2)]
+		pub fn register(
+			origin,
+			address: H160,
+			abi_hash: H256,
+			source_url: Vec<u8>,
+			compiler_version: Vec<u8>,
+		) -> dispatch::DispatchResult {
+			let who = ensure_signed(origin)?;
+			ensure!(Self::deployer(address).as_ref() == Some(&who), Error::<T>::NotDeployer);
+			ensure!(!Metadata::<T>::contains_key(address), Error::<T>::AlreadyRegistered);
+			Self::ensure_field_lengths(&source_url, &compiler_version)?;
+
+			let deposit = T::MetadataDeposit::get();
+			T::Currency::reserve(&who, deposit)?;
+			Metadata::<T>::insert(address, ContractMetadata {
+				owner: who.clone(),
+				deposit,
+				abi_hash,
+				source_url,
+				compiler_version,
+			});
+
+			Self::deposit_event(RawEvent::Registered(address, who));
+			Ok(())
+		}
+
+		/// Replace the metadata of a contract. Must be called by its owner.
+		#[weight = 10_000 + T::DbWeight::get().reads_writes(1, 1)]
+		pub fn update(
+			origin,
+			address: H160,
+			abi_hash: H256,
+			source_url: Vec<u8>,
+			compiler_version: Vec<u8>,
+		) -> dispatch::DispatchResult {
+			let who = ensure_signed(origin)?;
+			let mut metadata = Self::owned_metadata(address, &who)?;
+			Self::ensure_field_lengths(&source_url, &compiler_version)?;
+
+			metadata.abi_hash = abi_hash;
+			metadata.source_url = source_url;
+			metadata.compiler_version = compiler_version;
+			Metadata::<T>::insert(address, metadata);
+
+			Self::deposit_event(RawEvent::Updated(address));
+			Ok(())
+		}
+
+		/// Remove the metadata of a contract and return the deposit. Must be called by its owner.
+		#[weight = 10_000 + T::DbWeight::get().reads_writes(2, 2)]
+		pub fn unregister(origin, address: H160) -> dispatch::DispatchResult {
+			let who = ensure_signed(origin)?;
+			let metadata = Self::owned_metadata(address, &who)?;
+
+			T::Currency::unreserve(&who, metadata.deposit);
+			Metadata::<T>::remove(address);
+
+			Self::deposit_event(RawEvent::Unregistered(address));
+			Ok(())
+		}
+
+		/// Set the account that deployed a contract, which lets it register the metadata of
+		/// the contract. Must be called by Root.
+		#[weight = 10_000 + T::DbWeight::get().writes(1)]
+		pub fn force_set_deployer(origin, address: H160, deployer: T::AccountId) -> dispatch::DispatchResult {
+			ensure_root(origin)?;
+
+			Self::note_deployed(address, deployer.clone());
+
+			Self::deposit_event(RawEvent::DeployerSet(address, deployer));
+			Ok(())
+		}
+	}
+}
+
+impl<T: Trait> Module<T> {
+	/// Record that `deployer` created the contract at `address`.
+	pub fn note_deployed(address: H160, deployer: T::AccountId) {
+		Deployers::<T>::insert(address, deployer);
+	}
+
+	fn owned_metadata(
+		address: H160,
+		who: &T::AccountId,
+	) -> Result<ContractMetadata<T::AccountId, BalanceOf<T>>, Error<T>> {
+		let metadata = Self::metadata(address).ok_or(Error::<T>::NotRegistered)?;
+		ensure!(&metadata.owner == who, Error::<T>::NotOwner);
+		Ok(metadata)
+	}
+
+	fn ensure_field_lengths(source_url: &[u8], compiler_version: &[u8]) -> Result<(), Error<T>> {
+		let max = T::MaxFieldLength::get() as usize;
+		ensure!(source_url.len() <= max && compiler_version.len() <= max, Error::<T>::FieldTooLong);
+		Ok(())
+	}
+}
@@ -0,0 +1,125 @@
+use crate::{ContractMetadata, Error, mock::*};
+use frame_support::{assert_ok, assert_noop};
+use sp_core::{H160, H256};
+
+fn register(who: u64, address: H160) -> frame_support::dispatch::DispatchResult {
+	ContractRegistry::register(
+		Origin::signed(who),
+		address,
+		H256::repeat_byte(7),
+		b"https://example.com/erc20.sol".to_vec(),
+		b"0.5.17".to_vec(),
+	)
+}
+
+#[test]
+fn deployer_registers_metadata_against_deposit() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(register(DEPLOYER, contract()));
+
+		assert_eq!(ContractRegistry::metadata(contract()), Some(ContractMetadata {
+			owner: DEPLOYER,
+			deposit: 100,
+			abi_hash: H256::repeat_byte(7),
+			source_url: b"https://example.com/erc20.sol".to_vec(),
+			compiler_version: b"0.5.17".to_vec(),
+		}));
+		assert_eq!(Balances::reserved_balance(DEPLOYER), 100);
+		assert_eq!(Balances::free_balance(DEPLOYER), 900);
+	});
+}
+
+#[test]
+fn only_deployer_registers() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(register(OTHER, contract()), Error::<Test>::NotDeployer);
+		assert_noop!(register(DEPLOYER, H160::repeat_byte(2)), Error::<Test>::NotDeployer);
+
+		assert_ok!(register(DEPLOYER, contract()));
+		assert_noop!(register(DEPLOYER, contract()), Error::<Test>::AlreadyRegistered);
+	});
+}
+
+#[test]
+fn rejects_long_fields() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			ContractRegistry::register(
+				Origin::signed(DEPLOYER),
+				contract(),
+				H256::zero(),
+				vec![b'a'; 33],
+				vec![],
+			),
+			Error::<Test>::FieldTooLong,
+		);
+	});
+}
+
+#[test]
+fn only_owner_updates() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			ContractRegistry::update(Origin::signed(DEPLOYER), contract(), H256::zero(), vec![], vec![]),
+			Error::<Test>::NotRegistered,
+		);
+		assert_ok!(register(DEPLOYER, contract()));
+
+		assert_noop!(
+			ContractRegistry::update(Origin::signed(OTHER), contract(), H256::zero(), vec![], vec![]),
+			Error::<Test>::NotOwner,
+		);
+		assert_ok!(ContractRegistry::update(
+			Origin::signed(DEPLOYER),
+			contract(),
+			H256::repeat_byte(8),
+			vec![],
+			b"0.8.0".to_vec(),
+		));
+
+		let metadata = ContractRegistry::metadata(contract()).unwrap();
+		assert_eq!(metadata.abi_hash, H256::repeat_byte(8));
+		assert_eq!(metadata.compiler_version, b"0.8.0".to_vec());
+		assert_eq!(Balances::reserved_balance(DEPLOYER), 100);
+	});
+}
+
+#[test]
+fn unregister_returns_deposit() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(register(DEPLOYER, contract()));
+		assert_noop!(
+			ContractRegistry::unregister(Origin::signed(OTHER), contract()),
+			Error::<Test>::NotOwner,
+		);
+
+		assert_ok!(ContractRegistry::unregister(Origin::signed(DEPLOYER), contract()));
+		assert_eq!(ContractRegistry::metadata(contract()), None);
+		assert_eq!(Balances::reserved_balance(DEPLOYER), 0);
+		assert_eq!(Balances::free_balance(DEPLOYER), 1_000);
+	});
+}
+
+#[test]
+fn register_requires_deposit() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Balances::transfer(Origin::signed(DEPLOYER), OTHER, 950));
+		assert!(register(DEPLOYER, contract()).is_err());
+		assert_eq!(ContractRegistry::metadata(contract()), None);
+	});
+}
+
+#[test]
+fn root_sets_deployer() {
+	new_test_ext().execute_with(|| {
+		let address = H160::repeat_byte(2);
+		assert_noop!(
+			ContractRegistry::force_set_deployer(Origin::signed(OTHER), address, OTHER),
+			sp_runtime::DispatchError::BadOrigin,
+		);
+
+		assert_ok!(ContractRegistry::force_set_deployer(Origin::root(), address, OTHER));
+		assert_eq!(ContractRegistry::deployer(address), Some(OTHER));
+		assert_ok!(register(OTHER, address));
+	});
+}
@@ -0,0 +1,74 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Revert reasons of failed contract calls.
+//!
+//! When a contract reverts, the data it returns usually explains why. Solidity encodes
+//! `revert("reason")` and failed `require`s as a call to `Error(string)`, which this
+//! pallet decodes so that clients get the reason itself instead of an opaque failure.
+//! The runtime reports failed calls with `Module::note_revert`, which deposits a
+//! `ContractReverted` event.
+
+use sp_std::prelude::*;
+use frame_support::{decl_module, decl_event};
+use sp_core::{H160, U256};
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+/// Selector of Solidity's `Error(string)`, which `revert("reason")` returns.
+pub const ERROR_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+
+/// Configure the pallet by specifying the parameters and types on which it depends.
+pub trait Trait: frame_system::Trait {
+	/// Because this pallet emits events, it depends on the runtime's definition of an event.
+	type Event: From<Event> + Into<<Self as frame_system::Trait>::Event>;
+}
+
+decl_event!(
+	pub enum Event {
+		/// A contract call reverted, with the decoded reason if the contract returned an
+		/// `Error(string)`, or the raw revert data otherwise. [address, reason]
+		ContractReverted(H160, Vec<u8>),
+	}
+);
+
+decl_module! {
+	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+		fn deposit_event() = default;
+	}
+}
+
+impl<T: Trait> Module<T> {
+	/// Report that a call to the contract at `address` reverted with `data`.
+	pub fn note_revert(address: H160, data: &[u8]) {
+		let reason = decode_revert_reason(data).unwrap_or_else(|| data.to_vec());
+		Self::deposit_event(Event::ContractReverted(address, reason));
+	}
+}
+
+/// Decode the reason of an `Error(string)` revert, `None` if `data` is something else.
+pub fn decode_revert_reason(data: &[u8]) -> Option<Vec<u8>> {
+	if !data.starts_with(&ERROR_SELECTOR) {
+		return None;
+	}
+	let arguments = &data[ERROR_SELECTOR.len()..];
+
+	// The only argument is dynamic: its head is the offset of the length prefixed bytes.
+	let offset = read_word(arguments, 0)?;
+	let length = read_word(arguments, offset)?;
+	let start = offset.checked_add(32)?;
+	arguments.get(start..start.checked_add(length)?).map(|reason| reason.to_vec())
+}
+
+/// Read the ABI word at `offset` as a `usize`.
+fn read_word(data: &[u8], offset: usize) -> Option<usize> {
+	let word = U256::from_big_endian(data.get(offset..offset.checked_add(32)?)?);
+	if word > U256::from(usize::max_value()) {
+		None
+	} else {
+		Some(word.as_usize())
+	}
+}
@@ -0,0 +1,70 @@
+use crate::{mock::*, decode_revert_reason, Event, ERROR_SELECTOR};
+use sp_core::{H160, U256};
+
+/// ABI encoding of a call to `Error(string)` with `reason`.
+fn error_string(reason: &[u8]) -> Vec<u8> {
+	let word = |value: usize| {
+		let mut word = [0u8; 32];
+		U256::from(value).to_big_endian(&mut word);
+		word.to_vec()
+	};
+	let mut data = ERROR_SELECTOR.to_vec();
+	data.extend(word(32));
+	data.extend(word(reason.len()));
+	data.extend(reason);
+	// Pad the bytes to a whole number of words.
+	data.resize(data.len() + (32 - reason.len() % 32) % 32, 0);
+	data
+}
+
+fn last_event() -> TestEvent {
+	System::events().pop().expect("an event was deposited").event
+}
+
+#[test]
+fn decodes_error_string() {
+	let reason = b"ERC20: transfer amount exceeds allowance";
+	assert_eq!(decode_revert_reason(&error_string(reason)), Some(reason.to_vec()));
+	assert_eq!(decode_revert_reason(&error_string(b"")), Some(vec![]));
+}
+
+#[test]
+fn ignores_other_revert_data() {
+	assert_eq!(decode_revert_reason(&[]), None);
+	// `Panic(uint256)`.
+	assert_eq!(decode_revert_reason(&[0x4e, 0x48, 0x7b, 0x71]), None);
+	// A custom error returning a single word.
+	assert_eq!(decode_revert_reason(&[0xab; 36]), None);
+}
+
+#[test]
+fn rejects_truncated_error_string() {
+	let data = error_string(b"insufficient allowance");
+	assert_eq!(decode_revert_reason(&data[..4 + 32 + 32 + 10]), None);
+	assert_eq!(decode_revert_reason(&data[..4 + 16]), None);
+
+	// Offset and length pointing outside of the data.
+	let mut data = error_string(b"reason");
+	data[4 + 31] = 0xff;
+	assert_eq!(decode_revert_reason(&data), None);
+	let mut data = error_string(b"reason");
+	data[4 + 32] = 0xff;
+	assert_eq!(decode_revert_reason(&data), None);
+}
+
+#[test]
+fn note_revert_deposits_decoded_reason() {
+	new_test_ext().execute_with(|| {
+		let address = H160::repeat_byte(1);
+
+		ContractRevert::note_revert(address, &error_string(b"insufficient allowance"));
+		assert_eq!(
+			last_event(),
+			TestEvent::contract_revert(Event::ContractReverted(address, b"insufficient allowance".to_vec())),
+		);
+
+		// Revert data that is not an `Error(string)` is reported as is.
+		ContractRevert::note_revert(address, &[1, 2, 3]);
+		assert_eq!(last_event(), TestEvent::contract_revert(Event::ContractReverted(address, vec![1, 2, 3])));
+	});
+}
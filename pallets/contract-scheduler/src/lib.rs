@@ -0,0 +1,376 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Calls scheduled by accounts.
+//!
+//! `pallet_scheduler` only lets privileged origins schedule calls, since scheduled calls
+//! are dispatched without going through the signed extensions that charge for them. This
+//! pallet lets any account schedule a call paying for gas, such as `ssvm.call`, at a future
+//! block and optionally periodically after it. The gas of every execution is reserved from
+//! the account upfront, along with a deposit for the storage of the call, and cancelling the
+//! remaining executions returns their reserve. The reserve of an execution is returned to
+//! the account right before the call is dispatched, so that the call pays for its gas as it
+//! would as an extrinsic.
+//!
+//! Executions count against the gas the calls of a block may use. When a block has no gas
+//! left for an execution, it keeps its reserve and is postponed to the next block.
+//!
+//! The calls are dispatched from the origin of the account that scheduled them, through
+//! `execute`, which the scheduler dispatches from the root origin. Only the next execution
+//! of a call is in the scheduler at any time: each execution schedules the one after it,
+//! so that postponing an execution delays the following ones.
+
+use codec::{Encode, Decode};
+use frame_support::{
+	decl_module, decl_storage, decl_event, decl_error, dispatch, ensure, transactional,
+	dispatch::Parameter,
+	traits::{
+		Currency, Get, ReservableCurrency,
+		schedule::{DispatchTime, Named as ScheduleNamed, Period, LOWEST_PRIORITY},
+	},
+	weights::{GetDispatchInfo, PostDispatchInfo},
+};
+use frame_system::{ensure_root, ensure_signed, RawOrigin};
+use sp_runtime::{DispatchResult, RuntimeDebug, traits::{Dispatchable, One, Saturating, Zero}};
+use sp_std::prelude::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+type BalanceOf<T> =
+	<<T as Trait>::Currency as Currency<<T as frame_system::Trait>::AccountId>>::Balance;
+type ScheduledCallOf<T> = ScheduledCall<
+	<T as frame_system::Trait>::AccountId,
+	BalanceOf<T>,
+	<T as frame_system::Trait>::BlockNumber,
+	<T as Trait>::Call,
+>;
+
+/// Identifier of a scheduled call.
+pub type TaskId = u64;
+
+/// Prefix of the names under which calls are scheduled in the scheduler.
+const SCHEDULE_PREFIX: &[u8] = b"contract-scheduler";
+
+/// Gas paid by calls. Only calls paying for gas can be scheduled.
+pub trait GasCostOf<Call, Balance> {
+	/// Amount paid for the whole gas limit of `call`, or `None` if it cannot be scheduled.
+	fn gas_cost(call: &Call) -> Option<Balance>;
+
+	/// Reserve the gas limit of `call` from the gas the calls of the current block may use,
+	/// failing if there is not enough left.
+	fn reserve_block_gas(call: &Call) -> DispatchResult;
+}
+
+/// Observer of the dispatch of scheduled calls.
+pub trait OnDispatch<AccountId, Call> {
+	/// What is recorded before a call is dispatched.
+	type Pre;
+
+	/// Called before `call` is dispatched from the origin of `who`.
+	fn pre_dispatch(who: &AccountId, call: &Call) -> Self::Pre;
+
+	/// Called after the call is dispatched, with its result.
+	fn post_dispatch(pre: Self::Pre, result: &DispatchResult);
+}
+
+impl<AccountId, Call> OnDispatch<AccountId, Call> for () {
+	type Pre = ();
+
+	fn pre_dispatch(_who: &AccountId, _call: &Call) {}
+
+	fn post_dispatch(_pre: (), _result: &DispatchResult) {}
+}
+
+impl<AccountId, Call, A, B> OnDispatch<AccountId, Call> for (A, B)
+where
+	A: OnDispatch<AccountId, Call>,
+	B: OnDispatch<AccountId, Call>,
+{
+	type Pre = (A::Pre, B::Pre);
+
+	fn pre_dispatch(who: &AccountId, call: &Call) -> Self::Pre {
+		(A::pre_dispatch(who, call), B::pre_dispatch(who, call))
+	}
+
+	fn post_dispatch((a, b): Self::Pre, result: &DispatchResult) {
+		A::post_dispatch(a, result);
+		B::post_dispatch(b, result);
+	}
+}
+
+/// Configure the pallet by specifying the parameters and types on which it depends.
+pub trait Trait: frame_system::Trait {
+	/// Because this pallet emits events, it depends on the runtime's definition of an event.
+	type Event: From<Event<Self>> + Into<<Self as frame_system::Trait>::Event>;
+
+	/// The currency in which gas and deposits are reserved.
+	type Currency: ReservableCurrency<Self::AccountId>;
+
+	/// The calls that can be scheduled.
+	type Call: Parameter
+		+ Dispatchable<Origin = Self::Origin, PostInfo = PostDispatchInfo>
+		+ GetDispatchInfo
+		+ From<Call<Self>>;
+
+	/// Gas paid by the calls that can be scheduled.
+	type GasCost: GasCostOf<<Self as Trait>::Call, BalanceOf<Self>>;
+
+	/// Observer of the dispatch of scheduled calls.
+	type OnDispatch: OnDispatch<Self::AccountId, <Self as Trait>::Call>;
+
+	/// Minimum amount paid for the gas of each execution.
+	type MinGasCost: Get<BalanceOf<Self>>;
+
+	/// Amount reserved from the owner of each scheduled call for as long as it is stored.
+	type TaskDeposit: Get<BalanceOf<Self>>;
+
+	/// The origin `execute` is dispatched from by the scheduler.
+	type PalletsOrigin: From<RawOrigin<Self::AccountId>>;
+
+	/// The scheduler dispatching `execute`.
+	type Scheduler: ScheduleNamed<Self::BlockNumber, <Self as Trait>::Call, Self::PalletsOrigin>;
+
+	/// Maximum number of executions of a scheduled call.
+	type MaxExecutions: Get<u32>;
+}
+
+/// A call scheduled by an account.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct ScheduledCall<AccountId, Balance, BlockNumber, Call> {
+	/// Account the call is dispatched from, which pays for its gas.
+	pub owner: AccountId,
+	/// The call.
+	pub call: Call,
+	/// Gas paid for each execution.
+	pub gas_cost: Balance,
+	/// Number of executions left, whose gas is reserved from the owner.
+	pub remaining: u32,
+	/// Number of blocks between two executions, `None` for a single execution.
+	pub period: Option<BlockNumber>,
+	/// Block the next execution is scheduled at.
+	pub next: BlockNumber,
+	/// Amount reserved from the owner for the storage of the call.
+	pub deposit: Balance,
+}
+
+decl_storage! {
+	trait Store for Module<T: Trait> as ContractScheduler {
+		/// Identifier of the next scheduled call.
+		NextTaskId get(fn next_task_id): TaskId;
+
+		/// Calls scheduled by accounts.
+		Tasks get(fn task): map hasher(twox_64_concat) TaskId => Option<ScheduledCallOf<T>>;
+	}
+}
+
+decl_event!(
+	pub enum Event<T> where
+		AccountId = <T as frame_system::Trait>::AccountId,
+		BlockNumber = <T as frame_system::Trait>::BlockNumber,
+	{
+		/// A call was scheduled, first executed at the given block. [id, owner, when]
+		Scheduled(TaskId, AccountId, BlockNumber),
+		/// A scheduled call was executed. [id, result]
+		Executed(TaskId, DispatchResult),
+		/// An execution was postponed to the given block, as its block had no gas left for
+		/// it. [id, when]
+		Postponed(TaskId, BlockNumber),
+		/// The remaining executions of a scheduled call were cancelled. [id]
+		Cancelled(TaskId),
+	}
+);
+
+decl_error! {
+	pub enum Error for Module<T: Trait> {
+		/// The call does not pay for gas.
+		NotSchedulable,
+		/// The call pays less than `MinGasCost` for gas.
+		GasTooCheap,
+		/// Calls must be scheduled after the current block.
+		InThePast,
+		/// The period is zero, or the number of executions is zero or above `MaxExecutions`.
+		InvalidPeriod,
+		/// The gas of all the executions cannot be reserved.
+		InsufficientBalance,
+		/// The scheduler rejected the call.
+		FailedToSchedule,
+		/// No call is scheduled with this identifier.
+		NotFound,
+		/// Only the account that scheduled a call can cancel it.
+		NotOwner,
+	}
+}
+
+decl_module! {
+	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+		type Error = Error<T>;
+
+		const MaxExecutions: u32 = T::MaxExecutions::get();
+		const MinGasCost: BalanceOf<T> = T::MinGasCost::get();
+		const TaskDeposit: BalanceOf<T> = T::TaskDeposit::get();
+
+		fn deposit_event() = default;
+
+		/// Schedule `call` to be dispatched from the origin at block `when`. With
+		/// `maybe_periodic` set to `Some((period, executions))`, it is dispatched every
+		/// `period` blocks from then on, `executions` times in total.
+		///
+		/// The gas of all the executions and `TaskDeposit` are reserved from the origin.
+		#[weight = 10_000 + T::DbWeight::get().reads_writes(3, 4)]
+		#[transactional]
+		pub fn schedule(
+			origin,
+			when: T::BlockNumber,
+			maybe_periodic: Option<Period<T::BlockNumber>>,
+			call: Box<<T as Trait>::Call>,
+		) -> dispatch::DispatchResult {
+			let who = ensure_signed(origin)?;
+			ensure!(when > frame_system::Module::<T>::block_number(), Error::<T>::InThePast);
+			let (period, executions) = match maybe_periodic {
+				Some((period, executions)) => {
+					ensure!(!period.is_zero(), Error::<T>::InvalidPeriod);
+					(Some(period), executions)
+				}
+				None => (None, 1),
+			};
+			ensure!(
+				executions > 0 && executions <= T::MaxExecutions::get(),
+				Error::<T>::InvalidPeriod,
+			);
+
+			let gas_cost = T::GasCost::gas_cost(&call).ok_or(Error::<T>::NotSchedulable)?;
+			ensure!(gas_cost >= T::MinGasCost::get(), Error::<T>::GasTooCheap);
+			let deposit = T::TaskDeposit::get();
+			T::Currency::reserve(&who, gas_cost.saturating_mul(executions.into()).saturating_add(deposit))
+				.map_err(|_| Error::<T>::InsufficientBalance)?;
+
+			let id = NextTaskId::mutate(|next| {
+				let id = *next;
+				*next += 1;
+				id
+			});
+			Self::schedule_execution(id, when).map_err(|_| Error::<T>::FailedToSchedule)?;
+
+			<Tasks<T>>::insert(id, ScheduledCall {
+				owner: who.clone(),
+				call: *call,
+				gas_cost,
+				remaining: executions,
+				period: period.filter(|_| executions > 1),
+				next: when,
+				deposit,
+			});
+			Self::deposit_event(RawEvent::Scheduled(id, who, when));
+			Ok(())
+		}
+
+		/// Cancel the remaining executions of a call scheduled by the origin, returning their
+		/// reserved gas and the deposit.
+		#[weight = 10_000 + T::DbWeight::get().reads_writes(3, 3)]
+		pub fn cancel(origin, id: TaskId) -> dispatch::DispatchResult {
+			let who = ensure_signed(origin)?;
+			let task = Self::task(id).ok_or(Error::<T>::NotFound)?;
+			ensure!(task.owner == who, Error::<T>::NotOwner);
+
+			T::Scheduler::cancel_named(Self::schedule_name(id, task.next))
+				.map_err(|_| Error::<T>::NotFound)?;
+			Self::remove(id, task);
+			Ok(())
+		}
+
+		/// Return the reserved gas of an execution of a scheduled call to its owner, and
+		/// dispatch the call from the origin of its owner, which pays for its gas. Dispatched
+		/// by the scheduler, which the next execution is then given to.
+		///
+		/// The execution is postponed to the next block if its block has no gas left for it.
+		#[weight = Module::<T>::task(id)
+			.map_or(0, |task| task.call.get_dispatch_info().weight)
+			.saturating_add(10_000 + T::DbWeight::get().reads_writes(5, 5))]
+		pub fn execute(origin, id: TaskId) -> dispatch::DispatchResult {
+			ensure_root(origin)?;
+			let mut task = Self::task(id).ok_or(Error::<T>::NotFound)?;
+			let now = frame_system::Module::<T>::block_number();
+
+			if T::GasCost::reserve_block_gas(&task.call).is_err() {
+				let next = now.saturating_add(One::one());
+				if Self::schedule_next(id, task, next) {
+					Self::deposit_event(RawEvent::Postponed(id, next));
+				}
+				return Ok(());
+			}
+
+			let missing = T::Currency::unreserve(&task.owner, task.gas_cost);
+			task.remaining -= 1;
+			if !missing.is_zero() {
+				// The reserve was slashed by something else, so the gas cannot be paid.
+				Self::remove(id, task);
+				return Ok(());
+			}
+
+			let pre = T::OnDispatch::pre_dispatch(&task.owner, &task.call);
+			let result = task.call.clone().dispatch(RawOrigin::Signed(task.owner.clone()).into())
+				.map(|_| ())
+				.map_err(|e| e.error);
+			T::OnDispatch::post_dispatch(pre, &result);
+			Self::deposit_event(RawEvent::Executed(id, result));
+
+			match task.period.filter(|_| task.remaining > 0) {
+				Some(period) => {
+					Self::schedule_next(id, task, now.saturating_add(period));
+				}
+				None => {
+					T::Currency::unreserve(&task.owner, task.deposit);
+					<Tasks<T>>::remove(id);
+				}
+			}
+			Ok(())
+		}
+	}
+}
+
+impl<T: Trait> Module<T> {
+	/// Name under which the execution at `when` of the call with the given identifier is
+	/// scheduled. It differs from the name of the execution being dispatched, which the
+	/// scheduler only releases after the dispatch.
+	fn schedule_name(id: TaskId, when: T::BlockNumber) -> Vec<u8> {
+		(SCHEDULE_PREFIX, id, when).encode()
+	}
+
+	/// Schedule the execution at `when` of the call with the given identifier.
+	fn schedule_execution(id: TaskId, when: T::BlockNumber) -> Result<(), ()> {
+		T::Scheduler::schedule_named(
+			Self::schedule_name(id, when),
+			DispatchTime::At(when),
+			None,
+			LOWEST_PRIORITY,
+			RawOrigin::Root.into(),
+			Call::<T>::execute(id).into(),
+		)
+	}
+
+	/// Schedule the next execution of a call at `when`. If the scheduler rejects it, the
+	/// remaining executions are cancelled and `false` is returned.
+	fn schedule_next(id: TaskId, mut task: ScheduledCallOf<T>, when: T::BlockNumber) -> bool {
+		if Self::schedule_execution(id, when).is_err() {
+			Self::remove(id, task);
+			return false;
+		}
+		task.next = when;
+		<Tasks<T>>::insert(id, task);
+		true
+	}
+
+	/// Remove a scheduled call whose remaining executions are cancelled, returning their
+	/// reserved gas and the deposit to its owner.
+	fn remove(id: TaskId, task: ScheduledCallOf<T>) {
+		T::Currency::unreserve(
+			&task.owner,
+			task.gas_cost.saturating_mul(task.remaining.into()).saturating_add(task.deposit),
+		);
+		<Tasks<T>>::remove(id);
+		Self::deposit_event(RawEvent::Cancelled(id));
+	}
+}
@@ -0,0 +1,250 @@
+use crate::{self as contract_scheduler, GasCostOf, Module, OnDispatch, Trait};
+use sp_core::H256;
+use frame_support::{
+	impl_outer_origin, impl_outer_dispatch, parameter_types,
+	traits::{
+		Currency, ExistenceRequirement, WithdrawReason,
+		schedule::{DispatchTime, Named as ScheduleNamed, Period, Priority},
+	},
+	weights::Weight,
+};
+use sp_runtime::{
+	DispatchError, DispatchResult, Perbill, traits::{BlakeTwo256, IdentityLookup}, testing::Header,
+};
+use sp_std::cell::RefCell;
+use frame_system as system;
+
+impl_outer_origin! {
+	pub enum Origin for Test {}
+}
+
+impl_outer_dispatch! {
+	pub enum Call for Test where origin: Origin {
+		frame_system::System,
+		pallet_balances::Balances,
+		contract_scheduler::ContractScheduler,
+	}
+}
+
+// Configure a mock runtime to test the pallet.
+
+#[derive(Clone, Eq, PartialEq)]
+pub struct Test;
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+	pub const MaximumBlockWeight: Weight = 1024;
+	pub const MaximumBlockLength: u32 = 2 * 1024;
+	pub const AvailableBlockRatio: Perbill = Perbill::from_percent(75);
+}
+
+impl system::Trait for Test {
+	type BaseCallFilter = ();
+	type Origin = Origin;
+	type Call = Call;
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = u64;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = ();
+	type BlockHashCount = BlockHashCount;
+	type MaximumBlockWeight = MaximumBlockWeight;
+	type DbWeight = ();
+	type BlockExecutionWeight = ();
+	type ExtrinsicBaseWeight = ();
+	type MaximumExtrinsicWeight = MaximumBlockWeight;
+	type MaximumBlockLength = MaximumBlockLength;
+	type AvailableBlockRatio = AvailableBlockRatio;
+	type Version = ();
+	type PalletInfo = ();
+	type AccountData = pallet_balances::AccountData<u64>;
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+}
+
+parameter_types! {
+	pub const ExistentialDeposit: u64 = 1;
+}
+
+impl pallet_balances::Trait for Test {
+	type MaxLocks = ();
+	type Balance = u64;
+	type Event = ();
+	type DustRemoval = ();
+	type ExistentialDeposit = ExistentialDeposit;
+	type AccountStore = System;
+	type WeightInfo = ();
+}
+
+/// Transfers pay `TRANSFER_GAS` for gas and transfers keeping the sender alive are free,
+/// other calls cannot be scheduled. The gas is also the gas limit of the call.
+pub struct TransferGas;
+
+pub const TRANSFER_GAS: u64 = 10;
+
+thread_local! {
+	static BLOCK_GAS: RefCell<u64> = RefCell::new(u64::max_value());
+}
+
+/// Set the gas the calls of the current block may still use.
+pub fn set_block_gas(gas: u64) {
+	BLOCK_GAS.with(|block_gas| *block_gas.borrow_mut() = gas);
+}
+
+impl GasCostOf<Call, u64> for TransferGas {
+	fn gas_cost(call: &Call) -> Option<u64> {
+		match call {
+			Call::Balances(pallet_balances::Call::transfer(..)) => Some(TRANSFER_GAS),
+			Call::Balances(pallet_balances::Call::transfer_keep_alive(..)) => Some(0),
+			_ => None,
+		}
+	}
+
+	fn reserve_block_gas(call: &Call) -> DispatchResult {
+		let gas = Self::gas_cost(call).unwrap_or_default();
+		BLOCK_GAS.with(|block_gas| {
+			let mut block_gas = block_gas.borrow_mut();
+			*block_gas = block_gas.checked_sub(gas).ok_or(DispatchError::Other("block gas exhausted"))?;
+			Ok(())
+		})
+	}
+}
+
+/// Burns the gas of transfers from the account they are dispatched from, as `pallet_ssvm`
+/// does for its calls.
+pub struct BurnGas;
+
+impl OnDispatch<u64, Call> for BurnGas {
+	type Pre = ();
+
+	fn pre_dispatch(who: &u64, call: &Call) {
+		let gas = TransferGas::gas_cost(call).unwrap_or_default();
+		let _ = Balances::withdraw(who, gas, WithdrawReason::Fee.into(), ExistenceRequirement::AllowDeath);
+	}
+
+	fn post_dispatch(_pre: (), _result: &DispatchResult) {}
+}
+
+/// A call scheduled in `Scheduler`.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Scheduled {
+	pub name: Vec<u8>,
+	pub when: u64,
+	pub maybe_periodic: Option<Period<u64>>,
+	pub call: Call,
+}
+
+thread_local! {
+	static SCHEDULED: RefCell<Vec<Scheduled>> = RefCell::new(vec![]);
+}
+
+/// The calls scheduled in `Scheduler`, which never dispatches them by itself.
+pub fn scheduled() -> Vec<Scheduled> {
+	SCHEDULED.with(|scheduled| scheduled.borrow().clone())
+}
+
+/// Scheduler recording the calls scheduled in it.
+pub struct Scheduler;
+
+impl ScheduleNamed<u64, Call, Origin> for Scheduler {
+	type Address = ();
+
+	fn schedule_named(
+		name: Vec<u8>,
+		when: DispatchTime<u64>,
+		maybe_periodic: Option<Period<u64>>,
+		_priority: Priority,
+		_origin: Origin,
+		call: Call,
+	) -> Result<(), ()> {
+		let when = match when {
+			DispatchTime::At(when) => when,
+			DispatchTime::After(after) => System::block_number() + after + 1,
+		};
+		SCHEDULED.with(|scheduled| scheduled.borrow_mut().push(Scheduled { name, when, maybe_periodic, call }));
+		Ok(())
+	}
+
+	fn cancel_named(name: Vec<u8>) -> Result<(), ()> {
+		SCHEDULED.with(|scheduled| {
+			let mut scheduled = scheduled.borrow_mut();
+			let count = scheduled.len();
+			scheduled.retain(|call| call.name != name);
+			if scheduled.len() < count { Ok(()) } else { Err(()) }
+		})
+	}
+
+	fn reschedule_named(_name: Vec<u8>, _when: DispatchTime<u64>) -> Result<(), DispatchError> {
+		Err(DispatchError::Other("not supported"))
+	}
+
+	fn next_dispatch_time(name: Vec<u8>) -> Result<u64, ()> {
+		scheduled().into_iter().find(|call| call.name == name).map(|call| call.when).ok_or(())
+	}
+}
+
+thread_local! {
+	static DISPATCHED: RefCell<Vec<(u64, Call, DispatchResult)>> = RefCell::new(vec![]);
+}
+
+/// The scheduled calls dispatched so far, with their origin and their result.
+pub fn dispatched() -> Vec<(u64, Call, DispatchResult)> {
+	DISPATCHED.with(|dispatched| dispatched.borrow().clone())
+}
+
+/// Observer recording the scheduled calls it sees dispatched.
+pub struct RecordDispatch;
+
+impl OnDispatch<u64, Call> for RecordDispatch {
+	type Pre = (u64, Call);
+
+	fn pre_dispatch(who: &u64, call: &Call) -> (u64, Call) {
+		(*who, call.clone())
+	}
+
+	fn post_dispatch((who, call): (u64, Call), result: &DispatchResult) {
+		DISPATCHED.with(|dispatched| dispatched.borrow_mut().push((who, call, *result)));
+	}
+}
+
+parameter_types! {
+	pub const MinGasCost: u64 = 1;
+	pub const TaskDeposit: u64 = 50;
+	pub const MaxExecutions: u32 = 5;
+}
+
+impl Trait for Test {
+	type Event = ();
+	type Currency = Balances;
+	type Call = Call;
+	type GasCost = TransferGas;
+	type OnDispatch = (BurnGas, RecordDispatch);
+	type MinGasCost = MinGasCost;
+	type TaskDeposit = TaskDeposit;
+	type PalletsOrigin = Origin;
+	type Scheduler = Scheduler;
+	type MaxExecutions = MaxExecutions;
+}
+
+pub const ALICE: u64 = 1;
+pub const BOB: u64 = 2;
+
+pub type System = system::Module<Test>;
+pub type Balances = pallet_balances::Module<Test>;
+pub type ContractScheduler = Module<Test>;
+
+// Build genesis storage according to the mock runtime, at block 1 with unlimited block gas.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	pallet_balances::GenesisConfig::<Test> {
+		balances: vec![(ALICE, 1_000)],
+	}.assimilate_storage(&mut t).unwrap();
+
+	let mut ext: sp_io::TestExternalities = t.into();
+	ext.execute_with(|| System::set_block_number(1));
+	set_block_gas(u64::max_value());
+	ext
+}
@@ -0,0 +1,195 @@
+use crate::{Error, ScheduledCall, mock::*};
+use frame_support::{assert_ok, assert_noop, traits::Get};
+use sp_runtime::DispatchError;
+
+fn transfer(to: u64, value: u64) -> Box<Call> {
+	Box::new(Call::Balances(pallet_balances::Call::transfer(to, value)))
+}
+
+fn execute(id: u64) -> frame_support::dispatch::DispatchResult {
+	ContractScheduler::execute(Origin::root(), id)
+}
+
+#[test]
+fn scheduling_reserves_gas_of_every_execution() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(ContractScheduler::schedule(Origin::signed(ALICE), 5, Some((10, 3)), transfer(BOB, 100)));
+
+		assert_eq!(ContractScheduler::task(0), Some(ScheduledCall {
+			owner: ALICE,
+			call: *transfer(BOB, 100),
+			gas_cost: TRANSFER_GAS,
+			remaining: 3,
+			period: Some(10),
+			next: 5,
+			deposit: TaskDeposit::get(),
+		}));
+		assert_eq!(Balances::reserved_balance(ALICE), 3 * TRANSFER_GAS + TaskDeposit::get());
+
+		// Only the first execution is scheduled.
+		let scheduled = scheduled();
+		assert_eq!(scheduled.len(), 1);
+		assert_eq!((scheduled[0].when, scheduled[0].maybe_periodic), (5, None));
+		assert_eq!(scheduled[0].call, Call::ContractScheduler(crate::Call::execute(0)));
+	});
+}
+
+#[test]
+fn single_executions_have_no_period() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(ContractScheduler::schedule(Origin::signed(ALICE), 5, None, transfer(BOB, 100)));
+		assert_ok!(ContractScheduler::schedule(Origin::signed(ALICE), 5, Some((10, 1)), transfer(BOB, 100)));
+
+		assert_eq!(ContractScheduler::task(0).unwrap().period, None);
+		assert_eq!(ContractScheduler::task(1).unwrap().period, None);
+		assert_eq!(ContractScheduler::next_task_id(), 2);
+
+		System::set_block_number(5);
+		assert_ok!(execute(0));
+		assert_eq!(ContractScheduler::task(0), None);
+		assert_eq!(scheduled().len(), 2);
+	});
+}
+
+#[test]
+fn executions_pay_gas_from_reserve_and_dispatch_from_owner() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(ContractScheduler::schedule(Origin::signed(ALICE), 5, Some((10, 2)), transfer(BOB, 100)));
+		assert_noop!(ContractScheduler::execute(Origin::signed(ALICE), 0), DispatchError::BadOrigin);
+
+		// The gas is paid once, by the dispatched call.
+		System::set_block_number(5);
+		assert_ok!(execute(0));
+		assert_eq!(Balances::free_balance(BOB), 100);
+		assert_eq!(Balances::reserved_balance(ALICE), TRANSFER_GAS + TaskDeposit::get());
+		assert_eq!(Balances::free_balance(ALICE), 1_000 - 100 - 2 * TRANSFER_GAS - TaskDeposit::get());
+		assert_eq!(ContractScheduler::task(0).unwrap().remaining, 1);
+
+		// The execution scheduled the next one.
+		let next = scheduled().pop().unwrap();
+		assert_eq!((next.when, next.call), (15, Call::ContractScheduler(crate::Call::execute(0))));
+		assert_eq!(ContractScheduler::task(0).unwrap().next, 15);
+
+		System::set_block_number(15);
+		assert_ok!(execute(0));
+		assert_eq!(Balances::free_balance(BOB), 200);
+		assert_eq!(Balances::reserved_balance(ALICE), 0);
+		assert_eq!(Balances::free_balance(ALICE), 1_000 - 200 - 2 * TRANSFER_GAS);
+		assert_eq!(ContractScheduler::task(0), None);
+		assert_noop!(execute(0), Error::<Test>::NotFound);
+	});
+}
+
+#[test]
+fn failed_executions_pay_gas_and_keep_the_schedule() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(ContractScheduler::schedule(Origin::signed(ALICE), 5, Some((10, 2)), transfer(BOB, 5_000)));
+
+		assert_ok!(execute(0));
+		assert_eq!(Balances::free_balance(BOB), 0);
+		assert_eq!(Balances::reserved_balance(ALICE), TRANSFER_GAS + TaskDeposit::get());
+		assert_eq!(Balances::free_balance(ALICE), 1_000 - 2 * TRANSFER_GAS - TaskDeposit::get());
+		assert_eq!(ContractScheduler::task(0).unwrap().remaining, 1);
+	});
+}
+
+#[test]
+fn executions_are_postponed_without_block_gas() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(ContractScheduler::schedule(Origin::signed(ALICE), 5, Some((10, 2)), transfer(BOB, 100)));
+
+		System::set_block_number(5);
+		set_block_gas(TRANSFER_GAS - 1);
+		assert_ok!(execute(0));
+		assert_eq!(Balances::free_balance(BOB), 0);
+		assert!(dispatched().is_empty());
+		// The postponed execution keeps its gas and is retried in the next block.
+		assert_eq!(Balances::reserved_balance(ALICE), 2 * TRANSFER_GAS + TaskDeposit::get());
+		assert_eq!(ContractScheduler::task(0).unwrap().remaining, 2);
+		assert_eq!(ContractScheduler::task(0).unwrap().next, 6);
+		assert_eq!(scheduled().pop().unwrap().when, 6);
+
+		// The following executions are delayed as much.
+		System::set_block_number(6);
+		set_block_gas(TRANSFER_GAS);
+		assert_ok!(execute(0));
+		assert_eq!(Balances::free_balance(BOB), 100);
+		assert_eq!(ContractScheduler::task(0).unwrap().remaining, 1);
+		assert_eq!(ContractScheduler::task(0).unwrap().next, 16);
+
+		System::set_block_number(16);
+		set_block_gas(TRANSFER_GAS);
+		assert_ok!(execute(0));
+		assert_eq!(Balances::free_balance(BOB), 200);
+		assert_eq!(Balances::reserved_balance(ALICE), 0);
+		assert_eq!(ContractScheduler::task(0), None);
+	});
+}
+
+#[test]
+fn dispatches_are_observed() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(ContractScheduler::schedule(Origin::signed(ALICE), 5, Some((10, 2)), transfer(BOB, 5_000)));
+		assert_ok!(execute(0));
+
+		let dispatched = dispatched();
+		assert_eq!(dispatched.len(), 1);
+		assert_eq!(dispatched[0].0, ALICE);
+		assert_eq!(dispatched[0].1, *transfer(BOB, 5_000));
+		assert!(dispatched[0].2.is_err());
+	});
+}
+
+#[test]
+fn rejects_invalid_schedules() {
+	new_test_ext().execute_with(|| {
+		let remark = Box::new(Call::System(frame_system::Call::remark(vec![])));
+		assert_noop!(
+			ContractScheduler::schedule(Origin::signed(ALICE), 5, None, remark),
+			Error::<Test>::NotSchedulable,
+		);
+		assert_noop!(
+			ContractScheduler::schedule(
+				Origin::signed(ALICE),
+				5,
+				None,
+				Box::new(Call::Balances(pallet_balances::Call::transfer_keep_alive(BOB, 1))),
+			),
+			Error::<Test>::GasTooCheap,
+		);
+		assert_noop!(
+			ContractScheduler::schedule(Origin::signed(ALICE), 1, None, transfer(BOB, 1)),
+			Error::<Test>::InThePast,
+		);
+		for periodic in &[(0, 2), (10, 0), (10, 6)] {
+			assert_noop!(
+				ContractScheduler::schedule(Origin::signed(ALICE), 5, Some(*periodic), transfer(BOB, 1)),
+				Error::<Test>::InvalidPeriod,
+			);
+		}
+		assert_noop!(
+			ContractScheduler::schedule(Origin::signed(BOB), 5, None, transfer(ALICE, 1)),
+			Error::<Test>::InsufficientBalance,
+		);
+		assert!(scheduled().is_empty());
+	});
+}
+
+#[test]
+fn owner_cancels_remaining_executions() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(ContractScheduler::schedule(Origin::signed(ALICE), 5, Some((10, 3)), transfer(BOB, 100)));
+		System::set_block_number(5);
+		assert_ok!(execute(0));
+
+		assert_noop!(ContractScheduler::cancel(Origin::signed(BOB), 0), Error::<Test>::NotOwner);
+		assert_ok!(ContractScheduler::cancel(Origin::signed(ALICE), 0));
+
+		assert_eq!(Balances::reserved_balance(ALICE), 0);
+		assert_eq!(Balances::free_balance(ALICE), 1_000 - 100 - TRANSFER_GAS);
+		assert_eq!(ContractScheduler::task(0), None);
+		// Only the dispatched first execution, which the mock scheduler never releases, is left.
+		assert_eq!(scheduled().iter().map(|call| call.when).collect::<Vec<_>>(), vec![5]);
+		assert_noop!(ContractScheduler::cancel(Origin::signed(ALICE), 0), Error::<Test>::NotFound);
+	});
+}
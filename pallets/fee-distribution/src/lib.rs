@@ -0,0 +1,142 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Distribution of transaction fees and SSVM gas payments.
+//!
+//! Instead of being burned, every payment handed to this pallet is split between the
+//! author of the current block, as reported by `pallet_authorship`, and a treasury
+//! account. The share going to the author is set by Root, the rest goes to the treasury.
+
+use frame_support::{
+	decl_module, decl_storage, decl_event, dispatch,
+	traits::{Currency, Get, Imbalance, OnUnbalanced},
+};
+use frame_system::ensure_root;
+use sp_runtime::{ModuleId, Perbill, traits::AccountIdConversion};
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+type BalanceOf<T> =
+	<<T as Trait>::Currency as Currency<<T as frame_system::Trait>::AccountId>>::Balance;
+type NegativeImbalanceOf<T> =
+	<<T as Trait>::Currency as Currency<<T as frame_system::Trait>::AccountId>>::NegativeImbalance;
+
+/// Configure the pallet by specifying the parameters and types on which it depends.
+pub trait Trait: frame_system::Trait + pallet_authorship::Trait {
+	/// Because this pallet emits events, it depends on the runtime's definition of an event.
+	type Event: From<Event<Self>> + Into<<Self as frame_system::Trait>::Event>;
+
+	/// The currency in which fees are paid.
+	type Currency: Currency<Self::AccountId>;
+
+	/// Identifier from which the treasury account is derived.
+	type TreasuryModuleId: Get<ModuleId>;
+}
+
+decl_storage! {
+	trait Store for Module<T: Trait> as FeeDistribution {
+		/// Portion of each payment that goes to the block author. The rest goes to the treasury.
+		AuthorShare get(fn author_share) config(): Perbill;
+	}
+	add_extra_genesis {
+		build(|_config| {
+			// Make sure the treasury account exists, so that small payments are not lost.
+			let _ = T::Currency::make_free_balance_be(
+				&<Module<T>>::treasury_account_id(),
+				T::Currency::minimum_balance(),
+			);
+		});
+	}
+}
+
+decl_event!(
+	pub enum Event<T> where
+		AccountId = <T as frame_system::Trait>::AccountId,
+		Balance = BalanceOf<T>,
+	{
+		/// A payment was split between the block author and the treasury.
+		/// [author, author_amount, treasury_amount]
+		Distributed(AccountId, Balance, Balance),
+		/// A payment went to the treasury only. [amount]
+		TreasuryDeposit(Balance),
+		/// The share of the block author was changed. [author_share]
+		AuthorShareSet(Perbill),
+	}
+);
+
+decl_module! {
+	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+		const TreasuryModuleId: ModuleId = T::TreasuryModuleId::get();
+
+		fn deposit_event() = default;
+
+		/// Set the portion of fees going to the block author. Must be called by Root.
+		#[weight = 10_000 + T::DbWeight::get().writes(1)]
+		pub fn set_author_share(origin, share: Perbill) -> dispatch::DispatchResult {
+			ensure_root(origin)?;
+
+			AuthorShare::put(share);
+
+			Self::deposit_event(RawEvent::AuthorShareSet(share));
+			Ok(())
+		}
+	}
+}
+
+impl<T: Trait> Module<T> {
+	/// The account holding the treasury funds.
+	pub fn treasury_account_id() -> T::AccountId {
+		T::TreasuryModuleId::get().into_account()
+	}
+
+	/// Split `amount` between the current block author and the treasury.
+	fn distribute(amount: NegativeImbalanceOf<T>) {
+		let author = <pallet_authorship::Module<T>>::author();
+		// Without a known author, e.g. outside of block production, everything goes to
+		// the treasury.
+		if author == Default::default() {
+			return ToTreasury::<T>::on_unbalanced(amount);
+		}
+
+		let author_amount = Self::author_share() * amount.peek();
+		let (to_author, to_treasury) = amount.split(author_amount);
+		let author_amount = to_author.peek();
+		let treasury_amount = to_treasury.peek();
+
+		T::Currency::resolve_creating(&author, to_author);
+		T::Currency::resolve_creating(&Self::treasury_account_id(), to_treasury);
+
+		Self::deposit_event(RawEvent::Distributed(author, author_amount, treasury_amount));
+	}
+}
+
+/// Fees come first, then tips, both are split the same way.
+impl<T: Trait> OnUnbalanced<NegativeImbalanceOf<T>> for Module<T> {
+	fn on_unbalanceds<B>(mut fees_then_tips: impl Iterator<Item = NegativeImbalanceOf<T>>) {
+		if let Some(mut fees) = fees_then_tips.next() {
+			if let Some(tips) = fees_then_tips.next() {
+				fees.subsume(tips);
+			}
+			Self::on_unbalanced(fees);
+		}
+	}
+
+	fn on_nonzero_unbalanced(amount: NegativeImbalanceOf<T>) {
+		Self::distribute(amount);
+	}
+}
+
+/// Payment handler sending everything to the treasury, e.g. for dust of reaped accounts.
+pub struct ToTreasury<T>(sp_std::marker::PhantomData<T>);
+
+impl<T: Trait> OnUnbalanced<NegativeImbalanceOf<T>> for ToTreasury<T> {
+	fn on_nonzero_unbalanced(amount: NegativeImbalanceOf<T>) {
+		let value = amount.peek();
+		T::Currency::resolve_creating(&<Module<T>>::treasury_account_id(), amount);
+
+		<Module<T>>::deposit_event(RawEvent::TreasuryDeposit(value));
+	}
+}
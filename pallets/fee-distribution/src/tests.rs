@@ -0,0 +1,66 @@
+use crate::mock::*;
+use frame_support::{
+	assert_ok, assert_noop,
+	traits::{Currency, ExistenceRequirement, OnUnbalanced, WithdrawReasons},
+};
+use sp_runtime::{DispatchError, Perbill};
+
+fn withdraw(amount: u64) -> <Balances as Currency<u64>>::NegativeImbalance {
+	Balances::withdraw(&PAYER, amount, WithdrawReasons::all(), ExistenceRequirement::KeepAlive).unwrap()
+}
+
+#[test]
+fn genesis_creates_treasury_account() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(Balances::free_balance(FeeDistribution::treasury_account_id()), 1);
+	});
+}
+
+#[test]
+fn fees_are_split_between_author_and_treasury() {
+	new_test_ext().execute_with(|| {
+		FeeDistribution::on_unbalanced(withdraw(100));
+
+		assert_eq!(Balances::free_balance(AUTHOR), 20);
+		assert_eq!(Balances::free_balance(FeeDistribution::treasury_account_id()), 1 + 80);
+		assert_eq!(Balances::free_balance(PAYER), 900);
+		// Nothing is burned.
+		assert_eq!(Balances::total_issuance(), 1_000 + 1);
+	});
+}
+
+#[test]
+fn tips_are_split_with_fees() {
+	new_test_ext().execute_with(|| {
+		FeeDistribution::on_unbalanceds(vec![withdraw(100), withdraw(50)].into_iter());
+
+		assert_eq!(Balances::free_balance(AUTHOR), 30);
+		assert_eq!(Balances::free_balance(FeeDistribution::treasury_account_id()), 1 + 120);
+	});
+}
+
+#[test]
+fn dust_goes_to_treasury() {
+	new_test_ext().execute_with(|| {
+		crate::ToTreasury::<Test>::on_unbalanced(withdraw(7));
+
+		assert_eq!(Balances::free_balance(AUTHOR), 0);
+		assert_eq!(Balances::free_balance(FeeDistribution::treasury_account_id()), 1 + 7);
+	});
+}
+
+#[test]
+fn root_can_change_author_share() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			FeeDistribution::set_author_share(Origin::signed(PAYER), Perbill::from_percent(100)),
+			DispatchError::BadOrigin
+		);
+		assert_ok!(FeeDistribution::set_author_share(Origin::root(), Perbill::from_percent(50)));
+		assert_eq!(FeeDistribution::author_share(), Perbill::from_percent(50));
+
+		FeeDistribution::on_unbalanced(withdraw(100));
+		assert_eq!(Balances::free_balance(AUTHOR), 50);
+		assert_eq!(Balances::free_balance(FeeDistribution::treasury_account_id()), 1 + 50);
+	});
+}
@@ -0,0 +1,336 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Human-readable names for accounts and SSVM contracts.
+//!
+//! A name such as `erc20.token` is registered for a number of periods by paying
+//! `RegistrationFee` for each of them. Its owner points it to an account, itself by
+//! default, and optionally to an SSVM address, and can transfer it to another owner. Once
+//! a name expires it stops resolving and anyone can register it again.
+//!
+//! The runtime accepts names wherever it looks up accounts from a `MultiAddress::Raw`.
+//! Names also resolve back from the account and the address they point to, so that
+//! accounts can be displayed by their names. So that nobody can give a name to someone
+//! else's account or contract, an account or address only resolves back to a name if the
+//! owner of the name controls it when pointing the name to it, and stops resolving back
+//! once the name is transferred.
+
+use codec::{Encode, Decode};
+use frame_support::{
+	decl_module, decl_storage, decl_event, decl_error, dispatch, ensure,
+	traits::{Currency, ExistenceRequirement, Get, OnUnbalanced, WithdrawReason, WithdrawReasons},
+};
+use frame_system::ensure_signed;
+use sp_core::H160;
+use sp_runtime::{RuntimeDebug, traits::{Saturating, Zero}};
+use sp_std::prelude::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+type BalanceOf<T> =
+	<<T as Trait>::Currency as Currency<<T as frame_system::Trait>::AccountId>>::Balance;
+type NegativeImbalanceOf<T> =
+	<<T as Trait>::Currency as Currency<<T as frame_system::Trait>::AccountId>>::NegativeImbalance;
+
+/// Configure the pallet by specifying the parameters and types on which it depends.
+pub trait Trait: frame_system::Trait {
+	/// Because this pallet emits events, it depends on the runtime's definition of an event.
+	type Event: From<Event<Self>> + Into<<Self as frame_system::Trait>::Event>;
+
+	/// The currency in which registration fees are paid.
+	type Currency: Currency<Self::AccountId>;
+
+	/// Handler of the registration fees.
+	type OnRegistrationFee: OnUnbalanced<NegativeImbalanceOf<Self>>;
+
+	/// Fee paid for each period a name is registered for.
+	type RegistrationFee: Get<BalanceOf<Self>>;
+
+	/// Number of blocks in a registration period.
+	type RegistrationPeriod: Get<Self::BlockNumber>;
+
+	/// Maximum number of periods a name can be registered for at once.
+	type MaxPeriods: Get<u32>;
+
+	/// Minimum length of names, in bytes.
+	type MinNameLength: Get<u32>;
+
+	/// Maximum length of names, in bytes.
+	type MaxNameLength: Get<u32>;
+
+	/// The SSVM addresses controlled by each account.
+	type Addresses: ControlsAddress<Self::AccountId>;
+}
+
+/// Control of accounts over SSVM addresses.
+pub trait ControlsAddress<AccountId> {
+	/// Whether `who` controls `address`, for instance because it deployed the contract there.
+	fn controls(who: &AccountId, address: &H160) -> bool;
+}
+
+/// No account controls any address.
+impl<AccountId> ControlsAddress<AccountId> for () {
+	fn controls(_who: &AccountId, _address: &H160) -> bool {
+		false
+	}
+}
+
+/// Resolution of names to accounts, used when looking up accounts.
+pub trait ResolveName<AccountId> {
+	/// The account `name` points to, if it is registered and not expired.
+	fn resolve(name: &[u8]) -> Option<AccountId>;
+}
+
+/// Resolves no name at all.
+impl<AccountId> ResolveName<AccountId> for () {
+	fn resolve(_name: &[u8]) -> Option<AccountId> {
+		None
+	}
+}
+
+/// A registered name.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct NameRecord<AccountId, BlockNumber> {
+	/// Account allowed to change the record and to transfer the name.
+	pub owner: AccountId,
+	/// Account the name resolves to.
+	pub account: AccountId,
+	/// SSVM address the name resolves to, if any.
+	pub address: Option<H160>,
+	/// First block at which the name no longer resolves.
+	pub expiry: BlockNumber,
+}
+
+decl_storage! {
+	trait Store for Module<T: Trait> as NameService {
+		/// Registered names, including expired ones until they are registered again.
+		Names get(fn record):
+			map hasher(blake2_128_concat) Vec<u8> => Option<NameRecord<T::AccountId, T::BlockNumber>>;
+
+		/// Name resolving to each account.
+		AccountNames: map hasher(blake2_128_concat) T::AccountId => Option<Vec<u8>>;
+
+		/// Name resolving to each SSVM address.
+		AddressNames: map hasher(blake2_128_concat) H160 => Option<Vec<u8>>;
+	}
+}
+
+decl_event!(
+	pub enum Event<T> where
+		AccountId = <T as frame_system::Trait>::AccountId,
+		BlockNumber = <T as frame_system::Trait>::BlockNumber,
+	{
+		/// A name was registered. [name, owner, expiry]
+		Registered(Vec<u8>, AccountId, BlockNumber),
+		/// A name was renewed. [name, expiry]
+		Renewed(Vec<u8>, BlockNumber),
+		/// A name now resolves to another account or address. [name]
+		RecordChanged(Vec<u8>),
+		/// A name was transferred. [name, new_owner]
+		Transferred(Vec<u8>, AccountId),
+	}
+);
+
+decl_error! {
+	pub enum Error for Module<T: Trait> {
+		/// Names are made of lowercase letters, digits, `-` and `.`, within the length limits.
+		InvalidName,
+		/// Names are registered for at least one and at most `MaxPeriods` periods.
+		InvalidPeriods,
+		/// The name is registered and not expired.
+		AlreadyRegistered,
+		/// The name is not registered or expired.
+		NotRegistered,
+		/// Only the owner of a name can change it.
+		NotOwner,
+		/// The registration fee cannot be paid.
+		InsufficientBalance,
+	}
+}
+
+decl_module! {
+	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+		type Error = Error<T>;
+
+		const RegistrationFee: BalanceOf<T> = T::RegistrationFee::get();
+		const RegistrationPeriod: T::BlockNumber = T::RegistrationPeriod::get();
+		const MaxPeriods: u32 = T::MaxPeriods::get();
+
+		fn deposit_event() = default;
+
+		/// Register `name` for `periods` periods, resolving to the origin.
+		#[weight = 10_000 + T::DbWeight::get().reads_writes(4, 5)]
+		pub fn register(origin, name: Vec<u8>, periods: u32) -> dispatch::DispatchResult {
+			let who = ensure_signed(origin)?;
+			ensure!(Self::is_valid_name(&name), Error::<T>::InvalidName);
+			if let Some(record) = Self::record(&name) {
+				ensure!(Self::is_expired(&record), Error::<T>::AlreadyRegistered);
+				Self::clear_reverse(&name, &record);
+			}
+
+			let duration = Self::charge(&who, periods)?;
+			let expiry = <frame_system::Module<T>>::block_number().saturating_add(duration);
+			let record = NameRecord { owner: who.clone(), account: who.clone(), address: None, expiry };
+			Self::set_reverse(&name, &record, &who);
+			Names::<T>::insert(&name, record);
+
+			Self::deposit_event(RawEvent::Registered(name, who, expiry));
+			Ok(())
+		}
+
+		/// Extend the registration of `name` by `periods` periods. Anyone can pay for it.
+		#[weight = 10_000 + T::DbWeight::get().reads_writes(2, 2)]
+		pub fn renew(origin, name: Vec<u8>, periods: u32) -> dispatch::DispatchResult {
+			let who = ensure_signed(origin)?;
+			let mut record = Self::active_record(&name)?;
+
+			let duration = Self::charge(&who, periods)?;
+			record.expiry = record.expiry.saturating_add(duration);
+			let expiry = record.expiry;
+			Names::<T>::insert(&name, record);
+
+			Self::deposit_event(RawEvent::Renewed(name, expiry));
+			Ok(())
+		}
+
+		/// Point `name` to `account` and `address`. Must be called by the owner of the name.
+		///
+		/// `account` and `address` only resolve back to `name` if they are the origin and an
+		/// address it controls respectively.
+		#[weight = 10_000 + T::DbWeight::get().reads_writes(3, 5)]
+		pub fn set_record(
+			origin,
+			name: Vec<u8>,
+			account: T::AccountId,
+			address: Option<H160>,
+		) -> dispatch::DispatchResult {
+			let who = ensure_signed(origin)?;
+			let mut record = Self::owned_record(&name, &who)?;
+
+			Self::clear_reverse(&name, &record);
+			record.account = account;
+			record.address = address;
+			Self::set_reverse(&name, &record, &who);
+			Names::<T>::insert(&name, record);
+
+			Self::deposit_event(RawEvent::RecordChanged(name));
+			Ok(())
+		}
+
+		/// Give `name` to `new_owner`. Must be called by the owner of the name. The name keeps
+		/// resolving to the same account and address, which no longer resolve back to it.
+		#[weight = 10_000 + T::DbWeight::get().reads_writes(3, 3)]
+		pub fn transfer(origin, name: Vec<u8>, new_owner: T::AccountId) -> dispatch::DispatchResult {
+			let who = ensure_signed(origin)?;
+			let mut record = Self::owned_record(&name, &who)?;
+
+			Self::clear_reverse(&name, &record);
+			record.owner = new_owner.clone();
+			Names::<T>::insert(&name, record);
+
+			Self::deposit_event(RawEvent::Transferred(name, new_owner));
+			Ok(())
+		}
+	}
+}
+
+impl<T: Trait> Module<T> {
+	/// The account `name` resolves to.
+	pub fn resolve_account(name: &[u8]) -> Option<T::AccountId> {
+		Self::active_record(name).ok().map(|record| record.account)
+	}
+
+	/// The SSVM address `name` resolves to.
+	pub fn resolve_address(name: &[u8]) -> Option<H160> {
+		Self::active_record(name).ok().and_then(|record| record.address)
+	}
+
+	/// The name resolving to `account`.
+	pub fn name_of_account(account: &T::AccountId) -> Option<Vec<u8>> {
+		AccountNames::<T>::get(account)
+			.filter(|name| Self::resolve_account(name).as_ref() == Some(account))
+	}
+
+	/// The name resolving to `address`.
+	pub fn name_of_address(address: &H160) -> Option<Vec<u8>> {
+		AddressNames::get(address)
+			.filter(|name| Self::resolve_address(name).as_ref() == Some(address))
+	}
+
+	fn is_valid_name(name: &[u8]) -> bool {
+		let length = name.len() as u32;
+		length >= T::MinNameLength::get() &&
+			length <= T::MaxNameLength::get() &&
+			name.iter().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || *c == b'-' || *c == b'.') &&
+			!name.starts_with(b".") &&
+			!name.ends_with(b".")
+	}
+
+	fn is_expired(record: &NameRecord<T::AccountId, T::BlockNumber>) -> bool {
+		record.expiry <= <frame_system::Module<T>>::block_number()
+	}
+
+	fn active_record(name: &[u8]) -> Result<NameRecord<T::AccountId, T::BlockNumber>, Error<T>> {
+		Self::record(name)
+			.filter(|record| !Self::is_expired(record))
+			.ok_or(Error::<T>::NotRegistered)
+	}
+
+	fn owned_record(
+		name: &[u8],
+		who: &T::AccountId,
+	) -> Result<NameRecord<T::AccountId, T::BlockNumber>, Error<T>> {
+		let record = Self::active_record(name)?;
+		ensure!(&record.owner == who, Error::<T>::NotOwner);
+		Ok(record)
+	}
+
+	/// Make `who` pay the fee of `periods` periods and return their duration.
+	fn charge(who: &T::AccountId, periods: u32) -> Result<T::BlockNumber, Error<T>> {
+		ensure!(periods > 0 && periods <= T::MaxPeriods::get(), Error::<T>::InvalidPeriods);
+
+		let fee = T::RegistrationFee::get().saturating_mul(periods.into());
+		if !fee.is_zero() {
+			let payment = T::Currency::withdraw(
+				who,
+				fee,
+				WithdrawReasons::from(WithdrawReason::Fee),
+				ExistenceRequirement::KeepAlive,
+			).map_err(|_| Error::<T>::InsufficientBalance)?;
+			T::OnRegistrationFee::on_unbalanced(payment);
+		}
+		Ok(T::RegistrationPeriod::get().saturating_mul(periods.into()))
+	}
+
+	/// Make the account and the address of `record` resolve back to `name` if they are `who`
+	/// and an address controlled by `who` respectively.
+	fn set_reverse(name: &[u8], record: &NameRecord<T::AccountId, T::BlockNumber>, who: &T::AccountId) {
+		if &record.account == who {
+			AccountNames::<T>::insert(&record.account, name.to_vec());
+		}
+		if let Some(address) = record.address.as_ref().filter(|address| T::Addresses::controls(who, address)) {
+			AddressNames::insert(address, name.to_vec());
+		}
+	}
+
+	/// Remove the reverse resolution of the account and address of `record` if it is `name`.
+	fn clear_reverse(name: &[u8], record: &NameRecord<T::AccountId, T::BlockNumber>) {
+		if AccountNames::<T>::get(&record.account).as_deref() == Some(name) {
+			AccountNames::<T>::remove(&record.account);
+		}
+		if let Some(address) = &record.address {
+			if AddressNames::get(address).as_deref() == Some(name) {
+				AddressNames::remove(address);
+			}
+		}
+	}
+}
+
+impl<T: Trait> ResolveName<T::AccountId> for Module<T> {
+	fn resolve(name: &[u8]) -> Option<T::AccountId> {
+		Self::resolve_account(name)
+	}
+}
@@ -0,0 +1,112 @@
+use crate::{ControlsAddress, Module, Trait};
+use sp_core::{H160, H256};
+use frame_support::{impl_outer_origin, parameter_types, weights::Weight};
+use sp_runtime::{
+	traits::{BlakeTwo256, IdentityLookup}, testing::Header, Perbill,
+};
+use frame_system as system;
+
+impl_outer_origin! {
+	pub enum Origin for Test {}
+}
+
+// Configure a mock runtime to test the pallet.
+
+#[derive(Clone, Eq, PartialEq)]
+pub struct Test;
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+	pub const MaximumBlockWeight: Weight = 1024;
+	pub const MaximumBlockLength: u32 = 2 * 1024;
+	pub const AvailableBlockRatio: Perbill = Perbill::from_percent(75);
+}
+
+impl system::Trait for Test {
+	type BaseCallFilter = ();
+	type Origin = Origin;
+	type Call = ();
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = u64;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = ();
+	type BlockHashCount = BlockHashCount;
+	type MaximumBlockWeight = MaximumBlockWeight;
+	type DbWeight = ();
+	type BlockExecutionWeight = ();
+	type ExtrinsicBaseWeight = ();
+	type MaximumExtrinsicWeight = MaximumBlockWeight;
+	type MaximumBlockLength = MaximumBlockLength;
+	type AvailableBlockRatio = AvailableBlockRatio;
+	type Version = ();
+	type PalletInfo = ();
+	type AccountData = pallet_balances::AccountData<u64>;
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+}
+
+parameter_types! {
+	pub const ExistentialDeposit: u64 = 1;
+}
+
+impl pallet_balances::Trait for Test {
+	type MaxLocks = ();
+	type Balance = u64;
+	type Event = ();
+	type DustRemoval = ();
+	type ExistentialDeposit = ExistentialDeposit;
+	type AccountStore = System;
+	type WeightInfo = ();
+}
+
+parameter_types! {
+	pub const RegistrationFee: u64 = 10;
+	pub const RegistrationPeriod: u64 = 100;
+	pub const MaxPeriods: u32 = 5;
+	pub const MinNameLength: u32 = 3;
+	pub const MaxNameLength: u32 = 16;
+}
+
+impl Trait for Test {
+	type Event = ();
+	type Currency = Balances;
+	type OnRegistrationFee = ();
+	type RegistrationFee = RegistrationFee;
+	type RegistrationPeriod = RegistrationPeriod;
+	type MaxPeriods = MaxPeriods;
+	type MinNameLength = MinNameLength;
+	type MaxNameLength = MaxNameLength;
+	type Addresses = Addresses;
+}
+
+/// Each account controls the address made of its low byte repeated.
+pub struct Addresses;
+
+impl ControlsAddress<u64> for Addresses {
+	fn controls(who: &u64, address: &H160) -> bool {
+		*address == H160::repeat_byte(*who as u8)
+	}
+}
+
+pub const ALICE: u64 = 1;
+pub const BOB: u64 = 2;
+
+pub type System = system::Module<Test>;
+pub type Balances = pallet_balances::Module<Test>;
+pub type NameService = Module<Test>;
+
+// Build genesis storage according to the mock runtime, starting at block 1.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	pallet_balances::GenesisConfig::<Test> {
+		balances: vec![(ALICE, 1_000), (BOB, 1_000)],
+	}.assimilate_storage(&mut t).unwrap();
+
+	let mut ext: sp_io::TestExternalities = t.into();
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
@@ -0,0 +1,144 @@
+use crate::{Error, NameRecord, ResolveName, mock::*};
+use frame_support::{assert_ok, assert_noop};
+use sp_core::H160;
+
+const NAME: &[u8] = b"erc20.token";
+
+fn register(who: u64, periods: u32) -> frame_support::dispatch::DispatchResult {
+	NameService::register(Origin::signed(who), NAME.to_vec(), periods)
+}
+
+#[test]
+fn registration_resolves_to_registrant_and_charges_fee() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(register(ALICE, 2));
+
+		assert_eq!(NameService::record(NAME), Some(NameRecord {
+			owner: ALICE,
+			account: ALICE,
+			address: None,
+			expiry: 201,
+		}));
+		assert_eq!(Balances::free_balance(ALICE), 980);
+		assert_eq!(NameService::resolve_account(NAME), Some(ALICE));
+		assert_eq!(NameService::name_of_account(&ALICE), Some(NAME.to_vec()));
+	});
+}
+
+#[test]
+fn rejects_invalid_names_and_periods() {
+	new_test_ext().execute_with(|| {
+		for name in &[&b"ab"[..], b"Upper.case", b".token", b"token.", b"far-too-long-name", b"sp ace"] {
+			assert_noop!(
+				NameService::register(Origin::signed(ALICE), name.to_vec(), 1),
+				Error::<Test>::InvalidName,
+			);
+		}
+		assert_noop!(register(ALICE, 0), Error::<Test>::InvalidPeriods);
+		assert_noop!(register(ALICE, 6), Error::<Test>::InvalidPeriods);
+	});
+}
+
+#[test]
+fn registration_requires_fee() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Balances::transfer(Origin::signed(ALICE), BOB, 995));
+		assert_noop!(register(ALICE, 1), Error::<Test>::InsufficientBalance);
+	});
+}
+
+#[test]
+fn names_expire_and_can_be_registered_again() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(register(ALICE, 1));
+		assert_noop!(register(BOB, 1), Error::<Test>::AlreadyRegistered);
+
+		System::set_block_number(101);
+		assert_eq!(NameService::resolve_account(NAME), None);
+		assert_eq!(NameService::name_of_account(&ALICE), None);
+
+		assert_ok!(register(BOB, 1));
+		assert_eq!(NameService::resolve_account(NAME), Some(BOB));
+		assert_eq!(NameService::name_of_account(&BOB), Some(NAME.to_vec()));
+	});
+}
+
+#[test]
+fn renewal_extends_expiry() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(register(ALICE, 1));
+		assert_ok!(NameService::renew(Origin::signed(BOB), NAME.to_vec(), 2));
+
+		assert_eq!(NameService::record(NAME).unwrap().expiry, 301);
+		assert_eq!(Balances::free_balance(BOB), 980);
+
+		System::set_block_number(301);
+		assert_noop!(
+			NameService::renew(Origin::signed(ALICE), NAME.to_vec(), 1),
+			Error::<Test>::NotRegistered,
+		);
+	});
+}
+
+#[test]
+fn owner_points_name_to_account_and_address() {
+	new_test_ext().execute_with(|| {
+		let contract = H160::repeat_byte(1);
+		assert_ok!(register(ALICE, 1));
+		assert_noop!(
+			NameService::set_record(Origin::signed(BOB), NAME.to_vec(), BOB, Some(contract)),
+			Error::<Test>::NotOwner,
+		);
+
+		assert_ok!(NameService::set_record(Origin::signed(ALICE), NAME.to_vec(), BOB, Some(contract)));
+		assert_eq!(NameService::resolve_account(NAME), Some(BOB));
+		assert_eq!(NameService::resolve_address(NAME), Some(contract));
+		assert_eq!(NameService::name_of_address(&contract), Some(NAME.to_vec()));
+		// ALICE does not control BOB, so BOB does not resolve back to the name.
+		assert_eq!(NameService::name_of_account(&BOB), None);
+		assert_eq!(NameService::name_of_account(&ALICE), None);
+
+		assert_ok!(NameService::set_record(Origin::signed(ALICE), NAME.to_vec(), ALICE, None));
+		assert_eq!(NameService::name_of_account(&ALICE), Some(NAME.to_vec()));
+		assert_eq!(NameService::name_of_address(&contract), None);
+	});
+}
+
+#[test]
+fn names_do_not_resolve_back_from_uncontrolled_addresses() {
+	new_test_ext().execute_with(|| {
+		let contract = H160::repeat_byte(BOB as u8);
+		assert_ok!(NameService::register(Origin::signed(BOB), b"bob.token".to_vec(), 1));
+		assert_ok!(NameService::set_record(Origin::signed(BOB), b"bob.token".to_vec(), BOB, Some(contract)));
+
+		assert_ok!(register(ALICE, 1));
+		assert_ok!(NameService::set_record(Origin::signed(ALICE), NAME.to_vec(), ALICE, Some(contract)));
+		assert_eq!(NameService::resolve_address(NAME), Some(contract));
+		assert_eq!(NameService::name_of_address(&contract), Some(b"bob.token".to_vec()));
+	});
+}
+
+#[test]
+fn transfer_changes_owner_and_stops_resolving_back() {
+	new_test_ext().execute_with(|| {
+		let contract = H160::repeat_byte(ALICE as u8);
+		assert_ok!(register(ALICE, 1));
+		assert_ok!(NameService::set_record(Origin::signed(ALICE), NAME.to_vec(), ALICE, Some(contract)));
+		assert_eq!(NameService::name_of_address(&contract), Some(NAME.to_vec()));
+		assert_noop!(
+			NameService::transfer(Origin::signed(BOB), NAME.to_vec(), BOB),
+			Error::<Test>::NotOwner,
+		);
+
+		assert_ok!(NameService::transfer(Origin::signed(ALICE), NAME.to_vec(), BOB));
+		assert_eq!(NameService::record(NAME).unwrap().owner, BOB);
+		assert_eq!(<NameService as ResolveName<u64>>::resolve(NAME), Some(ALICE));
+		assert_eq!(NameService::resolve_address(NAME), Some(contract));
+		// The new owner does not control the account and address of the previous one.
+		assert_eq!(NameService::name_of_account(&ALICE), None);
+		assert_eq!(NameService::name_of_address(&contract), None);
+
+		assert_ok!(NameService::set_record(Origin::signed(BOB), NAME.to_vec(), BOB, None));
+		assert_eq!(NameService::name_of_account(&BOB), Some(NAME.to_vec()));
+	});
+}
@@ -0,0 +1,339 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Randomness from secrets committed to and then revealed by validators.
+//!
+//! `pallet_randomness_collective_flip` mixes the hashes of the last 81 blocks, which the
+//! author of a block chooses freely: it can try different blocks until it likes the
+//! outcome. This pallet produces randomness in rounds of `RoundLength` blocks instead.
+//! During the first half of a round, the participants (the validators) each commit to the
+//! hash of a secret, reserving `CommitDeposit`. During the second half, they reveal their
+//! secrets. At the end of the round, the hashes of the revealed secrets are combined with
+//! XOR and mixed with the previous output into the new randomness, and the deposits of the
+//! participants that did not reveal are slashed.
+//!
+//! Nobody can predict the output of a round as long as one participant keeps its secret
+//! until the reveal phase, and nobody can choose it: the secrets are fixed before any of
+//! them is revealed, and the output does not depend on the blocks or on the order in which
+//! the secrets are revealed. The last participant to reveal can still choose between two
+//! outputs by not revealing, at the cost of its deposit, and block authors can censor
+//! reveals as long as they author all the blocks of a reveal phase.
+//!
+//! The output is only unpredictable until the round that produces it ends. It then stays
+//! the same for the whole next round, and it is in storage for anyone to read: whoever
+//! knows the subject passed to `Randomness::random` knows the result for as long as the
+//! output is current. Consumers must therefore commit to what the randomness decides (a
+//! bet, a ticket, a draw) before the round producing it ends, and use the output of a
+//! later round to settle it, never the output that is current when they commit.
+//!
+//! The offchain worker of validator nodes takes part in the rounds on their behalf, with
+//! the `rand` key of their validator account in the local keystore. Secrets are generated
+//! from the randomness of the node and kept in its offchain storage until they are
+//! revealed.
+
+use codec::Encode;
+use frame_support::{
+	debug, decl_module, decl_storage, decl_event, decl_error, dispatch, ensure,
+	traits::{Contains, Currency, Get, OnUnbalanced, Randomness as RandomnessT, ReservableCurrency},
+};
+use frame_system::{
+	ensure_signed,
+	offchain::{AppCrypto, CreateSignedTransaction, SendSignedTransaction, Signer},
+};
+use sp_core::{H256, crypto::KeyTypeId};
+use sp_runtime::{
+	RuntimeAppPublic, RuntimeDebug,
+	offchain::storage::StorageValueRef,
+	traits::{Hash, IdentifyAccount, Saturating, Zero},
+};
+use sp_std::prelude::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+type BalanceOf<T> =
+	<<T as Trait>::Currency as Currency<<T as frame_system::Trait>::AccountId>>::Balance;
+type NegativeImbalanceOf<T> =
+	<<T as Trait>::Currency as Currency<<T as frame_system::Trait>::AccountId>>::NegativeImbalance;
+
+/// Key type of the keys signing the commitments and reveals of validators.
+pub const KEY_TYPE: KeyTypeId = KeyTypeId(*b"rand");
+
+/// Keys signing the commitments and reveals of validators.
+pub mod crypto {
+	use super::KEY_TYPE;
+	use sp_core::sr25519::Signature as Sr25519Signature;
+	use sp_runtime::{
+		MultiSignature, MultiSigner,
+		app_crypto::{app_crypto, sr25519},
+		traits::Verify,
+	};
+
+	app_crypto!(sr25519, KEY_TYPE);
+
+	/// Signs the transactions of runtimes with `MultiSignature` signatures.
+	pub struct BeaconId;
+
+	impl frame_system::offchain::AppCrypto<MultiSigner, MultiSignature> for BeaconId {
+		type RuntimeAppPublic = Public;
+		type GenericSignature = sp_core::sr25519::Signature;
+		type GenericPublic = sp_core::sr25519::Public;
+	}
+
+	impl frame_system::offchain::AppCrypto<<Sr25519Signature as Verify>::Signer, Sr25519Signature>
+		for BeaconId
+	{
+		type RuntimeAppPublic = Public;
+		type GenericSignature = sp_core::sr25519::Signature;
+		type GenericPublic = sp_core::sr25519::Public;
+	}
+}
+
+/// Configure the pallet by specifying the parameters and types on which it depends.
+pub trait Trait: CreateSignedTransaction<Call<Self>> {
+	/// Keys signing the commitments and reveals of validators.
+	type AuthorityId: AppCrypto<Self::Public, Self::Signature>;
+
+	/// Because this pallet emits events, it depends on the runtime's definition of an event.
+	type Event: From<Event<Self>> + Into<<Self as frame_system::Trait>::Event>;
+
+	/// The overarching dispatch call type.
+	type Call: From<Call<Self>>;
+
+	/// The currency in which deposits are reserved.
+	type Currency: ReservableCurrency<Self::AccountId>;
+
+	/// The accounts taking part in the rounds.
+	type Participants: Contains<Self::AccountId>;
+
+	/// Amount reserved from participants when they commit, and slashed if they do not
+	/// reveal.
+	type CommitDeposit: Get<BalanceOf<Self>>;
+
+	/// Handler for slashed deposits.
+	type OnSlash: OnUnbalanced<NegativeImbalanceOf<Self>>;
+
+	/// Number of blocks of a round, half of which are for committing and the other half
+	/// for revealing.
+	type RoundLength: Get<Self::BlockNumber>;
+}
+
+/// Phases of a round.
+#[derive(Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum Phase {
+	/// Participants commit to the hash of their secret.
+	Commit,
+	/// Participants reveal their secret.
+	Reveal,
+}
+
+decl_storage! {
+	trait Store for Module<T: Trait> as RandomnessBeacon {
+		/// Output of the last round at least one secret was revealed in.
+		Randomness get(fn randomness): T::Hash;
+
+		/// Commitments of the current round, with the deposit reserved for them.
+		Commitments get(fn commitment):
+			map hasher(blake2_128_concat) T::AccountId => Option<(T::Hash, BalanceOf<T>)>;
+
+		/// XOR of the hashes of the secrets revealed in the current round.
+		Revealed get(fn revealed): T::Hash;
+
+		/// Number of secrets revealed in the current round.
+		RevealCount get(fn reveal_count): u32;
+	}
+}
+
+decl_event!(
+	pub enum Event<T> where
+		AccountId = <T as frame_system::Trait>::AccountId,
+		Balance = BalanceOf<T>,
+		Hash = <T as frame_system::Trait>::Hash,
+	{
+		/// A participant committed to a secret. [participant]
+		Committed(AccountId),
+		/// A participant revealed its secret. [participant]
+		Revealed(AccountId),
+		/// The deposit of a participant that did not reveal its secret was slashed.
+		/// [participant, deposit]
+		Slashed(AccountId, Balance),
+		/// A round ended with a new output. [randomness]
+		NewRandomness(Hash),
+	}
+);
+
+decl_error! {
+	pub enum Error for Module<T: Trait> {
+		/// Only validators take part in the rounds.
+		NotParticipant,
+		/// The current phase of the round does not allow this.
+		WrongPhase,
+		/// The participant already committed in this round.
+		AlreadyCommitted,
+		/// The participant did not commit in this round.
+		NotCommitted,
+		/// The secret does not match the commitment.
+		InvalidSecret,
+		/// The deposit cannot be reserved.
+		InsufficientBalance,
+	}
+}
+
+decl_module! {
+	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+		type Error = Error<T>;
+
+		const CommitDeposit: BalanceOf<T> = T::CommitDeposit::get();
+		const RoundLength: T::BlockNumber = T::RoundLength::get();
+
+		fn deposit_event() = default;
+
+		/// Commit to the hash of the origin and of a secret, reserving `CommitDeposit`.
+		#[weight = 10_000 + T::DbWeight::get().reads_writes(2, 2)]
+		pub fn commit(origin, commitment: T::Hash) -> dispatch::DispatchResult {
+			let who = ensure_signed(origin)?;
+			ensure!(T::Participants::contains(&who), Error::<T>::NotParticipant);
+			ensure!(Self::current_phase() == Phase::Commit, Error::<T>::WrongPhase);
+			ensure!(!<Commitments<T>>::contains_key(&who), Error::<T>::AlreadyCommitted);
+
+			let deposit = T::CommitDeposit::get();
+			T::Currency::reserve(&who, deposit).map_err(|_| Error::<T>::InsufficientBalance)?;
+			<Commitments<T>>::insert(&who, (commitment, deposit));
+			Self::deposit_event(RawEvent::Committed(who));
+			Ok(())
+		}
+
+		/// Reveal the secret committed to in this round, returning the deposit.
+		#[weight = 10_000 + T::DbWeight::get().reads_writes(3, 3)]
+		pub fn reveal(origin, secret: H256) -> dispatch::DispatchResult {
+			let who = ensure_signed(origin)?;
+			ensure!(Self::current_phase() == Phase::Reveal, Error::<T>::WrongPhase);
+			let (commitment, deposit) = Self::commitment(&who).ok_or(Error::<T>::NotCommitted)?;
+			ensure!(Self::commitment_of(&who, &secret) == commitment, Error::<T>::InvalidSecret);
+
+			T::Currency::unreserve(&who, deposit);
+			<Commitments<T>>::remove(&who);
+			let hash = T::Hashing::hash(secret.as_bytes());
+			<Revealed<T>>::mutate(|revealed| {
+				for (byte, other) in revealed.as_mut().iter_mut().zip(hash.as_ref()) {
+					*byte ^= other;
+				}
+			});
+			RevealCount::mutate(|count| *count += 1);
+			Self::deposit_event(RawEvent::Revealed(who));
+			Ok(())
+		}
+
+		fn on_finalize(n: T::BlockNumber) {
+			if Self::round_of(n.saturating_add(1u32.into())) != Self::round_of(n) {
+				Self::end_round(n);
+			}
+		}
+
+		fn offchain_worker(n: T::BlockNumber) {
+			// The transactions are included from the next block on.
+			let next = n.saturating_add(1u32.into());
+			for (account, public) in Self::local_participants() {
+				if let Err(e) = Self::take_part(&account, public, next) {
+					debug::warn!("Failed to take part in the randomness round: {}", e);
+				}
+			}
+		}
+	}
+}
+
+impl<T: Trait> Module<T> {
+	/// Commitment of `who` to `secret`. Including the account prevents others from copying
+	/// the commitment and then the secret to cancel it out.
+	pub fn commitment_of(who: &T::AccountId, secret: &H256) -> T::Hash {
+		T::Hashing::hash_of(&(who, secret))
+	}
+
+	/// Round that block `n` is part of.
+	pub fn round_of(n: T::BlockNumber) -> T::BlockNumber {
+		n / T::RoundLength::get()
+	}
+
+	/// Phase of the round at block `n`.
+	pub fn phase_of(n: T::BlockNumber) -> Phase {
+		let length = T::RoundLength::get();
+		if n % length < length / 2u32.into() {
+			Phase::Commit
+		} else {
+			Phase::Reveal
+		}
+	}
+
+	fn current_phase() -> Phase {
+		Self::phase_of(frame_system::Module::<T>::block_number())
+	}
+
+	/// Slash the deposits of the participants that did not reveal, and produce the output
+	/// of the round ending at block `n` if any secret was revealed.
+	fn end_round(n: T::BlockNumber) {
+		for (who, (_, deposit)) in <Commitments<T>>::drain() {
+			let (slashed, _) = T::Currency::slash_reserved(&who, deposit);
+			T::OnSlash::on_unbalanced(slashed);
+			Self::deposit_event(RawEvent::Slashed(who, deposit));
+		}
+
+		let revealed = <Revealed<T>>::take();
+		if !RevealCount::take().is_zero() {
+			let randomness = T::Hashing::hash_of(&(Self::randomness(), revealed, n));
+			<Randomness<T>>::put(randomness);
+			Self::deposit_event(RawEvent::NewRandomness(randomness));
+		}
+	}
+
+	/// Participants whose key is in the local keystore.
+	fn local_participants() -> Vec<(T::AccountId, T::Public)> {
+		<T::AuthorityId as AppCrypto<T::Public, T::Signature>>::RuntimeAppPublic::all()
+			.into_iter()
+			.map(|key| {
+				let generic = <T::AuthorityId as AppCrypto<T::Public, T::Signature>>::GenericPublic::from(key);
+				let public: T::Public = generic.into();
+				(public.clone().into_account(), public)
+			})
+			.filter(|(account, _)| T::Participants::contains(account))
+			.collect()
+	}
+
+	/// Submit the commitment or the reveal of `account` for block `next`, if it is due.
+	///
+	/// The secret of each round is kept in the offchain storage, so that the same
+	/// commitment is submitted again if it did not make it into a block.
+	fn take_part(account: &T::AccountId, public: T::Public, next: T::BlockNumber) -> Result<(), &'static str> {
+		let key = (b"randomness-beacon/secret", Self::round_of(next), account).encode();
+		let storage = StorageValueRef::persistent(&key);
+		let secret = storage.get::<H256>().flatten();
+
+		let call = match (Self::phase_of(next), Self::commitment(account), secret) {
+			(Phase::Commit, None, Some(secret)) => Call::commit(Self::commitment_of(account, &secret)),
+			(Phase::Commit, None, None) => {
+				let secret = H256(sp_io::offchain::random_seed());
+				storage.set(&secret);
+				Call::commit(Self::commitment_of(account, &secret))
+			}
+			(Phase::Reveal, Some(_), Some(secret)) => Call::reveal(secret),
+			_ => return Ok(()),
+		};
+
+		let (_, result) = Signer::<T, T::AuthorityId>::all_accounts()
+			.with_filter(vec![public])
+			.send_signed_transaction(|_| call.clone())
+			.pop()
+			.ok_or("no signing key")?;
+		result.map_err(|()| "failed to submit transaction")
+	}
+}
+
+/// Outputs for different subjects are independent of each other. An output is
+/// `hash_of(&(subject, randomness()))`, so it is public and fixed until the end of the
+/// next round in which a secret is revealed.
+impl<T: Trait> RandomnessT<T::Hash> for Module<T> {
+	fn random(subject: &[u8]) -> T::Hash {
+		T::Hashing::hash_of(&(subject, Self::randomness()))
+	}
+}
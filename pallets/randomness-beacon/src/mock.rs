@@ -0,0 +1,156 @@
+use crate::{Module, Trait, crypto};
+use sp_core::{H256, Pair, sr25519};
+use frame_support::{impl_outer_origin, parameter_types, traits::Contains, weights::Weight};
+use sp_runtime::{
+	Perbill,
+	testing::{Header, TestXt},
+	traits::{BlakeTwo256, Extrinsic as ExtrinsicT, IdentifyAccount, IdentityLookup, Verify},
+};
+use frame_system as system;
+
+impl_outer_origin! {
+	pub enum Origin for Test {}
+}
+
+// Configure a mock runtime to test the pallet.
+
+#[derive(Clone, Eq, PartialEq)]
+pub struct Test;
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+	pub const MaximumBlockWeight: Weight = 1024;
+	pub const MaximumBlockLength: u32 = 2 * 1024;
+	pub const AvailableBlockRatio: Perbill = Perbill::from_percent(75);
+}
+
+type Signature = sr25519::Signature;
+pub type AccountId = <<Signature as Verify>::Signer as IdentifyAccount>::AccountId;
+
+impl system::Trait for Test {
+	type BaseCallFilter = ();
+	type Origin = Origin;
+	type Call = ();
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = AccountId;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = ();
+	type BlockHashCount = BlockHashCount;
+	type MaximumBlockWeight = MaximumBlockWeight;
+	type DbWeight = ();
+	type BlockExecutionWeight = ();
+	type ExtrinsicBaseWeight = ();
+	type MaximumExtrinsicWeight = MaximumBlockWeight;
+	type MaximumBlockLength = MaximumBlockLength;
+	type AvailableBlockRatio = AvailableBlockRatio;
+	type Version = ();
+	type PalletInfo = ();
+	type AccountData = pallet_balances::AccountData<u64>;
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+}
+
+parameter_types! {
+	pub const ExistentialDeposit: u64 = 1;
+}
+
+impl pallet_balances::Trait for Test {
+	type MaxLocks = ();
+	type Balance = u64;
+	type Event = ();
+	type DustRemoval = ();
+	type ExistentialDeposit = ExistentialDeposit;
+	type AccountStore = System;
+	type WeightInfo = ();
+}
+
+pub type Extrinsic = TestXt<crate::Call<Test>, ()>;
+
+impl frame_system::offchain::SigningTypes for Test {
+	type Public = <Signature as Verify>::Signer;
+	type Signature = Signature;
+}
+
+impl<C> frame_system::offchain::SendTransactionTypes<C> for Test where
+	crate::Call<Test>: From<C>,
+{
+	type OverarchingCall = crate::Call<Test>;
+	type Extrinsic = Extrinsic;
+}
+
+impl<C> frame_system::offchain::CreateSignedTransaction<C> for Test where
+	crate::Call<Test>: From<C>,
+{
+	fn create_transaction<A: frame_system::offchain::AppCrypto<Self::Public, Self::Signature>>(
+		call: crate::Call<Test>,
+		_public: <Signature as Verify>::Signer,
+		_account: AccountId,
+		nonce: u64,
+	) -> Option<(crate::Call<Test>, <Extrinsic as ExtrinsicT>::SignaturePayload)> {
+		Some((call, (nonce, ())))
+	}
+}
+
+/// Alice and Bob take part in the rounds, Charlie does not.
+pub struct Participants;
+
+impl Contains<AccountId> for Participants {
+	fn sorted_members() -> Vec<AccountId> {
+		let mut members = vec![alice(), bob()];
+		members.sort();
+		members
+	}
+}
+
+parameter_types! {
+	pub const CommitDeposit: u64 = 100;
+	pub const RoundLength: u64 = 10;
+}
+
+impl Trait for Test {
+	type AuthorityId = crypto::BeaconId;
+	type Event = ();
+	type Call = crate::Call<Test>;
+	type Currency = Balances;
+	type Participants = Participants;
+	type CommitDeposit = CommitDeposit;
+	type OnSlash = ();
+	type RoundLength = RoundLength;
+}
+
+pub fn account(seed: &str) -> AccountId {
+	sr25519::Pair::from_string(seed, None).unwrap().public()
+}
+
+pub fn alice() -> AccountId {
+	account("//Alice")
+}
+
+pub fn bob() -> AccountId {
+	account("//Bob")
+}
+
+pub fn charlie() -> AccountId {
+	account("//Charlie")
+}
+
+pub type System = system::Module<Test>;
+pub type Balances = pallet_balances::Module<Test>;
+pub type RandomnessBeacon = Module<Test>;
+
+// Build genesis storage according to the mock runtime, at the first block of the commit
+// phase of round 1.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	pallet_balances::GenesisConfig::<Test> {
+		balances: vec![(alice(), 1_000), (bob(), 1_000), (charlie(), 1_000)],
+	}.assimilate_storage(&mut t).unwrap();
+
+	let mut ext: sp_io::TestExternalities = t.into();
+	ext.execute_with(|| System::set_block_number(10));
+	ext
+}
@@ -0,0 +1,228 @@
+//! Besides the protocol itself, these tests document the security properties of the
+//! randomness: what participants and block authors can and cannot do to it.
+
+use crate::{Error, mock::*};
+use codec::Decode;
+use frame_support::{
+	assert_ok, assert_noop,
+	traits::{OffchainWorker, OnFinalize, Randomness},
+};
+use sp_core::{
+	H256,
+	offchain::{OffchainExt, TransactionPoolExt, testing::{TestOffchainExt, TestTransactionPoolExt}},
+	testing::KeyStore,
+	traits::KeystoreExt,
+};
+use sp_runtime::traits::{BlakeTwo256, Hash};
+
+fn secret(byte: u8) -> H256 {
+	H256::repeat_byte(byte)
+}
+
+fn commit(who: AccountId, secret: H256) -> frame_support::dispatch::DispatchResult {
+	RandomnessBeacon::commit(Origin::signed(who.clone()), RandomnessBeacon::commitment_of(&who, &secret))
+}
+
+fn reveal(who: AccountId, secret: H256) -> frame_support::dispatch::DispatchResult {
+	RandomnessBeacon::reveal(Origin::signed(who), secret)
+}
+
+/// Finalize the blocks up to `n`, excluded, and start block `n`.
+fn run_to(n: u64) {
+	while System::block_number() < n {
+		RandomnessBeacon::on_finalize(System::block_number());
+		System::set_block_number(System::block_number() + 1);
+	}
+}
+
+/// Output of round 1, in which Alice and Bob commit and then reveal at the given blocks.
+fn round_output(alice_reveal: Option<(u64, H256)>, bob_reveal: Option<(u64, H256)>) -> H256 {
+	new_test_ext().execute_with(|| {
+		assert_ok!(commit(alice(), alice_reveal.map_or(secret(1), |(_, secret)| secret)));
+		assert_ok!(commit(bob(), bob_reveal.map_or(secret(2), |(_, secret)| secret)));
+
+		let mut reveals = vec![(alice(), alice_reveal), (bob(), bob_reveal)];
+		reveals.sort_by_key(|(_, reveal)| reveal.map(|(block, _)| block));
+		for (who, reveal_at) in reveals {
+			if let Some((block, secret)) = reveal_at {
+				run_to(block);
+				assert_ok!(reveal(who, secret));
+			}
+		}
+		run_to(20);
+		RandomnessBeacon::randomness()
+	})
+}
+
+#[test]
+fn rounds_mix_revealed_secrets_into_randomness() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(commit(alice(), secret(1)));
+		assert_ok!(commit(bob(), secret(2)));
+		assert_eq!(Balances::reserved_balance(alice()), 100);
+
+		run_to(15);
+		assert_ok!(reveal(alice(), secret(1)));
+		assert_ok!(reveal(bob(), secret(2)));
+		assert_eq!(Balances::reserved_balance(alice()), 0);
+		assert_eq!(RandomnessBeacon::reveal_count(), 2);
+
+		run_to(20);
+		let hashes = (BlakeTwo256::hash(secret(1).as_bytes()), BlakeTwo256::hash(secret(2).as_bytes()));
+		let revealed = hashes.0 ^ hashes.1;
+		assert_eq!(RandomnessBeacon::randomness(), BlakeTwo256::hash_of(&(H256::zero(), revealed, 19u64)));
+		assert_eq!(RandomnessBeacon::reveal_count(), 0);
+		assert_eq!(RandomnessBeacon::revealed(), H256::zero());
+
+		// Outputs for different subjects are unrelated.
+		assert_ne!(RandomnessBeacon::random(b"lottery"), RandomnessBeacon::random(b"raffle"));
+	});
+}
+
+#[test]
+fn commitments_and_reveals_follow_the_phases() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(commit(charlie(), secret(3)), Error::<Test>::NotParticipant);
+		assert_ok!(commit(alice(), secret(1)));
+		assert_noop!(commit(alice(), secret(1)), Error::<Test>::AlreadyCommitted);
+		assert_noop!(reveal(alice(), secret(1)), Error::<Test>::WrongPhase);
+
+		run_to(15);
+		assert_noop!(commit(bob(), secret(2)), Error::<Test>::WrongPhase);
+		assert_noop!(reveal(bob(), secret(2)), Error::<Test>::NotCommitted);
+		assert_noop!(reveal(alice(), secret(2)), Error::<Test>::InvalidSecret);
+		assert_ok!(reveal(alice(), secret(1)));
+	});
+}
+
+#[test]
+fn reveal_order_and_timing_do_not_change_the_output() {
+	// Block authors decide which block and in which order reveals are included, which
+	// gives them no say over the output.
+	assert_eq!(
+		round_output(Some((15, secret(1))), Some((15, secret(2)))),
+		round_output(Some((19, secret(1))), Some((16, secret(2)))),
+	);
+}
+
+#[test]
+fn one_unknown_secret_determines_the_output() {
+	// Knowing every other secret does not help predicting the output.
+	assert_ne!(
+		round_output(Some((15, secret(1))), Some((15, secret(2)))),
+		round_output(Some((15, secret(3))), Some((15, secret(2)))),
+	);
+}
+
+#[test]
+fn withholding_a_reveal_costs_the_deposit() {
+	// The last participant to reveal can choose between two outputs by not revealing,
+	// which the deposit makes costly.
+	assert_ne!(
+		round_output(Some((15, secret(1))), Some((16, secret(2)))),
+		round_output(Some((15, secret(1))), None),
+	);
+
+	new_test_ext().execute_with(|| {
+		assert_ok!(commit(alice(), secret(1)));
+		assert_ok!(commit(bob(), secret(2)));
+		run_to(15);
+		assert_ok!(reveal(alice(), secret(1)));
+
+		run_to(20);
+		assert_eq!(Balances::reserved_balance(bob()), 0);
+		assert_eq!(Balances::free_balance(bob()), 900);
+		assert_eq!(RandomnessBeacon::commitment(bob()), None);
+	});
+}
+
+#[test]
+fn copied_commitments_cannot_cancel_secrets() {
+	new_test_ext().execute_with(|| {
+		// Revealing Alice's secret a second time would cancel it out of the XOR.
+		let commitment = RandomnessBeacon::commitment_of(&alice(), &secret(1));
+		assert_ok!(commit(alice(), secret(1)));
+		assert_ok!(RandomnessBeacon::commit(Origin::signed(bob()), commitment));
+
+		run_to(15);
+		assert_ok!(reveal(alice(), secret(1)));
+		assert_noop!(reveal(bob(), secret(1)), Error::<Test>::InvalidSecret);
+	});
+}
+
+#[test]
+fn rounds_without_reveals_keep_the_previous_output() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(commit(alice(), secret(1)));
+		run_to(15);
+		assert_ok!(reveal(alice(), secret(1)));
+		run_to(20);
+		let randomness = RandomnessBeacon::randomness();
+
+		// Consumers relying on fresh randomness must check that the round produced some.
+		run_to(30);
+		assert_eq!(RandomnessBeacon::randomness(), randomness);
+	});
+}
+
+#[test]
+fn outputs_are_public_until_the_next_round_ends() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(commit(alice(), secret(1)));
+		run_to(15);
+		assert_ok!(reveal(alice(), secret(1)));
+		run_to(20);
+
+		// Anyone can compute the output for a subject from storage, and it does not change
+		// during the round: it must not decide anything committed to after round 0 ended.
+		let output = RandomnessBeacon::random(b"lottery");
+		assert_eq!(output, BlakeTwo256::hash_of(&(&b"lottery"[..], RandomnessBeacon::randomness())));
+		assert_ok!(commit(alice(), secret(3)));
+		run_to(35);
+		assert_ok!(reveal(alice(), secret(3)));
+		run_to(39);
+		assert_eq!(RandomnessBeacon::random(b"lottery"), output);
+
+		run_to(40);
+		assert_ne!(RandomnessBeacon::random(b"lottery"), output);
+	});
+}
+
+#[test]
+fn offchain_worker_commits_then_reveals_local_secret() {
+	let (offchain, _) = TestOffchainExt::new();
+	let (pool, pool_state) = TestTransactionPoolExt::new();
+	let keystore = KeyStore::new();
+	keystore.write().sr25519_generate_new(crate::KEY_TYPE, Some("//Alice")).unwrap();
+	let mut ext = new_test_ext();
+	ext.register_extension(OffchainExt::new(offchain));
+	ext.register_extension(TransactionPoolExt::new(pool));
+	ext.register_extension(KeystoreExt(keystore));
+
+	ext.execute_with(|| {
+		let submitted = |index: usize| {
+			let transactions = &pool_state.read().transactions;
+			let tx = Extrinsic::decode(&mut &transactions[index][..]).unwrap();
+			assert_eq!(tx.signature, Some((0, ())));
+			tx.call
+		};
+
+		// The commitment is submitted again with the same secret until it is included.
+		RandomnessBeacon::offchain_worker(10);
+		RandomnessBeacon::offchain_worker(11);
+		let commitment = match submitted(0) {
+			crate::Call::commit(commitment) => commitment,
+			call => panic!("unexpected call {:?}", call),
+		};
+		assert_eq!(submitted(1), crate::Call::commit(commitment));
+		assert_ok!(RandomnessBeacon::commit(Origin::signed(alice()), commitment));
+
+		run_to(14);
+		RandomnessBeacon::offchain_worker(14);
+		match submitted(2) {
+			crate::Call::reveal(secret) => assert_ok!(reveal(alice(), secret)),
+			call => panic!("unexpected call {:?}", call),
+		}
+		assert_eq!(pool_state.read().transactions.len(), 3);
+	});
+}
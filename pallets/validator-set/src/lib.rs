@@ -0,0 +1,202 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! A Root-managed validator set for PoA chains.
+//!
+//! The pallet keeps the list of validator accounts in storage and hands it to
+//! `pallet_session` through the `SessionManager` trait. Changes made by Root are
+//! picked up at the next session boundary and, following the usual session
+//! queueing rules, become active one session later. Validators must register
+//! their keys with `session.set_keys` before they are able to author blocks.
+//!
+//! The pallet also acts as the offence handler of the chain: validators reported
+//! for an offence (e.g. a GRANDPA equivocation) are removed from the set.
+
+use sp_std::prelude::*;
+use frame_support::{
+	decl_module, decl_storage, decl_event, decl_error, dispatch, ensure,
+	traits::{Contains, Get}, weights::Weight,
+};
+use frame_system::ensure_root;
+use pallet_session::SessionIndex;
+use sp_runtime::Perbill;
+use sp_staking::offence::{OffenceDetails, OnOffenceHandler};
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+/// Configure the pallet by specifying the parameters and types on which it depends.
+pub trait Trait: frame_system::Trait {
+	/// Because this pallet emits events, it depends on the runtime's definition of an event.
+	type Event: From<Event<Self>> + Into<<Self as frame_system::Trait>::Event>;
+}
+
+decl_storage! {
+	trait Store for Module<T: Trait> as ValidatorSet {
+		/// The accounts that should be validating from the next queued session on.
+		Validators get(fn validators) config(): Vec<T::AccountId>;
+
+		/// Whether `Validators` changed since it was last handed to the session pallet.
+		ValidatorsChanged get(fn validators_changed) build(|_: &GenesisConfig<T>| true): bool;
+	}
+}
+
+decl_event!(
+	pub enum Event<T> where AccountId = <T as frame_system::Trait>::AccountId {
+		/// A validator was added to the set. [validator]
+		ValidatorAdded(AccountId),
+		/// A validator was removed from the set. [validator]
+		ValidatorRemoved(AccountId),
+		/// A validator was removed from the set after being reported for an offence. [validator]
+		OffenderRemoved(AccountId),
+	}
+);
+
+decl_error! {
+	pub enum Error for Module<T: Trait> {
+		/// The account is already part of the validator set.
+		AlreadyValidator,
+		/// The account is not part of the validator set.
+		NotValidator,
+		/// Removing the account would leave the chain without validators.
+		TooFewValidators,
+	}
+}
+
+decl_module! {
+	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+		type Error = Error<T>;
+
+		fn deposit_event() = default;
+
+		/// Add a validator to the set. It starts authoring once the queued session
+		/// containing it becomes active. Must be called by Root.
+		#[weight = 10_000 + T::DbWeight::get().reads_writes(1, 2)]
+		pub fn add_validator(origin, validator: T::AccountId) -> dispatch::DispatchResult {
+			ensure_root(origin)?;
+
+			let mut validators = Self::validators();
+			ensure!(!validators.contains(&validator), Error::<T>::AlreadyValidator);
+			validators.push(validator.clone());
+
+			<Validators<T>>::put(validators);
+			ValidatorsChanged::put(true);
+
+			Self::deposit_event(RawEvent::ValidatorAdded(validator));
+			Ok(())
+		}
+
+		/// Remove a validator from the set. The last remaining validator can not be
+		/// removed. Must be called by Root.
+		#[weight = 10_000 + T::DbWeight::get().reads_writes(1, 2)]
+		pub fn remove_validator(origin, validator: T::AccountId) -> dispatch::DispatchResult {
+			ensure_root(origin)?;
+
+			let mut validators = Self::validators();
+			let position = validators.iter()
+				.position(|v| v == &validator)
+				.ok_or(Error::<T>::NotValidator)?;
+			ensure!(validators.len() > 1, Error::<T>::TooFewValidators);
+			validators.swap_remove(position);
+
+			<Validators<T>>::put(validators);
+			ValidatorsChanged::put(true);
+
+			Self::deposit_event(RawEvent::ValidatorRemoved(validator));
+			Ok(())
+		}
+	}
+}
+
+impl<T: Trait> Module<T> {
+	/// Make `validators` the validators of a chain that has none, such as a chain migrated
+	/// to this pallet. No change is pending afterwards: the session pallet is expected to
+	/// be validated by them already.
+	pub fn initialize_validators(validators: &[T::AccountId]) {
+		if Self::validators().is_empty() {
+			<Validators<T>>::put(validators);
+		}
+	}
+}
+
+impl<T: Trait> pallet_session::SessionManager<T::AccountId> for Module<T> {
+	fn new_session(_new_index: SessionIndex) -> Option<Vec<T::AccountId>> {
+		if Self::validators_changed() {
+			ValidatorsChanged::put(false);
+			Some(Self::validators())
+		} else {
+			None
+		}
+	}
+
+	fn end_session(_end_index: SessionIndex) {}
+
+	fn start_session(_start_index: SessionIndex) {}
+}
+
+/// The full identification of a validator is its account, see `pallet_session::historical`.
+impl<T: Trait> pallet_session::historical::SessionManager<T::AccountId, T::AccountId> for Module<T> {
+	fn new_session(new_index: SessionIndex) -> Option<Vec<(T::AccountId, T::AccountId)>> {
+		<Self as pallet_session::SessionManager<_>>::new_session(new_index)
+			.map(|validators| validators.into_iter().map(|v| (v.clone(), v)).collect())
+	}
+
+	fn end_session(end_index: SessionIndex) {
+		<Self as pallet_session::SessionManager<_>>::end_session(end_index)
+	}
+
+	fn start_session(start_index: SessionIndex) {
+		<Self as pallet_session::SessionManager<_>>::start_session(start_index)
+	}
+}
+
+/// The members are the accounts in `Validators`, whether they are validating already or not.
+impl<T: Trait> Contains<T::AccountId> for Module<T> {
+	fn contains(who: &T::AccountId) -> bool {
+		Self::validators().contains(who)
+	}
+
+	fn sorted_members() -> Vec<T::AccountId> {
+		let mut validators = Self::validators();
+		validators.sort();
+		validators
+	}
+}
+
+impl<T: Trait> OnOffenceHandler<T::AccountId, (T::AccountId, T::AccountId), Weight> for Module<T> {
+	fn on_offence(
+		offenders: &[OffenceDetails<T::AccountId, (T::AccountId, T::AccountId)>],
+		_slash_fraction: &[Perbill],
+		_session: SessionIndex,
+	) -> Result<Weight, ()> {
+		let mut validators = Self::validators();
+		let mut removed = 0;
+
+		for details in offenders {
+			let (offender, _) = &details.offender;
+			// Never remove the last validator, the chain would stall.
+			if validators.len() <= 1 {
+				break;
+			}
+			if let Some(position) = validators.iter().position(|v| v == offender) {
+				validators.swap_remove(position);
+				removed += 1;
+				Self::deposit_event(RawEvent::OffenderRemoved(offender.clone()));
+			}
+		}
+
+		if removed > 0 {
+			<Validators<T>>::put(validators);
+			ValidatorsChanged::put(true);
+			Ok(T::DbWeight::get().reads_writes(1, 2))
+		} else {
+			Ok(T::DbWeight::get().reads(1))
+		}
+	}
+
+	fn can_report() -> bool {
+		true
+	}
+}
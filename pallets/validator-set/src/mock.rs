@@ -1,4 +1,4 @@
-use crate::{Module, Trait};
+use crate::{Module, Trait, GenesisConfig};
 use sp_core::H256;
 use frame_support::{impl_outer_origin, parameter_types, weights::Weight};
 use sp_runtime::{
@@ -53,9 +53,13 @@ impl Trait for Test {
 	type Event = ();
 }
 
-pub type TemplateModule = Module<Test>;
+pub type ValidatorSet = Module<Test>;
 
-// Build genesis storage according to the mock runtime.
+// Build genesis storage according to the mock runtime, with accounts 1 and 2 as validators.
 pub fn new_test_ext() -> sp_io::TestExternalities {
-	system::GenesisConfig::default().build_storage::<Test>().unwrap().into()
+	let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	GenesisConfig::<Test> {
+		validators: vec![1, 2],
+	}.assimilate_storage(&mut t).unwrap();
+	t.into()
 }
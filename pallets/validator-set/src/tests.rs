@@ -0,0 +1,102 @@
+use crate::{Error, mock::*};
+use frame_support::{assert_ok, assert_noop, traits::Contains};
+use pallet_session::SessionManager;
+use sp_runtime::{DispatchError, Perbill};
+use sp_staking::offence::{OffenceDetails, OnOffenceHandler};
+
+#[test]
+fn genesis_validators_are_handed_to_session() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(ValidatorSet::validators(), vec![1, 2]);
+		assert_eq!(ValidatorSet::new_session(0), Some(vec![1, 2]));
+		// Nothing changed since, so the session pallet keeps its current set.
+		assert_eq!(ValidatorSet::new_session(1), None);
+	});
+}
+
+#[test]
+fn chains_without_validators_are_initialized_without_pending_change() {
+	sp_io::TestExternalities::new(Default::default()).execute_with(|| {
+		ValidatorSet::initialize_validators(&[1, 2]);
+		assert_eq!(ValidatorSet::validators(), vec![1, 2]);
+		assert_eq!(ValidatorSet::new_session(1), None);
+
+		ValidatorSet::initialize_validators(&[3]);
+		assert_eq!(ValidatorSet::validators(), vec![1, 2]);
+	});
+}
+
+#[test]
+fn root_can_add_validator() {
+	new_test_ext().execute_with(|| {
+		ValidatorSet::new_session(0);
+		assert_ok!(ValidatorSet::add_validator(Origin::root(), 3));
+		assert_eq!(ValidatorSet::validators(), vec![1, 2, 3]);
+		assert_eq!(ValidatorSet::new_session(1), Some(vec![1, 2, 3]));
+		assert_eq!(ValidatorSet::new_session(2), None);
+	});
+}
+
+#[test]
+fn root_can_remove_validator() {
+	new_test_ext().execute_with(|| {
+		ValidatorSet::new_session(0);
+		assert_ok!(ValidatorSet::remove_validator(Origin::root(), 1));
+		assert_eq!(ValidatorSet::new_session(1), Some(vec![2]));
+	});
+}
+
+#[test]
+fn only_root_can_change_validators() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(ValidatorSet::add_validator(Origin::signed(1), 3), DispatchError::BadOrigin);
+		assert_noop!(ValidatorSet::remove_validator(Origin::signed(1), 2), DispatchError::BadOrigin);
+	});
+}
+
+#[test]
+fn correct_error_for_invalid_changes() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			ValidatorSet::add_validator(Origin::root(), 1),
+			Error::<Test>::AlreadyValidator
+		);
+		assert_noop!(
+			ValidatorSet::remove_validator(Origin::root(), 3),
+			Error::<Test>::NotValidator
+		);
+		assert_ok!(ValidatorSet::remove_validator(Origin::root(), 1));
+		assert_noop!(
+			ValidatorSet::remove_validator(Origin::root(), 2),
+			Error::<Test>::TooFewValidators
+		);
+	});
+}
+
+#[test]
+fn offenders_are_removed_from_the_set() {
+	new_test_ext().execute_with(|| {
+		ValidatorSet::new_session(0);
+		let offence = |who: u64| OffenceDetails { offender: (who, who), reporters: vec![] };
+
+		assert_ok!(ValidatorSet::on_offence(&[offence(1)], &[Perbill::from_percent(100)], 0));
+		assert_eq!(ValidatorSet::validators(), vec![2]);
+		assert_eq!(ValidatorSet::new_session(1), Some(vec![2]));
+
+		// The last validator is kept even if it misbehaves.
+		assert_ok!(ValidatorSet::on_offence(&[offence(2)], &[Perbill::from_percent(100)], 1));
+		assert_eq!(ValidatorSet::validators(), vec![2]);
+		assert_eq!(ValidatorSet::new_session(2), None);
+	});
+}
+
+#[test]
+fn validators_are_members() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(ValidatorSet::add_validator(Origin::root(), 0));
+
+		assert!(ValidatorSet::contains(&0));
+		assert!(!ValidatorSet::contains(&3));
+		assert_eq!(ValidatorSet::sorted_members(), vec![0, 1, 2]);
+	});
+}
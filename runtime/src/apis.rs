@@ -0,0 +1,90 @@
+//! Runtime APIs specific to this runtime, used by the node's RPC extensions.
+
+use codec::{Encode, Decode};
+use pallet_transaction_payment::Multiplier;
+use sp_core::{H160, U256};
+use sp_runtime::{ApplyExtrinsicResult, DispatchError, RuntimeDebug, traits::Block as BlockT};
+use sp_std::prelude::*;
+use crate::{AccountId, Balance, Event, Runtime};
+
+/// Metadata registered for a contract in `ContractRegistry`.
+pub type ContractMetadata = pallet_contract_registry::ContractMetadata<AccountId, Balance>;
+
+/// Outcome of an SSVM call executed by `SSVMApi::call`.
+#[derive(Encode, Decode, PartialEq, Eq, RuntimeDebug)]
+pub struct CallResult {
+	/// Output of the contract, which is the revert data if the call failed.
+	pub output: Vec<u8>,
+	/// Why the call failed, `None` if it succeeded.
+	pub error: Option<DispatchError>,
+	/// Decoded reason of an `Error(string)` revert.
+	pub revert_reason: Option<Vec<u8>>,
+}
+
+/// Outcome of an SSVM contract creation executed by `SSVMApi::create`.
+#[derive(Encode, Decode, PartialEq, Eq, RuntimeDebug)]
+pub struct CreateResult {
+	/// Address of the new contract, `None` if the creation failed.
+	pub address: Option<H160>,
+	/// Output of the deployment code.
+	pub output: Vec<u8>,
+	/// Why the creation failed, `None` if it succeeded.
+	pub error: Option<DispatchError>,
+}
+
+/// What the runtime observed while applying an extrinsic.
+#[derive(Encode, Decode, RuntimeDebug)]
+pub struct ExtrinsicReplay {
+	/// Outcome of applying the extrinsic.
+	pub result: ApplyExtrinsicResult,
+	/// Events deposited while applying the extrinsic, in order.
+	pub events: Vec<Event>,
+	/// Account that signed the extrinsic, with the name it was signed with resolved, `None`
+	/// for unsigned extrinsics.
+	pub signer: Option<AccountId>,
+	/// The SSVM calls the extrinsic dispatched, with the account each was dispatched from,
+	/// as found by `ssvm::dispatched_ssvm_calls` before applying it.
+	pub ssvm_calls: Vec<(AccountId, pallet_ssvm::Call<Runtime>)>,
+}
+
+sp_api::decl_runtime_apis! {
+	/// Fee related queries that are not covered by `TransactionPaymentApi`.
+	pub trait FeeMultiplierApi {
+		/// The multiplier that will be applied to the weight fee of transactions in the next block.
+		fn next_fee_multiplier() -> Multiplier;
+	}
+
+	/// Queries executing SSVM contracts.
+	pub trait SSVMApi {
+		/// Deploy `code` as `from` without persisting any change.
+		fn create(
+			from: AccountId,
+			code: Vec<u8>,
+			value: U256,
+			gas_limit: u32,
+			gas_price: U256,
+		) -> CreateResult;
+
+		/// Call the contract at `target` as `from` without persisting any change.
+		fn call(
+			from: AccountId,
+			target: H160,
+			input: Vec<u8>,
+			value: U256,
+			gas_limit: u32,
+			gas_price: U256,
+		) -> CallResult;
+	}
+
+	/// Queries of the contract metadata registry.
+	pub trait ContractRegistryApi {
+		/// The metadata registered for the contract at `address`, if any.
+		fn metadata(address: H160) -> Option<ContractMetadata>;
+	}
+
+	/// Re-execution of extrinsics on top of the state of their block.
+	pub trait ReplayApi {
+		/// Apply `extrinsic` like `BlockBuilder::apply_extrinsic` does and report what happened.
+		fn replay_extrinsic(extrinsic: <Block as BlockT>::Extrinsic) -> ExtrinsicReplay;
+	}
+}
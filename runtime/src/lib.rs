@@ -9,11 +9,12 @@ include!(concat!(env!("OUT_DIR"), "/wasm_binary.rs"));
 use sp_std::prelude::*;
 use sp_core::{crypto::KeyTypeId, Hasher, OpaqueMetadata};
 use sp_runtime::{
-	ApplyExtrinsicResult, generic, create_runtime_str, impl_opaque_keys, MultiSignature,
-	transaction_validity::{TransactionValidity, TransactionSource},
+	ApplyExtrinsicResult, generic, create_runtime_str, impl_opaque_keys, MultiSignature, ModuleId,
+	transaction_validity::{TransactionValidity, TransactionSource, TransactionPriority},
 };
 use sp_runtime::traits::{
-	BlakeTwo256, Block as BlockT, Verify, IdentifyAccount, NumberFor, Saturating,
+	BlakeTwo256, Block as BlockT, Verify, IdentifyAccount, NumberFor, Saturating, ConvertInto,
+	OpaqueKeys, Extrinsic as ExtrinsicT, SaturatedConversion,
 };
 use hash256_std_hasher::Hash256StdHasher;
 use sha3::{Digest, Keccak256};
@@ -30,16 +31,19 @@ use sp_version::NativeVersion;
 pub use sp_runtime::BuildStorage;
 pub use pallet_timestamp::Call as TimestampCall;
 pub use pallet_balances::Call as BalancesCall;
-pub use sp_runtime::{Permill, Perbill};
+pub use sp_runtime::{Permill, Perbill, Perquintill, FixedPointNumber};
+pub use pallet_transaction_payment::{Multiplier, TargetedFeeAdjustment};
 pub use frame_support::{
 	construct_runtime, parameter_types, StorageValue,
-	traits::{KeyOwnerProofSystem, Randomness},
+	traits::{GetCallMetadata, KeyOwnerProofSystem, Randomness},
 	weights::{
 		Weight, IdentityFee,
 		constants::{BlockExecutionWeight, ExtrinsicBaseWeight, RocksDbWeight, WEIGHT_PER_SECOND},
 	},
 };
 
+use pallet_session::historical as pallet_session_historical;
+
 pub use pallet_ssvm::Account as SSVMAccount;
 use pallet_ssvm::HashTruncateConvertAccountId;
 
@@ -108,10 +112,10 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	spec_name: create_runtime_str!("ssvm-node"),
 	impl_name: create_runtime_str!("ssvm-node"),
 	authoring_version: 1,
-	spec_version: 100,
+	spec_version: 101,
 	impl_version: 1,
 	apis: RUNTIME_API_VERSIONS,
-	transaction_version: 1,
+	transaction_version: 2,
 };
 
 pub const MILLISECS_PER_BLOCK: u64 = 6000;
@@ -154,7 +158,7 @@ impl frame_system::Trait for Runtime {
 	/// The aggregated dispatch type that is available for extrinsics.
 	type Call = Call;
 	/// The lookup mechanism to get account ID from whatever is passed in dispatchers.
-	type Lookup = multiaddress::AccountIdLookup<AccountId, ()>;
+	type Lookup = multiaddress::AccountIdLookup<AccountId, (), NameService>;
 	/// The index type for storing how many extrinsics an account has signed.
 	type Index = Index;
 	/// The index type for blocks.
@@ -205,6 +209,107 @@ impl frame_system::Trait for Runtime {
 	type SystemWeightInfo = ();
 }
 
+parameter_types! {
+	/// Validator set changes are applied at the boundaries of these sessions.
+	pub const Period: BlockNumber = 10 * MINUTES;
+	pub const Offset: BlockNumber = 0;
+	pub const DisabledValidatorsThreshold: Perbill = Perbill::from_percent(17);
+}
+
+impl pallet_session::Trait for Runtime {
+	type Event = Event;
+	/// Validators are identified by their account, no stash/controller split.
+	type ValidatorId = AccountId;
+	type ValidatorIdOf = ConvertInto;
+	type ShouldEndSession = pallet_session::PeriodicSessions<Period, Offset>;
+	type NextSessionRotation = pallet_session::PeriodicSessions<Period, Offset>;
+	/// The validator set is managed by Root through the `ValidatorSet` pallet.
+	type SessionManager = pallet_session::historical::NoteHistoricalRoot<Self, ValidatorSet>;
+	type SessionHandler = <opaque::SessionKeys as OpaqueKeys>::KeyTypeIdProviders;
+	type Keys = opaque::SessionKeys;
+	type DisabledValidatorsThreshold = DisabledValidatorsThreshold;
+	type WeightInfo = ();
+}
+
+impl pallet_session::historical::Trait for Runtime {
+	type FullIdentification = AccountId;
+	type FullIdentificationOf = ConvertInto;
+}
+
+impl pallet_validator_set::Trait for Runtime {
+	type Event = Event;
+}
+
+parameter_types! {
+	pub OffencesWeightSoftLimit: Weight = Perbill::from_percent(60) * MaximumBlockWeight::get();
+}
+
+impl pallet_offences::Trait for Runtime {
+	type Event = Event;
+	type IdentificationTuple = pallet_session::historical::IdentificationTuple<Self>;
+	/// Reported validators are removed from the validator set.
+	type OnOffenceHandler = ValidatorSet;
+	type WeightSoftLimit = OffencesWeightSoftLimit;
+}
+
+parameter_types! {
+	pub const UncleGenerations: BlockNumber = 0;
+}
+
+impl pallet_authorship::Trait for Runtime {
+	type FindAuthor = pallet_session::FindAccountFromAuthorIndex<Self, Aura>;
+	type UncleGenerations = UncleGenerations;
+	type FilterUncle = ();
+	type EventHandler = ();
+}
+
+impl<C> frame_system::offchain::SendTransactionTypes<C> for Runtime where
+	Call: From<C>,
+{
+	type OverarchingCall = Call;
+	type Extrinsic = UncheckedExtrinsic;
+}
+
+impl frame_system::offchain::SigningTypes for Runtime {
+	type Public = <Signature as Verify>::Signer;
+	type Signature = Signature;
+}
+
+impl<C> frame_system::offchain::CreateSignedTransaction<C> for Runtime where
+	Call: From<C>,
+{
+	/// Sign transactions submitted by offchain workers with the runtime's `SignedExtra`,
+	/// mortal for half of `BlockHashCount` blocks.
+	fn create_transaction<A: frame_system::offchain::AppCrypto<Self::Public, Self::Signature>>(
+		call: Call,
+		public: <Signature as Verify>::Signer,
+		account: AccountId,
+		nonce: Index,
+	) -> Option<(Call, <UncheckedExtrinsic as ExtrinsicT>::SignaturePayload)> {
+		let period = BlockHashCount::get().checked_next_power_of_two().map(|c| c / 2).unwrap_or(2) as u64;
+		let current_block = System::block_number().saturated_into::<u64>().saturating_sub(1);
+		let extra: SignedExtra = (
+			frame_system::CheckSpecVersion::new(),
+			frame_system::CheckTxVersion::new(),
+			frame_system::CheckGenesis::new(),
+			frame_system::CheckEra::from(generic::Era::mortal(period, current_block)),
+			frame_system::CheckNonce::from(nonce),
+			frame_system::CheckWeight::new(),
+			pallet_transaction_payment::ChargeTransactionPayment::from(0),
+			pallet_block_gas::CheckBlockGas::new(),
+			ssvm::ChargeGas,
+			ssvm::ReportRevert,
+			ssvm::NoteDeployer,
+		);
+		let payload = SignedPayload::new(call, extra)
+			.map_err(|e| frame_support::debug::warn!("Unable to create signed payload: {:?}", e))
+			.ok()?;
+		let signature = payload.using_encoded(|payload| A::sign(payload, public))?;
+		let (call, extra, _) = payload.deconstruct();
+		Some((call, (account.into(), signature, extra)))
+	}
+}
+
 impl pallet_aura::Trait for Runtime {
 	type AuthorityId = AuraId;
 }
@@ -213,7 +318,7 @@ impl pallet_grandpa::Trait for Runtime {
 	type Event = Event;
 	type Call = Call;
 
-	type KeyOwnerProofSystem = ();
+	type KeyOwnerProofSystem = Historical;
 
 	type KeyOwnerProof =
 		<Self::KeyOwnerProofSystem as KeyOwnerProofSystem<(KeyTypeId, GrandpaId)>>::Proof;
@@ -223,7 +328,7 @@ impl pallet_grandpa::Trait for Runtime {
 		GrandpaId,
 	)>>::IdentificationTuple;
 
-	type HandleEquivocation = ();
+	type HandleEquivocation = pallet_grandpa::EquivocationHandler<Self::KeyOwnerIdentification, Offences>;
 
 	type WeightInfo = ();
 }
@@ -251,7 +356,8 @@ impl pallet_balances::Trait for Runtime {
 	type Balance = Balance;
 	/// The ubiquitous event type.
 	type Event = Event;
-	type DustRemoval = ();
+	/// Dust of reaped accounts goes to the treasury.
+	type DustRemoval = pallet_fee_distribution::ToTreasury<Runtime>;
 	type ExistentialDeposit = ExistentialDeposit;
 	type AccountStore = System;
 	type WeightInfo = ();
@@ -259,14 +365,208 @@ impl pallet_balances::Trait for Runtime {
 
 parameter_types! {
 	pub const TransactionByteFee: Balance = 1;
+	/// Fees go up when blocks are fuller than this, and down when they are emptier.
+	pub const TargetBlockFullness: Perquintill = Perquintill::from_percent(25);
+	/// How fast the multiplier reacts to the block fullness.
+	pub AdjustmentVariable: Multiplier = Multiplier::saturating_from_rational(3, 100_000);
+	/// The multiplier never drops below this value, however empty the blocks are.
+	pub MinimumMultiplier: Multiplier = Multiplier::saturating_from_rational(1, 1_000_000_000u128);
 }
 
 impl pallet_transaction_payment::Trait for Runtime {
 	type Currency = Balances;
-	type OnTransactionPayment = ();
+	/// Fees and tips are split between the block author and the treasury.
+	type OnTransactionPayment = FeeDistribution;
 	type TransactionByteFee = TransactionByteFee;
 	type WeightToFee = IdentityFee<Balance>;
-	type FeeMultiplierUpdate = ();
+	type FeeMultiplierUpdate =
+		TargetedFeeAdjustment<Self, TargetBlockFullness, AdjustmentVariable, MinimumMultiplier>;
+}
+
+parameter_types! {
+	pub const TreasuryModuleId: ModuleId = ModuleId(*b"py/trsry");
+}
+
+impl pallet_fee_distribution::Trait for Runtime {
+	type Event = Event;
+	type Currency = Balances;
+	type TreasuryModuleId = TreasuryModuleId;
+}
+
+impl pallet_block_gas::Trait for Runtime {
+	type Event = Event;
+	type GasLimitOf = ssvm::GasParams;
+}
+
+impl pallet_contract_revert::Trait for Runtime {
+	type Event = Event;
+}
+
+parameter_types! {
+	pub const ContractMetadataDeposit: Balance = 100 * ExistentialDeposit::get();
+	pub const MaxContractMetadataLength: u32 = 256;
+}
+
+impl pallet_contract_registry::Trait for Runtime {
+	type Event = Event;
+	type Currency = Balances;
+	type MetadataDeposit = ContractMetadataDeposit;
+	type MaxFieldLength = MaxContractMetadataLength;
+}
+
+parameter_types! {
+	pub const NameRegistrationFee: Balance = 10 * ExistentialDeposit::get();
+	pub const NameRegistrationPeriod: BlockNumber = 365 * DAYS;
+	pub const MaxNamePeriods: u32 = 10;
+	pub const MinNameLength: u32 = 3;
+	pub const MaxNameLength: u32 = 64;
+}
+
+impl pallet_name_service::Trait for Runtime {
+	type Event = Event;
+	type Currency = Balances;
+	/// Registration fees are split between the block author and the treasury.
+	type OnRegistrationFee = FeeDistribution;
+	type RegistrationFee = NameRegistrationFee;
+	type RegistrationPeriod = NameRegistrationPeriod;
+	type MaxPeriods = MaxNamePeriods;
+	type MinNameLength = MinNameLength;
+	type MaxNameLength = MaxNameLength;
+	type Addresses = ssvm::Addresses;
+}
+
+impl pallet_utility::Trait for Runtime {
+	type Event = Event;
+	type Call = Call;
+	type WeightInfo = ();
+}
+
+impl pallet_atomic_batch::Trait for Runtime {
+	type Event = Event;
+	type Call = Call;
+}
+
+parameter_types! {
+	/// Deposit reserved for a pending multisig operation, per operation and per signatory.
+	pub const MultisigDepositBase: Balance = 10 * ExistentialDeposit::get();
+	pub const MultisigDepositFactor: Balance = ExistentialDeposit::get();
+	pub const MaxSignatories: u16 = 16;
+}
+
+impl pallet_multisig::Trait for Runtime {
+	type Event = Event;
+	type Call = Call;
+	type Currency = Balances;
+	type DepositBase = MultisigDepositBase;
+	type DepositFactor = MultisigDepositFactor;
+	type MaxSignatories = MaxSignatories;
+	type WeightInfo = ();
+}
+
+parameter_types! {
+	/// Deposit reserved for the proxies of an account, per account and per proxy.
+	pub const ProxyDepositBase: Balance = 10 * ExistentialDeposit::get();
+	pub const ProxyDepositFactor: Balance = ExistentialDeposit::get();
+	pub const MaxProxies: u16 = 32;
+	/// Deposit reserved for the announcements of a proxy, per proxy and per announcement.
+	pub const AnnouncementDepositBase: Balance = 10 * ExistentialDeposit::get();
+	pub const AnnouncementDepositFactor: Balance = ExistentialDeposit::get();
+	pub const MaxPending: u32 = 32;
+}
+
+impl pallet_proxy::Trait for Runtime {
+	type Event = Event;
+	type Call = Call;
+	type Currency = Balances;
+	/// Proxies either dispatch any call or SSVM calls only.
+	type ProxyType = ssvm::ProxyType;
+	type ProxyDepositBase = ProxyDepositBase;
+	type ProxyDepositFactor = ProxyDepositFactor;
+	type MaxProxies = MaxProxies;
+	type WeightInfo = ();
+	type MaxPending = MaxPending;
+	type CallHasher = BlakeTwo256;
+	type AnnouncementDepositBase = AnnouncementDepositBase;
+	type AnnouncementDepositFactor = AnnouncementDepositFactor;
+}
+
+parameter_types! {
+	pub MaximumSchedulerWeight: Weight = Perbill::from_percent(80) * MaximumBlockWeight::get();
+	pub const MaxScheduledPerBlock: u32 = 50;
+}
+
+impl pallet_scheduler::Trait for Runtime {
+	type Event = Event;
+	type Origin = Origin;
+	type PalletsOrigin = OriginCaller;
+	type Call = Call;
+	type MaximumWeight = MaximumSchedulerWeight;
+	/// Accounts schedule calls through `ContractScheduler`, which pays for them.
+	type ScheduleOrigin = frame_system::EnsureRoot<AccountId>;
+	type MaxScheduledPerBlock = MaxScheduledPerBlock;
+	type WeightInfo = ();
+}
+
+parameter_types! {
+	pub const MaxScheduledExecutions: u32 = 1_000;
+	/// Executions pay at least this much for gas, so that scheduling them is not free.
+	pub const MinScheduledGasCost: Balance = ExistentialDeposit::get();
+	pub const ScheduledTaskDeposit: Balance = 10 * ExistentialDeposit::get();
+}
+
+impl pallet_contract_scheduler::Trait for Runtime {
+	type Event = Event;
+	type Currency = Balances;
+	type Call = Call;
+	type GasCost = ssvm::ScheduledGas;
+	/// Gas is split between the block author and the treasury like the one of extrinsics,
+	/// and failed contract calls and deployers are reported like the ones of extrinsics.
+	type OnDispatch = (ssvm::ChargeGas, (ssvm::ReportRevert, ssvm::NoteDeployer));
+	type MinGasCost = MinScheduledGasCost;
+	type TaskDeposit = ScheduledTaskDeposit;
+	type PalletsOrigin = OriginCaller;
+	type Scheduler = Scheduler;
+	type MaxExecutions = MaxScheduledExecutions;
+}
+
+parameter_types! {
+	pub const UpkeepInterval: BlockNumber = 10;
+	pub const UpkeepUnsignedPriority: TransactionPriority = TransactionPriority::max_value() / 2;
+	pub const MaxUpkeepInputLength: u32 = 1024;
+	pub const MaxUpkeepGasLimit: u32 = 10_000_000;
+	/// A block using all of its gas uses all of its weight.
+	pub const UpkeepWeightPerGas: Weight = MaximumBlockWeight::get() / migrations::BLOCK_GAS_LIMIT;
+}
+
+impl pallet_contract_keeper::Trait for Runtime {
+	type AuthorityId = pallet_contract_keeper::crypto::KeeperId;
+	type Event = Event;
+	type Call = Call;
+	/// Contracts registered in `ContractRegistry`, whose owner registers their upkeep.
+	type Contracts = ssvm::Upkeep;
+	type UpkeepInterval = UpkeepInterval;
+	type UnsignedPriority = UpkeepUnsignedPriority;
+	type MaxInputLength = MaxUpkeepInputLength;
+	type MaxGasLimit = MaxUpkeepGasLimit;
+	type WeightPerGas = UpkeepWeightPerGas;
+}
+
+parameter_types! {
+	/// Validators commit during the first half of a round and reveal during the second.
+	pub const RandomnessRoundLength: BlockNumber = 20;
+	pub const RandomnessCommitDeposit: Balance = 100 * ExistentialDeposit::get();
+}
+
+impl pallet_randomness_beacon::Trait for Runtime {
+	type AuthorityId = pallet_randomness_beacon::crypto::BeaconId;
+	type Event = Event;
+	type Call = Call;
+	type Currency = Balances;
+	type Participants = ValidatorSet;
+	type CommitDeposit = RandomnessCommitDeposit;
+	/// Deposits of validators that do not reveal go to the treasury.
+	type OnSlash = pallet_fee_distribution::ToTreasury<Runtime>;
+	type RoundLength = RandomnessRoundLength;
 }
 
 impl pallet_sudo::Trait for Runtime {
@@ -288,20 +588,48 @@ construct_runtime!(
 		UncheckedExtrinsic = UncheckedExtrinsic
 	{
 		System: frame_system::{Module, Call, Config, Storage, Event<T>},
-		RandomnessCollectiveFlip: pallet_randomness_collective_flip::{Module, Call, Storage},
+		RandomnessBeacon: pallet_randomness_beacon::{Module, Call, Storage, Event<T>},
 		Timestamp: pallet_timestamp::{Module, Call, Storage, Inherent},
+		// `ValidatorSet` must come before `Session`, which reads it when building genesis,
+		// and `Session` must come before the consensus pallets whose authorities it drives.
+		ValidatorSet: pallet_validator_set::{Module, Call, Storage, Event<T>, Config<T>},
+		Session: pallet_session::{Module, Call, Storage, Event, Config<T>},
+		Historical: pallet_session_historical::{Module},
 		Aura: pallet_aura::{Module, Config<T>, Inherent},
-		Grandpa: pallet_grandpa::{Module, Call, Storage, Config, Event},
+		Authorship: pallet_authorship::{Module, Call, Storage},
+		Grandpa: pallet_grandpa::{Module, Call, Storage, Config, Event, ValidateUnsigned},
+		Offences: pallet_offences::{Module, Call, Storage, Event},
 		Balances: pallet_balances::{Module, Call, Storage, Config<T>, Event<T>},
 		TransactionPayment: pallet_transaction_payment::{Module, Storage},
+		FeeDistribution: pallet_fee_distribution::{Module, Call, Storage, Event<T>, Config},
+		BlockGas: pallet_block_gas::{Module, Call, Storage, Event, Config},
+		ContractRevert: pallet_contract_revert::{Module, Call, Event},
+		ContractRegistry: pallet_contract_registry::{Module, Call, Storage, Event<T>},
+		NameService: pallet_name_service::{Module, Call, Storage, Event<T>},
 		Sudo: pallet_sudo::{Module, Call, Config<T>, Storage, Event<T>},
-		// Include the custom logic from the template pallet in the runtime.
+		Utility: pallet_utility::{Module, Call, Event},
+		AtomicBatch: pallet_atomic_batch::{Module, Call, Event},
+		Multisig: pallet_multisig::{Module, Call, Storage, Event<T>},
+		Proxy: pallet_proxy::{Module, Call, Storage, Event<T>},
+		Scheduler: pallet_scheduler::{Module, Call, Storage, Event<T>},
+		ContractScheduler: pallet_contract_scheduler::{Module, Call, Storage, Event<T>},
+		ContractKeeper: pallet_contract_keeper::{Module, Call, Storage, Event<T>, ValidateUnsigned},
 		SSVM: pallet_ssvm::{Module, Config, Call, Storage, Event},
 	}
 );
 
 /// The address format for describing accounts.
 mod multiaddress;
+/// Ethereum transaction semantics for SSVM extrinsics.
+pub mod ssvm;
+/// Runtime APIs declared by this runtime.
+pub mod apis;
+/// Storage migrations run on runtime upgrades.
+pub mod migrations;
+
+#[cfg(test)]
+mod tests;
+
 pub type Address = multiaddress::MultiAddress<AccountId, ()>;
 /// Block header type as expected by this runtime.
 pub type Header = generic::Header<BlockNumber, BlakeTwo256>;
@@ -319,10 +647,16 @@ pub type SignedExtra = (
 	frame_system::CheckEra<Runtime>,
 	frame_system::CheckNonce<Runtime>,
 	frame_system::CheckWeight<Runtime>,
-	pallet_transaction_payment::ChargeTransactionPayment<Runtime>
+	pallet_transaction_payment::ChargeTransactionPayment<Runtime>,
+	pallet_block_gas::CheckBlockGas<Runtime>,
+	ssvm::ChargeGas,
+	ssvm::ReportRevert,
+	ssvm::NoteDeployer,
 );
 /// Unchecked extrinsic type as expected by this runtime.
 pub type UncheckedExtrinsic = generic::UncheckedExtrinsic<Address, Call, Signature, SignedExtra>;
+/// The payload being signed in transactions.
+pub type SignedPayload = generic::SignedPayload<Call, SignedExtra>;
 /// Extrinsic type that has already been checked.
 pub type CheckedExtrinsic = generic::CheckedExtrinsic<AccountId, Call, SignedExtra>;
 /// Executive: handles dispatch to the various modules.
@@ -332,6 +666,7 @@ pub type Executive = frame_executive::Executive<
 	frame_system::ChainContext<Runtime>,
 	Runtime,
 	AllModules,
+	migrations::FromTemplate,
 >;
 
 impl_runtime_apis! {
@@ -377,7 +712,7 @@ impl_runtime_apis! {
 		}
 
 		fn random_seed() -> <Block as BlockT>::Hash {
-			RandomnessCollectiveFlip::random_seed()
+			RandomnessBeacon::random_seed()
 		}
 	}
 
@@ -386,7 +721,7 @@ impl_runtime_apis! {
 			source: TransactionSource,
 			tx: <Block as BlockT>::Extrinsic,
 		) -> TransactionValidity {
-			Executive::validate_transaction(source, tx)
+			ssvm::validate_transaction(source, tx)
 		}
 	}
 
@@ -424,23 +759,29 @@ impl_runtime_apis! {
 		}
 
 		fn submit_report_equivocation_unsigned_extrinsic(
-			_equivocation_proof: fg_primitives::EquivocationProof<
+			equivocation_proof: fg_primitives::EquivocationProof<
 				<Block as BlockT>::Hash,
 				NumberFor<Block>,
 			>,
-			_key_owner_proof: fg_primitives::OpaqueKeyOwnershipProof,
+			key_owner_proof: fg_primitives::OpaqueKeyOwnershipProof,
 		) -> Option<()> {
-			None
+			let key_owner_proof = key_owner_proof.decode()?;
+
+			Grandpa::submit_unsigned_equivocation_report(
+				equivocation_proof,
+				key_owner_proof,
+			)
 		}
 
 		fn generate_key_ownership_proof(
 			_set_id: fg_primitives::SetId,
-			_authority_id: GrandpaId,
+			authority_id: GrandpaId,
 		) -> Option<fg_primitives::OpaqueKeyOwnershipProof> {
-			// NOTE: this is the only implementation possible since we've
-			// defined our key owner proof type as a bottom type (i.e. a type
-			// with no values).
-			None
+			use codec::Encode;
+
+			Historical::prove((fg_primitives::KEY_TYPE, authority_id))
+				.map(|p| p.encode())
+				.map(fg_primitives::OpaqueKeyOwnershipProof::new)
 		}
 	}
 
@@ -460,6 +801,64 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl apis::FeeMultiplierApi<Block> for Runtime {
+		fn next_fee_multiplier() -> Multiplier {
+			TransactionPayment::next_fee_multiplier()
+		}
+	}
+
+	impl apis::SSVMApi<Block> for Runtime {
+		fn create(
+			from: AccountId,
+			code: Vec<u8>,
+			value: sp_core::U256,
+			gas_limit: u32,
+			gas_price: sp_core::U256,
+		) -> apis::CreateResult {
+			ssvm::dry_run_create(from, code, value, gas_limit, gas_price)
+		}
+
+		fn call(
+			from: AccountId,
+			target: sp_core::H160,
+			input: Vec<u8>,
+			value: sp_core::U256,
+			gas_limit: u32,
+			gas_price: sp_core::U256,
+		) -> apis::CallResult {
+			ssvm::dry_run(from, target, input, value, gas_limit, gas_price)
+		}
+	}
+
+	impl apis::ContractRegistryApi<Block> for Runtime {
+		fn metadata(address: sp_core::H160) -> Option<apis::ContractMetadata> {
+			ContractRegistry::metadata(address)
+		}
+	}
+
+	impl apis::ReplayApi<Block> for Runtime {
+		fn replay_extrinsic(extrinsic: <Block as BlockT>::Extrinsic) -> apis::ExtrinsicReplay {
+			use sp_runtime::traits::StaticLookup;
+
+			// Looked up before applying the extrinsic, which may change the names and the
+			// multisig approvals they depend on.
+			let signer = extrinsic.signature.as_ref()
+				.and_then(|(address, _, _)| <Runtime as frame_system::Trait>::Lookup::lookup(address.clone()).ok());
+			let ssvm_calls = signer.as_ref()
+				.map(|signer| ssvm::dispatched_ssvm_calls(signer, &extrinsic.function))
+				.unwrap_or_default();
+			let previous_events = System::event_count() as usize;
+			let result = Executive::apply_extrinsic(extrinsic);
+			let events = System::events()
+				.into_iter()
+				.skip(previous_events)
+				.map(|record| record.event)
+				.collect();
+
+			apis::ExtrinsicReplay { result, events, signer, ssvm_calls }
+		}
+	}
+
 	#[cfg(feature = "runtime-benchmarks")]
 	impl frame_benchmarking::Benchmark<Block> for Runtime {
 		fn dispatch_benchmark(
@@ -0,0 +1,91 @@
+//! Storage migrations run on runtime upgrades.
+
+use frame_support::{
+	generate_storage_alias,
+	storage::{StorageValue, migration::{have_storage_value, remove_storage_prefix}},
+	traits::{Currency, OnRuntimeUpgrade},
+	weights::Weight,
+};
+use frame_system::RawOrigin;
+use sp_core::sr25519;
+use sp_runtime::{MultiSigner, Perbill, traits::IdentifyAccount};
+use sp_std::prelude::*;
+use crate::{
+	Aura, Balances, BlockGas, ExistentialDeposit, FeeDistribution, Grandpa, MaximumBlockWeight,
+	Origin, Runtime, ValidatorSet, opaque::SessionKeys,
+};
+
+/// Share of the fees and gas going to the block author on chains started from the template.
+pub const AUTHOR_SHARE: Perbill = Perbill::from_percent(20);
+/// Block gas limit of chains started from the template.
+pub const BLOCK_GAS_LIMIT: pallet_block_gas::Gas = 500_000_000;
+
+type ValidatorId = <Runtime as pallet_session::Trait>::ValidatorId;
+type Keys = <Runtime as pallet_session::Trait>::Keys;
+
+// The storage items of `pallet_session` are private, so they are aliased with its own types.
+generate_storage_alias!(Session, Validators => Value<Vec<ValidatorId>>);
+generate_storage_alias!(Session, QueuedKeys => Value<Vec<(ValidatorId, Keys)>>);
+
+/// Migration of chains started with the runtime of the node template, which configured the
+/// Aura and GRANDPA authorities directly and kept the randomness of `RandomnessCollectiveFlip`.
+///
+/// The authorities become the validators of `ValidatorSet` and `Session`, the account of a
+/// validator being the one of its Aura key as in the chain specs of the template, and the
+/// pallets added since get the configuration of the chain specs of this node. Runs once: a
+/// chain with validators is not migrated again, and the storage of `RandomnessCollectiveFlip`
+/// is only removed while it is there.
+pub struct FromTemplate;
+
+impl OnRuntimeUpgrade for FromTemplate {
+	fn on_runtime_upgrade() -> Weight {
+		let db_weight = <Runtime as frame_system::Trait>::DbWeight::get();
+		// Left behind by the removal of `RandomnessCollectiveFlip`.
+		let mut weight = if have_storage_value(b"RandomnessCollectiveFlip", b"RandomMaterial", &[]) {
+			remove_storage_prefix(b"RandomnessCollectiveFlip", b"RandomMaterial", &[]);
+			db_weight.reads_writes(1, 1)
+		} else {
+			db_weight.reads(1)
+		};
+
+		if !ValidatorSet::validators().is_empty() {
+			weight += db_weight.reads(1);
+			return weight;
+		}
+
+		migrate_authorities();
+
+		let _ = FeeDistribution::set_author_share(RawOrigin::Root.into(), AUTHOR_SHARE);
+		let treasury = FeeDistribution::treasury_account_id();
+		if Balances::total_balance(&treasury) < ExistentialDeposit::get() {
+			let _ = Balances::make_free_balance_be(&treasury, ExistentialDeposit::get());
+		}
+		let _ = BlockGas::set_block_gas_limit(RawOrigin::Root.into(), BLOCK_GAS_LIMIT);
+
+		MaximumBlockWeight::get()
+	}
+}
+
+/// Make the Aura and GRANDPA authorities the validators of `ValidatorSet` and `Session`.
+///
+/// The template lists the Aura and GRANDPA keys of the authorities in the same order.
+fn migrate_authorities() {
+	let keys = Aura::authorities()
+		.into_iter()
+		.zip(Grandpa::grandpa_authorities().into_iter().map(|(grandpa, _)| grandpa))
+		.map(|(aura, grandpa)| {
+			let account = MultiSigner::from(sr25519::Public::from(aura.clone())).into_account();
+			(account, SessionKeys { aura, grandpa })
+		})
+		.collect::<Vec<_>>();
+	let validators = keys.iter().map(|(account, _)| account.clone()).collect::<Vec<_>>();
+
+	// The validators are already the authorities, so no validator set change is pending.
+	ValidatorSet::initialize_validators(&validators);
+	// The current and next sessions are validated by the authorities with their keys.
+	Validators::put(&validators);
+	QueuedKeys::put(&keys);
+	for (account, keys) in keys {
+		let _ = pallet_session::Module::<Runtime>::set_keys(Origin::signed(account), keys, Vec::new());
+	}
+}
@@ -22,6 +22,7 @@
 use codec::{Encode, Decode, Codec};
 use sp_std::{vec::Vec, marker::PhantomData, fmt::Debug};
 use sp_runtime::{RuntimeDebug, traits::{LookupError, StaticLookup}};
+use pallet_name_service::ResolveName;
 
 /// A multi-format address wrapper for on-chain accounts.
 #[derive(Encode, Decode, PartialEq, Eq, Clone, RuntimeDebug)]
@@ -69,18 +70,25 @@ impl<AccountId: Default, AccountIndex> Default for MultiAddress<AccountId, Accou
 }
 
 /// A lookup implementation returning the `AccountId` from a `MultiAddress`.
-pub struct AccountIdLookup<AccountId, AccountIndex>(PhantomData<(AccountId, AccountIndex)>);
-impl<AccountId, AccountIndex> StaticLookup for AccountIdLookup<AccountId, AccountIndex>
+///
+/// Unlike upstream, `MultiAddress::Raw` holds a name resolved by `Names`. Accounts are
+/// still turned back into `MultiAddress::Id`, which does not depend on the state.
+pub struct AccountIdLookup<AccountId, AccountIndex, Names = ()>(
+	PhantomData<(AccountId, AccountIndex, Names)>
+);
+impl<AccountId, AccountIndex, Names> StaticLookup for AccountIdLookup<AccountId, AccountIndex, Names>
 where
 	AccountId: Codec + Clone + PartialEq + Debug,
 	AccountIndex: Codec + Clone + PartialEq + Debug,
 	MultiAddress<AccountId, AccountIndex>: Codec,
+	Names: ResolveName<AccountId>,
 {
 	type Source = MultiAddress<AccountId, AccountIndex>;
 	type Target = AccountId;
 	fn lookup(x: Self::Source) -> Result<Self::Target, LookupError> {
 		match x {
 			MultiAddress::Id(i) => Ok(i),
+			MultiAddress::Raw(name) => Names::resolve(&name).ok_or(LookupError),
 			_ => Err(LookupError),
 		}
 	}
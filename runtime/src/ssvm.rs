@@ -0,0 +1,909 @@
+//! Ethereum flavoured handling of SSVM extrinsics on top of the FRAME pallets.
+
+use codec::{Encode, Decode};
+use frame_support::{
+	storage::{with_transaction, TransactionOutcome},
+	traits::{Currency, ExistenceRequirement, InstanceFilter, OnUnbalanced, WithdrawReason, WithdrawReasons},
+};
+use sp_core::{H160, U256};
+use sp_runtime::{
+	DispatchError, DispatchResult, RuntimeDebug,
+	traits::{DispatchInfoOf, Dispatchable, PostDispatchInfoOf, SignedExtension},
+	transaction_validity::{
+		TransactionValidity, TransactionValidityError, TransactionSource, TransactionPriority,
+		ValidTransaction, InvalidTransaction,
+	},
+};
+use pallet_block_gas::{Gas, GasLimitOf};
+use pallet_ssvm::ConvertAccountId;
+use sp_std::{collections::btree_map::BTreeMap, prelude::*};
+use crate::{
+	AccountId, Balance, Balances, BlockGas, BlockNumber, Call, ContractRegistry, ContractRevert, Event, Executive,
+	FeeDistribution, Multisig, Origin, Runtime, System, UncheckedExtrinsic, Utility,
+	apis::{CallResult, CreateResult},
+};
+
+/// SSVM address of `account`, which contracts see as the caller of the SSVM extrinsics
+/// dispatched from its origin.
+///
+/// Proxied extrinsics are dispatched from the origin of the proxied account and multisig
+/// ones from the origin of the multisig account, so their caller is the address of that
+/// account rather than the one of the signer. A contract administered by a 2-of-3 multisig
+/// is thus owned by `address_of(&multisig_account(&signatories, 2))`.
+pub fn address_of(account: &AccountId) -> H160 {
+	<Runtime as pallet_ssvm::Trait>::ConvertAccountId::convert_account_id(account)
+}
+
+/// Accounts control their own SSVM address and the contracts they deployed, so that only
+/// they can make those addresses resolve back to a name.
+pub struct Addresses;
+
+impl pallet_name_service::ControlsAddress<AccountId> for Addresses {
+	fn controls(who: &AccountId, address: &H160) -> bool {
+		address_of(who) == *address || ContractRegistry::deployer(*address).as_ref() == Some(who)
+	}
+}
+
+/// Account of the multisig of `signatories` with the given threshold, in any order.
+pub fn multisig_account(signatories: &[AccountId], threshold: u16) -> AccountId {
+	let mut signatories = signatories.to_vec();
+	signatories.sort();
+	Multisig::multi_account_id(&signatories, threshold)
+}
+
+/// The call dispatched by a multisig approval carrying the whole call, if it decodes.
+fn multisig_call(call: &[u8]) -> Option<Call> {
+	Decode::decode(&mut &call[..]).ok()
+}
+
+/// The kinds of proxies an account can delegate to.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Encode, Decode, RuntimeDebug)]
+pub enum ProxyType {
+	/// Dispatch any call.
+	Any,
+	/// Create and call SSVM contracts, possibly in batches, but nothing else: in particular
+	/// the balance of the proxied account can only be spent through contracts and gas.
+	SSVM,
+}
+
+impl Default for ProxyType {
+	fn default() -> Self {
+		ProxyType::Any
+	}
+}
+
+impl InstanceFilter<Call> for ProxyType {
+	fn filter(&self, call: &Call) -> bool {
+		match self {
+			ProxyType::Any => true,
+			// The calls of batches are dispatched from the filtered origin, so they are
+			// filtered as well.
+			ProxyType::SSVM => matches!(
+				call,
+				Call::SSVM(..) |
+				Call::Utility(pallet_utility::Call::batch(..)) |
+				Call::AtomicBatch(pallet_atomic_batch::Call::batch_all(..))
+			),
+		}
+	}
+
+	fn is_superset(&self, other: &Self) -> bool {
+		match (self, other) {
+			(ProxyType::Any, _) => true,
+			(_, ProxyType::Any) => false,
+			(ProxyType::SSVM, ProxyType::SSVM) => true,
+		}
+	}
+}
+
+/// Whether the approval of `call` by `who` in an `as_multi` of the multisig of `who` and
+/// `others` reaches the threshold, so that `call` is dispatched.
+fn executes_multisig(who: &AccountId, others: &[AccountId], threshold: u16, call: &[u8]) -> bool {
+	let multisig = signatories_account(who, others, threshold);
+	let approvals = multisig_approvals(&multisig, call);
+	approvals.iter().filter(|approval| *approval != who).count() + 1 >= threshold as usize
+}
+
+/// The accounts that approved the multisig operation of `multisig` dispatching `call`.
+fn multisig_approvals(multisig: &AccountId, call: &[u8]) -> Vec<AccountId> {
+	// The fields of `pallet_multisig::Multisig` are private, so it is decoded as a tuple.
+	type Operation = (pallet_multisig::Timepoint<BlockNumber>, Balance, AccountId, Vec<AccountId>);
+	pallet_multisig::Multisigs::<Runtime>::get(multisig, sp_io::hashing::blake2_256(call))
+		.and_then(|operation| Operation::decode(&mut &operation.encode()[..]).ok())
+		.map(|(_, _, _, approvals)| approvals)
+		.unwrap_or_default()
+}
+
+/// Every SSVM call dispatched by `call` from the origin of `who`, including those nested in
+/// batches, derivative, proxied and multisig dispatches, along with the account each is
+/// dispatched from.
+///
+/// That is `who` for batched calls, the derivative account for derivative ones, the proxied
+/// account for proxied ones and the multisig account for multisig ones. The call of an
+/// `as_multi` is only dispatched if the approval reaches the threshold.
+pub fn dispatched_ssvm_calls(who: &AccountId, call: &Call) -> Vec<(AccountId, pallet_ssvm::Call<Runtime>)> {
+	calls_dispatched_from(who, call, &executes_multisig)
+}
+
+/// Every SSVM call dispatched by `call` from the origin of `who`, along with the account each
+/// is dispatched from, like `dispatched_ssvm_calls` but whether or not a multisig call
+/// reaches its threshold. It does not read the state, so the node can use it as well.
+pub fn ssvm_calls_from(who: &AccountId, call: &Call) -> Vec<(AccountId, pallet_ssvm::Call<Runtime>)> {
+	calls_dispatched_from(who, call, &|_, _, _, _| true)
+}
+
+/// SSVM calls dispatched by `call` from the origin of `who`, where `executes` tells whether
+/// the call of an `as_multi` is dispatched.
+fn calls_dispatched_from(
+	who: &AccountId,
+	call: &Call,
+	executes: &dyn Fn(&AccountId, &[AccountId], u16, &[u8]) -> bool,
+) -> Vec<(AccountId, pallet_ssvm::Call<Runtime>)> {
+	match call {
+		Call::Utility(pallet_utility::Call::batch(calls)) |
+		Call::AtomicBatch(pallet_atomic_batch::Call::batch_all(calls)) =>
+			calls.iter().flat_map(|call| calls_dispatched_from(who, call, executes)).collect(),
+		Call::Utility(pallet_utility::Call::as_derivative(index, call)) =>
+			calls_dispatched_from(&Utility::derivative_account_id(who.clone(), *index), call, executes),
+		Call::Proxy(pallet_proxy::Call::proxy(real, _, call)) |
+		Call::Proxy(pallet_proxy::Call::proxy_announced(_, real, _, call)) =>
+			calls_dispatched_from(real, call, executes),
+		Call::Multisig(pallet_multisig::Call::as_multi_threshold_1(others, call)) =>
+			calls_dispatched_from(&signatories_account(who, others, 1), call, executes),
+		Call::Multisig(pallet_multisig::Call::as_multi(threshold, others, _, call, _, _)) => {
+			if !executes(who, others, *threshold, call) {
+				return Vec::new();
+			}
+			multisig_call(call)
+				.map(|call| calls_dispatched_from(&signatories_account(who, others, *threshold), &call, executes))
+				.unwrap_or_default()
+		}
+		Call::SSVM(call) => vec![(who.clone(), call.clone())],
+		_ => Vec::new(),
+	}
+}
+
+/// Every SSVM call dispatched by `call`, including those nested in batches, derivative,
+/// proxied and multisig dispatches, whether or not a multisig call reaches its threshold.
+pub fn ssvm_calls(call: &Call) -> Vec<pallet_ssvm::Call<Runtime>> {
+	match call {
+		Call::Utility(pallet_utility::Call::batch(calls)) |
+		Call::AtomicBatch(pallet_atomic_batch::Call::batch_all(calls)) =>
+			calls.iter().flat_map(ssvm_calls).collect(),
+		Call::Utility(pallet_utility::Call::as_derivative(_, call)) |
+		Call::Proxy(pallet_proxy::Call::proxy(_, _, call)) |
+		Call::Proxy(pallet_proxy::Call::proxy_announced(_, _, _, call)) |
+		Call::Multisig(pallet_multisig::Call::as_multi_threshold_1(_, call)) => ssvm_calls(call),
+		Call::Multisig(pallet_multisig::Call::as_multi(_, _, _, call, _, _)) =>
+			multisig_call(call).map(|call| ssvm_calls(&call)).unwrap_or_default(),
+		Call::SSVM(call) => vec![call.clone()],
+		_ => Vec::new(),
+	}
+}
+
+/// Gas parameters of an `ssvm.create` or `ssvm.call` extrinsic.
+#[derive(Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub struct GasParams {
+	/// Maximum amount of gas the execution may use.
+	pub gas_limit: u32,
+	/// Price paid for each unit of gas.
+	pub gas_price: U256,
+}
+
+impl GasParams {
+	/// Extract the gas parameters of `call`, if it is an SSVM extrinsic.
+	pub fn of(call: &Call) -> Option<Self> {
+		match call {
+			Call::SSVM(pallet_ssvm::Call::create(_, _, gas_limit, gas_price)) |
+			Call::SSVM(pallet_ssvm::Call::call(_, _, _, gas_limit, gas_price)) => Some(GasParams {
+				gas_limit: *gas_limit,
+				gas_price: *gas_price,
+			}),
+			_ => None,
+		}
+	}
+
+	/// Gas parameters of every SSVM extrinsic dispatched by `call` from the origin of `who`,
+	/// as found by `dispatched_ssvm_calls`, along with the account paying for its gas.
+	pub fn all(who: &AccountId, call: &Call) -> Vec<(AccountId, Self)> {
+		dispatched_ssvm_calls(who, call)
+			.into_iter()
+			.filter_map(|(payer, call)| Some((payer, Self::of(&Call::SSVM(call))?)))
+			.collect()
+	}
+
+	/// Amount each account pays for the whole gas limit of the SSVM extrinsics `call`
+	/// dispatches from its origin when dispatched from the origin of `who`, or `None` if
+	/// it does not fit in a `Balance`.
+	pub fn costs(who: &AccountId, call: &Call) -> Option<Vec<(AccountId, Balance)>> {
+		let mut costs: Vec<(AccountId, Balance)> = Vec::new();
+		for (payer, gas) in Self::all(who, call) {
+			let cost = gas.cost()?;
+			match costs.iter_mut().find(|(account, _)| *account == payer) {
+				Some((_, total)) => *total = total.checked_add(cost)?,
+				None => costs.push((payer, cost)),
+			}
+		}
+		Some(costs)
+	}
+
+	/// Transaction pool priority the extrinsic gets on top of the one of its fee, which is its
+	/// gas price like on Ethereum.
+	pub fn priority(&self) -> TransactionPriority {
+		if self.gas_price > U256::from(TransactionPriority::max_value()) {
+			TransactionPriority::max_value()
+		} else {
+			self.gas_price.low_u64()
+		}
+	}
+
+	/// Amount paid for the whole gas limit, or `None` if it does not fit in a `Balance`.
+	pub fn cost(&self) -> Option<Balance> {
+		let cost = self.gas_price.checked_mul(U256::from(self.gas_limit))?;
+		if cost > U256::from(Balance::max_value()) {
+			None
+		} else {
+			Some(cost.low_u128())
+		}
+	}
+}
+
+/// The gas limit of SSVM extrinsics counts against the block gas limit, batched or not.
+impl GasLimitOf<AccountId, Call> for GasParams {
+	fn gas_limit(who: &AccountId, call: &Call) -> Option<Gas> {
+		let all = Self::all(who, call);
+		if all.is_empty() {
+			None
+		} else {
+			Some(all.iter().map(|(_, gas)| Gas::from(gas.gas_limit)).sum())
+		}
+	}
+}
+
+/// Accounts can schedule `ssvm.call` extrinsics through `ContractScheduler`, reserving the
+/// whole gas limit of each execution. Executions count against the block gas limit like
+/// `ssvm.call` extrinsics.
+pub struct ScheduledGas;
+
+impl pallet_contract_scheduler::GasCostOf<Call, Balance> for ScheduledGas {
+	fn gas_cost(call: &Call) -> Option<Balance> {
+		match call {
+			Call::SSVM(pallet_ssvm::Call::call(..)) => GasParams::of(call)?.cost(),
+			_ => None,
+		}
+	}
+
+	fn reserve_block_gas(call: &Call) -> DispatchResult {
+		let gas = GasParams::of(call).map_or(0, |gas| Gas::from(gas.gas_limit));
+		BlockGas::reserve(gas).map_err(|_| DispatchError::Other("block gas limit reached"))
+	}
+}
+
+/// Validate `tx` for the transaction pool.
+///
+/// The gas price of SSVM extrinsics is added to the priority they get from their fee, so
+/// that they are ordered by gas price among themselves and tips still count. Since
+/// `CheckNonce` makes a transaction provide its `(sender, nonce)` tag, a pending SSVM
+/// extrinsic is replaced in the pool by one with the same sender and nonce and a higher gas
+/// price.
+pub fn validate_transaction(
+	source: TransactionSource,
+	tx: UncheckedExtrinsic,
+) -> TransactionValidity {
+	let gas = GasParams::of(&tx.function);
+	let validity = Executive::validate_transaction(source, tx)?;
+
+	Ok(match gas {
+		Some(gas) => ValidTransaction {
+			priority: validity.priority.saturating_add(gas.priority()),
+			..validity
+		},
+		None => validity,
+	})
+}
+
+/// Hand the gas of SSVM extrinsics to `FeeDistribution`.
+///
+/// `pallet_ssvm` charges the gas of an execution to the account dispatching it: it withdraws
+/// the whole gas limit at the gas price upfront, refunds the gas left after the execution and
+/// burns the rest. What the accounts paying for the gas of the extrinsic lost over its
+/// dispatch, SSVM extrinsics nested in batches, proxies or multisigs included, is taken as
+/// the burnt gas, up to the cost of the gas limit each of them pays for and up to the drop
+/// of the total issuance. Other burns and slashes during the dispatch are thus not taken for
+/// gas. The burnt gas is minted back to `FeeDistribution`, so that it is split between the
+/// block author and the treasury just like transaction fees. For the extrinsic to be valid, each
+/// account SSVM extrinsics are dispatched from must be able to pay for their whole gas
+/// limit.
+///
+/// The extension carries no data, so it does not change the encoding of extrinsics.
+#[derive(Encode, Decode, Clone, Eq, PartialEq, Default, RuntimeDebug)]
+pub struct ChargeGas;
+
+impl SignedExtension for ChargeGas {
+	const IDENTIFIER: &'static str = "ChargeGas";
+	type AccountId = AccountId;
+	type Call = Call;
+	type AdditionalSigned = ();
+	type Pre = Option<GasPayers>;
+
+	fn additional_signed(&self) -> Result<(), TransactionValidityError> {
+		Ok(())
+	}
+
+	fn validate(
+		&self,
+		who: &AccountId,
+		call: &Call,
+		_info: &DispatchInfoOf<Call>,
+		_len: usize,
+	) -> TransactionValidity {
+		let costs = GasParams::costs(who, call).ok_or(InvalidTransaction::Payment)?;
+		if costs.iter().any(|(payer, cost)| Balances::free_balance(payer) < *cost) {
+			return Err(InvalidTransaction::Payment.into());
+		}
+		Ok(ValidTransaction::default())
+	}
+
+	fn pre_dispatch(
+		self,
+		who: &AccountId,
+		call: &Call,
+		info: &DispatchInfoOf<Call>,
+		len: usize,
+	) -> Result<Self::Pre, TransactionValidityError> {
+		self.validate(who, call, info, len)?;
+		Ok(<Self as pallet_contract_scheduler::OnDispatch<AccountId, Call>>::pre_dispatch(who, call))
+	}
+
+	fn post_dispatch(
+		pre: Self::Pre,
+		_info: &DispatchInfoOf<Call>,
+		_post_info: &PostDispatchInfoOf<Call>,
+		_len: usize,
+		result: &DispatchResult,
+	) -> Result<(), TransactionValidityError> {
+		<Self as pallet_contract_scheduler::OnDispatch<AccountId, Call>>::post_dispatch(pre, result);
+		Ok(())
+	}
+}
+
+/// Scheduled calls are dispatched from the origin of their owner, who gets the reserve of
+/// the execution back right before and pays for the gas to `pallet_ssvm`.
+impl pallet_contract_scheduler::OnDispatch<AccountId, Call> for ChargeGas {
+	type Pre = Option<GasPayers>;
+
+	fn pre_dispatch(who: &AccountId, call: &Call) -> Self::Pre {
+		let mut costs = BTreeMap::<AccountId, Balance>::new();
+		for (payer, cost) in GasParams::costs(who, call)? {
+			let total = costs.entry(payer).or_default();
+			*total = total.saturating_add(cost);
+		}
+		if costs.is_empty() {
+			return None;
+		}
+		Some(GasPayers {
+			issuance: Balances::total_issuance(),
+			payers: costs.into_iter()
+				.map(|(payer, cost)| {
+					let balance = Balances::total_balance(&payer);
+					(payer, balance, cost)
+				})
+				.collect(),
+		})
+	}
+
+	fn post_dispatch(pre: Self::Pre, _result: &DispatchResult) {
+		if let Some(GasPayers { issuance, payers }) = pre {
+			let paid = payers.into_iter()
+				.map(|(payer, balance, cost)| balance.saturating_sub(Balances::total_balance(&payer)).min(cost))
+				.fold(0, Balance::saturating_add);
+			let burnt = issuance.saturating_sub(Balances::total_issuance()).min(paid);
+			if burnt > 0 {
+				FeeDistribution::on_unbalanced(Balances::issue(burnt));
+			}
+		}
+	}
+}
+
+/// What the accounts paying for the gas of a dispatch had before it.
+pub struct GasPayers {
+	/// Total issuance.
+	issuance: Balance,
+	/// Each payer, with its total balance and the cost of the gas limits it pays for.
+	payers: Vec<(AccountId, Balance, Balance)>,
+}
+
+/// Withdraw `cost` from `who` for gas that `pallet_ssvm` does not charge, and hand it to
+/// `FeeDistribution`.
+fn pay_gas(who: &AccountId, cost: Balance) -> DispatchResult {
+	if cost > 0 {
+		let payment = <Balances as Currency<AccountId>>::withdraw(
+			who,
+			cost,
+			WithdrawReasons::from(WithdrawReason::Fee),
+			ExistenceRequirement::KeepAlive,
+		)?;
+		FeeDistribution::on_unbalanced(payment);
+	}
+	Ok(())
+}
+
+/// The SSVM events deposited after the first `since` events of the block.
+fn ssvm_events_since(since: u32) -> impl Iterator<Item = pallet_ssvm::Event> {
+	System::events()
+		.into_iter()
+		.skip(since as usize)
+		.filter_map(|record| match record.event {
+			Event::pallet_ssvm(event) => Some(event),
+			_ => None,
+		})
+}
+
+/// Output of the last SSVM execution among the events deposited after the first `since`.
+fn output_since(since: u32) -> Option<Vec<u8>> {
+	ssvm_events_since(since)
+		.filter_map(|event| match event {
+			pallet_ssvm::Event::Output(output) => Some(output),
+			_ => None,
+		})
+		.last()
+}
+
+/// What became of an SSVM call nested in a call.
+#[derive(Clone, PartialEq, Eq, RuntimeDebug)]
+pub enum SsvmOutcome {
+	/// The call was not dispatched: it follows the interrupted call of a batch, or it is the
+	/// call of a multisig approval that does not reach the threshold.
+	NotDispatched,
+	/// The call was dispatched and succeeded.
+	Succeeded,
+	/// The call failed or was rolled back, with the output the SSVM reported for it, if any.
+	Failed(Vec<u8>),
+}
+
+/// An SSVM call dispatched by a call: its index among the SSVM calls walked, the called
+/// contract if it is an `ssvm.call`, and the index of the first event its execution may have
+/// deposited.
+type Dispatched = (usize, Option<H160>, usize);
+
+/// Finds what became of the SSVM calls dispatched by a call, and the `ssvm.call`s that
+/// failed among them, by walking the calls it dispatches in order along with the events they
+/// deposited.
+///
+/// Batches, proxies and multisigs do not fail when one of their calls fails, but deposit an
+/// event telling so right after it: a failed SSVM call is the last one dispatched before
+/// such an event, or before the end of the events if the whole call failed. The calls of a
+/// failed atomic batch are all reported, since their changes and events were rolled back.
+struct Reverts<'a> {
+	/// Events deposited while dispatching the call.
+	events: &'a [Event],
+	/// Index of the first event not walked yet.
+	next: usize,
+	/// What became of the SSVM calls walked, in the order of `ssvm_calls`.
+	outcomes: Vec<SsvmOutcome>,
+	/// The failed `ssvm.call`s and their output.
+	reverted: Vec<(H160, Vec<u8>)>,
+}
+
+impl<'a> Reverts<'a> {
+	/// Walk the calls dispatched by `call`, given the events deposited while dispatching it
+	/// and whether it failed.
+	fn of(call: &Call, events: &'a [Event], failed: bool) -> Self {
+		let mut reverts = Reverts { events, next: 0, outcomes: Vec::new(), reverted: Vec::new() };
+		let dispatched = reverts.walk(call);
+		if failed {
+			reverts.fail(dispatched, events.len());
+		}
+		reverts
+	}
+
+	/// Walk the calls dispatched by `call`, returning the SSVM calls that failed if `call`
+	/// itself failed.
+	fn walk(&mut self, call: &Call) -> Vec<Dispatched> {
+		match call {
+			Call::SSVM(call) => {
+				let target = match call {
+					pallet_ssvm::Call::call(target, ..) => Some(*target),
+					_ => None,
+				};
+				self.outcomes.push(SsvmOutcome::Succeeded);
+				vec![(self.outcomes.len() - 1, target, self.next)]
+			}
+			Call::Utility(pallet_utility::Call::as_derivative(_, call)) |
+			Call::Multisig(pallet_multisig::Call::as_multi_threshold_1(_, call)) => self.walk(call),
+			Call::Utility(pallet_utility::Call::batch(calls)) => {
+				for (index, call) in calls.iter().enumerate() {
+					let dispatched = self.walk(call);
+					if let Some((at, Event::pallet_utility(pallet_utility::Event::BatchInterrupted(interrupted, _)))) =
+						self.peek()
+					{
+						if *interrupted as usize == index {
+							self.fail(dispatched, at);
+							for call in &calls[index + 1..] {
+								let skipped = ssvm_calls(call).len();
+								self.outcomes.extend(sp_std::iter::repeat(SsvmOutcome::NotDispatched).take(skipped));
+							}
+							return Vec::new();
+						}
+					}
+				}
+				if let Some((at, Event::pallet_utility(pallet_utility::Event::BatchCompleted))) = self.peek() {
+					self.next = at + 1;
+				}
+				Vec::new()
+			}
+			Call::AtomicBatch(pallet_atomic_batch::Call::batch_all(calls)) => {
+				let start = self.next;
+				let dispatched = calls.iter().flat_map(|call| self.walk(call)).collect::<Vec<_>>();
+				match self.peek() {
+					Some((at, Event::pallet_atomic_batch(pallet_atomic_batch::Event::BatchAllCompleted(_)))) => {
+						self.next = at + 1;
+						Vec::new()
+					}
+					_ => {
+						self.next = start;
+						dispatched.into_iter().map(|(index, target, _)| (index, target, start)).collect()
+					}
+				}
+			}
+			Call::Proxy(pallet_proxy::Call::proxy(_, _, call)) |
+			Call::Proxy(pallet_proxy::Call::proxy_announced(_, _, _, call)) => {
+				let dispatched = self.walk(call);
+				if let Some((at, Event::pallet_proxy(pallet_proxy::RawEvent::ProxyExecuted(result)))) = self.peek() {
+					self.next = at + 1;
+					if result.is_err() {
+						self.fail(dispatched, at);
+					}
+				}
+				Vec::new()
+			}
+			Call::Multisig(pallet_multisig::Call::as_multi(_, _, _, call, _, _)) => {
+				let first = self.outcomes.len();
+				let dispatched = multisig_call(call).map(|call| self.walk(&call)).unwrap_or_default();
+				match self.peek() {
+					Some((at, Event::pallet_multisig(pallet_multisig::RawEvent::MultisigExecuted(.., result)))) => {
+						self.next = at + 1;
+						if result.is_err() {
+							self.fail(dispatched, at);
+						}
+					}
+					Some((at, Event::pallet_multisig(pallet_multisig::RawEvent::NewMultisig(..)))) |
+					Some((at, Event::pallet_multisig(pallet_multisig::RawEvent::MultisigApproval(..)))) => {
+						self.next = at + 1;
+						for outcome in &mut self.outcomes[first..] {
+							*outcome = SsvmOutcome::NotDispatched;
+						}
+					}
+					_ => {}
+				}
+				Vec::new()
+			}
+			_ => Vec::new(),
+		}
+	}
+
+	/// The next event deposited by a batch, a proxy or a multisig, and its index.
+	fn peek(&self) -> Option<(usize, &'a Event)> {
+		let events = self.events;
+		events.iter().enumerate().skip(self.next).find(|(_, event)| matches!(event,
+			Event::pallet_utility(_) |
+			Event::pallet_atomic_batch(_) |
+			Event::pallet_proxy(pallet_proxy::RawEvent::ProxyExecuted(..)) |
+			Event::pallet_multisig(pallet_multisig::RawEvent::MultisigExecuted(..)) |
+			Event::pallet_multisig(pallet_multisig::RawEvent::NewMultisig(..)) |
+			Event::pallet_multisig(pallet_multisig::RawEvent::MultisigApproval(..))
+		))
+	}
+
+	/// Record the failure of `dispatched`, which was reported by the event at `until`.
+	fn fail(&mut self, dispatched: Vec<Dispatched>, until: usize) {
+		self.next = self.next.max(until + 1);
+		for (index, target, since) in dispatched {
+			let output = self.events[since.min(until)..until]
+				.iter()
+				.filter_map(|event| match event {
+					Event::pallet_ssvm(pallet_ssvm::Event::Output(output)) => Some(output.clone()),
+					_ => None,
+				})
+				.last()
+				.unwrap_or_default();
+			if let Some(target) = target {
+				self.reverted.push((target, output.clone()));
+			}
+			self.outcomes[index] = SsvmOutcome::Failed(output);
+		}
+	}
+}
+
+/// What became of each SSVM call dispatched by `call`, in the order of `ssvm_calls(call)`,
+/// given the events deposited while dispatching it and whether it failed.
+///
+/// The calls of a failed call are all failed, since nothing they did was kept. It does not
+/// read the state, so the node can use it as well.
+pub fn ssvm_outcomes(call: &Call, events: &[Event], failed: bool) -> Vec<SsvmOutcome> {
+	let mut outcomes = Reverts::of(call, events, failed).outcomes;
+	if failed {
+		for outcome in &mut outcomes {
+			if *outcome == SsvmOutcome::Succeeded {
+				*outcome = SsvmOutcome::Failed(Vec::new());
+			}
+		}
+	}
+	outcomes
+}
+
+/// Deposit a `ContractReverted` event for each `ssvm.call` dispatched by `call` that failed,
+/// `call` being dispatched after the first `since` events of the block with the given
+/// result.
+///
+/// The revert data is the output the SSVM reported for the failed call, if any.
+pub fn report_reverts(call: &Call, since: u32, result: &DispatchResult) {
+	let events = System::events()
+		.into_iter()
+		.skip(since as usize)
+		.map(|record| record.event)
+		.collect::<Vec<_>>();
+	for (target, output) in Reverts::of(call, &events, result.is_err()).reverted {
+		ContractRevert::note_revert(target, &output);
+	}
+}
+
+/// Whether `call` dispatches an `ssvm.call`, whose failure is reported.
+fn calls_contracts(call: &Call) -> bool {
+	ssvm_calls(call).iter().any(|call| matches!(call, pallet_ssvm::Call::call(..)))
+}
+
+/// Deposit a `ContractReverted` event when an `ssvm.call` fails, whether it is the call of
+/// the extrinsic or is dispatched by a batch, a proxy or a multisig.
+#[derive(Encode, Decode, Clone, Eq, PartialEq, Default, RuntimeDebug)]
+pub struct ReportRevert;
+
+impl SignedExtension for ReportRevert {
+	const IDENTIFIER: &'static str = "ReportRevert";
+	type AccountId = AccountId;
+	type Call = Call;
+	type AdditionalSigned = ();
+	/// The call and the number of events deposited before it.
+	type Pre = Option<(Call, u32)>;
+
+	fn additional_signed(&self) -> Result<(), TransactionValidityError> {
+		Ok(())
+	}
+
+	fn pre_dispatch(
+		self,
+		who: &AccountId,
+		call: &Call,
+		_info: &DispatchInfoOf<Call>,
+		_len: usize,
+	) -> Result<Self::Pre, TransactionValidityError> {
+		Ok(<Self as pallet_contract_scheduler::OnDispatch<AccountId, Call>>::pre_dispatch(who, call))
+	}
+
+	fn post_dispatch(
+		pre: Self::Pre,
+		_info: &DispatchInfoOf<Call>,
+		_post_info: &PostDispatchInfoOf<Call>,
+		_len: usize,
+		result: &DispatchResult,
+	) -> Result<(), TransactionValidityError> {
+		<Self as pallet_contract_scheduler::OnDispatch<AccountId, Call>>::post_dispatch(pre, result);
+		Ok(())
+	}
+}
+
+/// Scheduled calls report their reverts like extrinsics.
+impl pallet_contract_scheduler::OnDispatch<AccountId, Call> for ReportRevert {
+	type Pre = Option<(Call, u32)>;
+
+	fn pre_dispatch(_who: &AccountId, call: &Call) -> Self::Pre {
+		if calls_contracts(call) {
+			Some((call.clone(), System::event_count()))
+		} else {
+			None
+		}
+	}
+
+	fn post_dispatch(pre: Self::Pre, result: &DispatchResult) {
+		if let Some((call, since)) = pre {
+			report_reverts(&call, since, result);
+		}
+	}
+}
+
+/// The account `who` dispatches the first `ssvm.create` of `call` from, if any.
+///
+/// Creations are looked for in batches, derivative calls, proxied calls and multisig calls,
+/// whose origin is the one of the derivative account, of the proxied account and of the
+/// multisig account respectively.
+fn creator_of(who: &AccountId, call: &Call) -> Option<AccountId> {
+	match call {
+		Call::SSVM(pallet_ssvm::Call::create(..)) => Some(who.clone()),
+		Call::Utility(pallet_utility::Call::batch(calls)) |
+		Call::AtomicBatch(pallet_atomic_batch::Call::batch_all(calls)) =>
+			calls.iter().find_map(|call| creator_of(who, call)),
+		Call::Utility(pallet_utility::Call::as_derivative(index, call)) =>
+			creator_of(&Utility::derivative_account_id(who.clone(), *index), call),
+		Call::Proxy(pallet_proxy::Call::proxy(real, _, call)) |
+		Call::Proxy(pallet_proxy::Call::proxy_announced(_, real, _, call)) => creator_of(real, call),
+		Call::Multisig(pallet_multisig::Call::as_multi_threshold_1(others, call)) =>
+			creator_of(&signatories_account(who, others, 1), call),
+		Call::Multisig(pallet_multisig::Call::as_multi(threshold, others, _, call, _, _)) =>
+			multisig_call(call)
+				.and_then(|call| creator_of(&signatories_account(who, others, *threshold), &call)),
+		_ => None,
+	}
+}
+
+/// Account of the multisig of `who` and `others` with the given threshold.
+fn signatories_account(who: &AccountId, others: &[AccountId], threshold: u16) -> AccountId {
+	let mut signatories = others.to_vec();
+	signatories.push(who.clone());
+	multisig_account(&signatories, threshold)
+}
+
+/// Record the account `ssvm.create` extrinsics and scheduled calls are dispatched from as the
+/// deployer of the contracts they create, which lets it register the metadata of those
+/// contracts in `ContractRegistry`. That is the signer for direct and batched creations, the
+/// proxied account for proxied ones, the multisig account for multisig ones and the owner
+/// for scheduled ones. Contracts created by other contracts are not recorded.
+#[derive(Encode, Decode, Clone, Eq, PartialEq, Default, RuntimeDebug)]
+pub struct NoteDeployer;
+
+impl SignedExtension for NoteDeployer {
+	const IDENTIFIER: &'static str = "NoteDeployer";
+	type AccountId = AccountId;
+	type Call = Call;
+	type AdditionalSigned = ();
+	/// The deployer and the number of events deposited before the creation.
+	type Pre = Option<(AccountId, u32)>;
+
+	fn additional_signed(&self) -> Result<(), TransactionValidityError> {
+		Ok(())
+	}
+
+	fn pre_dispatch(
+		self,
+		who: &AccountId,
+		call: &Call,
+		_info: &DispatchInfoOf<Call>,
+		_len: usize,
+	) -> Result<Self::Pre, TransactionValidityError> {
+		Ok(<Self as pallet_contract_scheduler::OnDispatch<AccountId, Call>>::pre_dispatch(who, call))
+	}
+
+	fn post_dispatch(
+		pre: Self::Pre,
+		_info: &DispatchInfoOf<Call>,
+		_post_info: &PostDispatchInfoOf<Call>,
+		_len: usize,
+		result: &DispatchResult,
+	) -> Result<(), TransactionValidityError> {
+		<Self as pallet_contract_scheduler::OnDispatch<AccountId, Call>>::post_dispatch(pre, result);
+		Ok(())
+	}
+}
+
+/// Scheduled calls are dispatched from the origin of their owner, who deploys the contracts
+/// they create.
+impl pallet_contract_scheduler::OnDispatch<AccountId, Call> for NoteDeployer {
+	/// The deployer and the number of events deposited before the creation.
+	type Pre = Option<(AccountId, u32)>;
+
+	fn pre_dispatch(who: &AccountId, call: &Call) -> Self::Pre {
+		creator_of(who, call).map(|deployer| (deployer, System::event_count()))
+	}
+
+	fn post_dispatch(pre: Self::Pre, result: &DispatchResult) {
+		// Contracts created by a failed atomic batch were rolled back, along with their events.
+		if let (Some((deployer, since)), Ok(())) = (pre, result) {
+			for event in ssvm_events_since(since) {
+				if let pallet_ssvm::Event::Create(address) = event {
+					ContractRegistry::note_deployed(address, deployer.clone());
+				}
+			}
+		}
+	}
+}
+
+/// Upkeep of the contracts registered in `ContractRegistry` by `ContractKeeper`.
+///
+/// The transactions performing upkeeps are not SSVM extrinsics themselves, so the predicate
+/// and the upkeep function count against the block gas limit here. The predicate runs with
+/// no gas price and its changes are discarded, so its owner pays for its whole gas limit;
+/// the upkeep function pays for its gas to `pallet_ssvm` like an `ssvm.call` extrinsic.
+pub struct Upkeep;
+
+impl pallet_contract_keeper::Contracts<AccountId> for Upkeep {
+	fn owner(address: H160) -> Option<AccountId> {
+		ContractRegistry::metadata(address).map(|metadata| metadata.owner)
+	}
+
+	fn needs_upkeep(from: &AccountId, address: H160, input: Vec<u8>, gas_limit: u32) -> bool {
+		let result = with_transaction(|| TransactionOutcome::Rollback(
+			dry_run(from.clone(), address, input, U256::zero(), gas_limit, U256::zero())
+		));
+		// An ABI encoded `true`.
+		let output = result.output;
+		result.error.is_none()
+			&& output.len() == 32
+			&& output[..31].iter().all(|byte| *byte == 0)
+			&& output[31] == 1
+	}
+
+	fn check_upkeep(
+		from: &AccountId,
+		address: H160,
+		input: Vec<u8>,
+		gas_limit: u32,
+		gas_price: U256,
+	) -> Result<bool, DispatchError> {
+		let cost = GasParams { gas_limit, gas_price }.cost().ok_or(DispatchError::Other("gas cost overflow"))?;
+		BlockGas::reserve(Gas::from(gas_limit))
+			.map_err(|_| DispatchError::Other("block gas limit reached"))?;
+		pay_gas(from, cost)?;
+		Ok(Self::needs_upkeep(from, address, input, gas_limit))
+	}
+
+	fn perform_upkeep(
+		from: &AccountId,
+		address: H160,
+		input: Vec<u8>,
+		gas_limit: u32,
+		gas_price: U256,
+	) -> DispatchResult {
+		// Charged and reported like an `ssvm.call` extrinsic.
+		type Observers = (ChargeGas, ReportRevert);
+
+		BlockGas::reserve(Gas::from(gas_limit))
+			.map_err(|_| DispatchError::Other("block gas limit reached"))?;
+
+		let call = Call::SSVM(pallet_ssvm::Call::call(address, input, U256::zero(), gas_limit, gas_price));
+		let pre = <Observers as pallet_contract_scheduler::OnDispatch<AccountId, Call>>::pre_dispatch(from, &call);
+		let result = call.dispatch(Origin::signed(from.clone())).map(|_| ()).map_err(|e| e.error);
+		<Observers as pallet_contract_scheduler::OnDispatch<AccountId, Call>>::post_dispatch(pre, &result);
+		result
+	}
+}
+
+/// Execute an `ssvm.call` as `from` and report its outcome. Meant to be used from runtime
+/// APIs, whose changes to the state are discarded.
+pub fn dry_run(
+	from: AccountId,
+	target: H160,
+	input: Vec<u8>,
+	value: U256,
+	gas_limit: u32,
+	gas_price: U256,
+) -> CallResult {
+	let since = System::event_count();
+	let call = Call::SSVM(pallet_ssvm::Call::call(target, input, value, gas_limit, gas_price));
+	let error = call.dispatch(Origin::signed(from)).err().map(|e| e.error);
+	let output = output_since(since).unwrap_or_default();
+	let revert_reason = error.as_ref()
+		.and_then(|_| pallet_contract_revert::decode_revert_reason(&output));
+
+	CallResult { output, error, revert_reason }
+}
+
+/// Execute an `ssvm.create` as `from` and report the address of the new contract. Meant
+/// to be used from runtime APIs, whose changes to the state are discarded.
+pub fn dry_run_create(
+	from: AccountId,
+	code: Vec<u8>,
+	value: U256,
+	gas_limit: u32,
+	gas_price: U256,
+) -> CreateResult {
+	let since = System::event_count();
+	let call = Call::SSVM(pallet_ssvm::Call::create(code, value, gas_limit, gas_price));
+	let error = call.dispatch(Origin::signed(from)).err().map(|e| e.error);
+	let address = ssvm_events_since(since)
+		.filter_map(|event| match event {
+			pallet_ssvm::Event::Create(address) => Some(address),
+			_ => None,
+		})
+		.last();
+
+	CreateResult { address, output: output_since(since).unwrap_or_default(), error }
+}
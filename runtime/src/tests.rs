@@ -0,0 +1,1124 @@
+//! Tests exercising the configured runtime as a whole.
+
+use super::*;
+use codec::Encode;
+use frame_support::{
+	assert_ok,
+	traits::{
+		Currency, ExistenceRequirement, KeyOwnerProofSystem, OnFinalize, OnRuntimeUpgrade,
+		WithdrawReason, WithdrawReasons,
+	},
+	weights::GetDispatchInfo,
+};
+use sp_consensus_aura::AURA_ENGINE_ID;
+use sp_runtime::{DispatchError, traits::{Dispatchable, SignedExtension}};
+use sp_core::{H160, H256, U256};
+use sp_keyring::{Ed25519Keyring, Sr25519Keyring};
+use sp_runtime::transaction_validity::{InvalidTransaction, TransactionSource};
+
+/// Build the genesis storage of a chain validated by Alice and Bob.
+fn new_test_ext() -> sp_io::TestExternalities {
+	let validators = vec![Sr25519Keyring::Alice, Sr25519Keyring::Bob];
+	let grandpa_keys = vec![Ed25519Keyring::Alice, Ed25519Keyring::Bob];
+
+	let storage = GenesisConfig {
+		frame_system: Some(SystemConfig {
+			code: vec![],
+			changes_trie_config: Default::default(),
+		}),
+		pallet_balances: Some(BalancesConfig {
+			balances: validators.iter().map(|k| (k.to_account_id(), 1 << 60)).collect(),
+		}),
+		pallet_validator_set: Some(ValidatorSetConfig {
+			validators: validators.iter().map(|k| k.to_account_id()).collect(),
+		}),
+		pallet_session: Some(SessionConfig {
+			keys: validators.iter().zip(grandpa_keys.iter()).map(|(aura, grandpa)| (
+				aura.to_account_id(),
+				aura.to_account_id(),
+				opaque::SessionKeys {
+					aura: aura.public().into(),
+					grandpa: grandpa.public().into(),
+				},
+			)).collect(),
+		}),
+		pallet_aura: Some(AuraConfig {
+			authorities: vec![],
+		}),
+		pallet_grandpa: Some(GrandpaConfig {
+			authorities: vec![],
+		}),
+		pallet_fee_distribution: Some(FeeDistributionConfig {
+			author_share: Perbill::from_percent(20),
+		}),
+		pallet_block_gas: Some(BlockGasConfig {
+			block_gas_limit: 250_000,
+		}),
+		pallet_sudo: Some(SudoConfig {
+			key: Sr25519Keyring::Alice.to_account_id(),
+		}),
+		pallet_ssvm: Some(SSVMConfig {
+			accounts: vec![],
+		}),
+	}.build_storage().unwrap();
+
+	let mut ext = sp_io::TestExternalities::new(storage);
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
+
+/// Build an extrinsic signed by `signer` with the runtime's `SignedExtra`.
+fn sign(call: Call, signer: Sr25519Keyring, nonce: Index) -> UncheckedExtrinsic {
+	let extra: SignedExtra = (
+		frame_system::CheckSpecVersion::new(),
+		frame_system::CheckTxVersion::new(),
+		frame_system::CheckGenesis::new(),
+		frame_system::CheckEra::from(generic::Era::Immortal),
+		frame_system::CheckNonce::from(nonce),
+		frame_system::CheckWeight::new(),
+		pallet_transaction_payment::ChargeTransactionPayment::from(0),
+		pallet_block_gas::CheckBlockGas::new(),
+		ssvm::ChargeGas,
+		ssvm::ReportRevert,
+		ssvm::NoteDeployer,
+	);
+	let payload = SignedPayload::new(call, extra).unwrap();
+	let signature = payload.using_encoded(|payload| signer.sign(payload));
+	let (call, extra, _) = payload.deconstruct();
+	UncheckedExtrinsic::new_signed(call, signer.to_account_id().into(), signature.into(), extra)
+}
+
+fn ssvm_call(gas_price: u64) -> Call {
+	Call::SSVM(pallet_ssvm::Call::call(H160::repeat_byte(1), vec![], 0.into(), 100_000, gas_price.into()))
+}
+
+/// Sign two different prevotes for the same round with `keyring`.
+fn generate_equivocation_proof(
+	set_id: fg_primitives::SetId,
+	round: fg_primitives::RoundNumber,
+	keyring: Ed25519Keyring,
+) -> fg_primitives::EquivocationProof<Hash, BlockNumber> {
+	let signed_prevote = |target_hash: Hash| {
+		let prevote = finality_grandpa::Prevote { target_hash, target_number: 1 };
+		let message = finality_grandpa::Message::Prevote(prevote.clone());
+		let payload = fg_primitives::localized_payload(round, set_id, &message);
+		(prevote, keyring.sign(&payload).into())
+	};
+
+	fg_primitives::EquivocationProof::new(
+		set_id,
+		fg_primitives::Equivocation::Prevote(finality_grandpa::Equivocation {
+			round_number: round,
+			identity: keyring.public().into(),
+			first: signed_prevote(Hash::repeat_byte(1)),
+			second: signed_prevote(Hash::repeat_byte(2)),
+		}),
+	)
+}
+
+#[test]
+fn session_drives_consensus_authorities() {
+	new_test_ext().execute_with(|| {
+		let expected: Vec<AuraId> = vec![
+			Sr25519Keyring::Alice.public().into(),
+			Sr25519Keyring::Bob.public().into(),
+		];
+		assert_eq!(Aura::authorities(), expected);
+		assert_eq!(Grandpa::grandpa_authorities().len(), 2);
+	});
+}
+
+#[test]
+fn grandpa_equivocation_removes_offender() {
+	new_test_ext().execute_with(|| {
+		let offender = Ed25519Keyring::Alice;
+		let set_id = Grandpa::current_set_id();
+
+		let equivocation_proof = generate_equivocation_proof(set_id, 1, offender);
+		let key_owner_proof =
+			Historical::prove((fg_primitives::KEY_TYPE, GrandpaId::from(offender.public()))).unwrap();
+
+		assert_ok!(Grandpa::report_equivocation_unsigned(
+			Origin::none(),
+			equivocation_proof,
+			key_owner_proof,
+		));
+
+		// The offence is recorded and Alice is dropped from the next validator set.
+		let alice = Sr25519Keyring::Alice.to_account_id();
+		assert!(System::events().iter().any(|record| record.event ==
+			Event::pallet_validator_set(pallet_validator_set::RawEvent::OffenderRemoved(alice.clone()))
+		));
+		assert_eq!(ValidatorSet::validators(), vec![Sr25519Keyring::Bob.to_account_id()]);
+	});
+}
+
+#[test]
+fn equivocation_by_non_validator_is_rejected() {
+	new_test_ext().execute_with(|| {
+		let outsider = GrandpaId::from(Ed25519Keyring::Charlie.public());
+		assert!(Historical::prove((fg_primitives::KEY_TYPE, outsider)).is_none());
+
+		// A key owner proof for another validator does not match the equivocating key.
+		let set_id = Grandpa::current_set_id();
+		let equivocation_proof = generate_equivocation_proof(set_id, 1, Ed25519Keyring::Charlie);
+		let key_owner_proof =
+			Historical::prove((fg_primitives::KEY_TYPE, GrandpaId::from(Ed25519Keyring::Alice.public()))).unwrap();
+
+		assert!(Grandpa::report_equivocation_unsigned(
+			Origin::none(),
+			equivocation_proof,
+			key_owner_proof,
+		).is_err());
+		assert_eq!(ValidatorSet::validators().len(), 2);
+	});
+}
+
+#[test]
+fn ssvm_transactions_are_prioritized_by_gas_price() {
+	new_test_ext().execute_with(|| {
+		let cheap = sign(ssvm_call(1), Sr25519Keyring::Alice, 0);
+		let fee_priority = Executive::validate_transaction(TransactionSource::External, cheap.clone())
+			.unwrap()
+			.priority;
+		let cheap = ssvm::validate_transaction(TransactionSource::External, cheap).unwrap();
+		let expensive = ssvm::validate_transaction(
+			TransactionSource::External,
+			sign(ssvm_call(20), Sr25519Keyring::Alice, 0),
+		).unwrap();
+
+		// The gas price comes on top of the priority of the fee.
+		assert!(fee_priority > 0);
+		assert_eq!(cheap.priority, fee_priority + 1);
+		assert_eq!(expensive.priority, fee_priority + 20);
+		// Both provide the same (sender, nonce) tag, so the pool replaces the cheaper
+		// transaction with the more expensive one.
+		assert_eq!(cheap.provides, expensive.provides);
+	});
+}
+
+#[test]
+fn gas_price_priority_saturates() {
+	let gas = ssvm::GasParams::of(&Call::SSVM(pallet_ssvm::Call::create(
+		vec![], 0.into(), 100_000, sp_core::U256::max_value(),
+	))).unwrap();
+
+	assert_eq!(gas.gas_limit, 100_000);
+	assert_eq!(gas.priority(), u64::max_value());
+}
+
+#[test]
+fn other_transactions_keep_fee_priority() {
+	new_test_ext().execute_with(|| {
+		let transfer = sign(
+			Call::Balances(BalancesCall::transfer(Sr25519Keyring::Bob.to_account_id().into(), 1_000)),
+			Sr25519Keyring::Alice,
+			0,
+		);
+
+		assert!(ssvm::GasParams::of(&transfer.function).is_none());
+		assert_eq!(
+			ssvm::validate_transaction(TransactionSource::External, transfer.clone()),
+			Executive::validate_transaction(TransactionSource::External, transfer),
+		);
+	});
+}
+
+/// Finalize `blocks` blocks in which normal extrinsics used `weight`.
+fn run_blocks_with_weight(blocks: u32, weight: Weight) {
+	for _ in 0..blocks {
+		System::set_block_limits(weight, 0);
+		TransactionPayment::on_finalize(System::block_number());
+		System::set_block_number(System::block_number() + 1);
+	}
+}
+
+fn max_normal_weight() -> Weight {
+	AvailableBlockRatio::get() * MaximumBlockWeight::get()
+}
+
+#[test]
+fn fee_multiplier_grows_with_sustained_full_blocks() {
+	new_test_ext().execute_with(|| {
+		let full = max_normal_weight();
+		let mut previous = TransactionPayment::next_fee_multiplier();
+		assert_eq!(previous, Multiplier::saturating_from_integer(1));
+
+		for _ in 0..100 {
+			run_blocks_with_weight(10, full);
+			let next = TransactionPayment::next_fee_multiplier();
+			assert!(next > previous, "{:?} should be above {:?}", next, previous);
+			previous = next;
+		}
+	});
+}
+
+#[test]
+fn fee_multiplier_is_stable_at_target_fullness() {
+	new_test_ext().execute_with(|| {
+		let target = TargetBlockFullness::get() * max_normal_weight();
+
+		run_blocks_with_weight(100, target);
+		assert_eq!(TransactionPayment::next_fee_multiplier(), Multiplier::saturating_from_integer(1));
+	});
+}
+
+#[test]
+fn fee_multiplier_shrinks_with_sustained_empty_blocks() {
+	new_test_ext().execute_with(|| {
+		let mut previous = TransactionPayment::next_fee_multiplier();
+
+		for _ in 0..100 {
+			run_blocks_with_weight(10, 0);
+			let next = TransactionPayment::next_fee_multiplier();
+			assert!(next < previous, "{:?} should be below {:?}", next, previous);
+			previous = next;
+		}
+	});
+}
+
+#[test]
+fn fee_multiplier_never_drops_below_minimum() {
+	new_test_ext().execute_with(|| {
+		pallet_transaction_payment::NextFeeMultiplier::put(MinimumMultiplier::get());
+
+		run_blocks_with_weight(100, 0);
+		assert_eq!(TransactionPayment::next_fee_multiplier(), MinimumMultiplier::get());
+
+		// Full blocks raise fees again from the minimum.
+		run_blocks_with_weight(1, max_normal_weight());
+		assert!(TransactionPayment::next_fee_multiplier() > MinimumMultiplier::get());
+	});
+}
+
+/// Start a block in the slot of the first validator, Alice.
+fn initialize_block_authored_by_alice() {
+	let slot: u64 = 0;
+	let digest = generic::Digest {
+		logs: vec![DigestItem::PreRuntime(AURA_ENGINE_ID, slot.encode())],
+	};
+	System::initialize(&2, &Default::default(), &Default::default(), &digest, Default::default());
+	assert_eq!(Authorship::author(), Sr25519Keyring::Alice.to_account_id());
+}
+
+#[test]
+fn ssvm_gas_is_split_between_author_and_treasury() {
+	new_test_ext().execute_with(|| {
+		let alice = Sr25519Keyring::Alice.to_account_id();
+		let charlie = Sr25519Keyring::Charlie.to_account_id();
+		let erc20 = deploy_erc20(&alice);
+		assert_ok!(BlockGas::set_block_gas_limit(Origin::root(), 10_000_000));
+		initialize_block_authored_by_alice();
+		let treasury = FeeDistribution::treasury_account_id();
+		let treasury_before = Balances::free_balance(&treasury);
+		let alice_before = Balances::free_balance(&alice);
+		let issuance = Balances::total_issuance();
+
+		// `transfer(address,uint256)` of 10 tokens to Charlie, at a gas price of 2.
+		let input = erc20_input([0xa9, 0x05, 0x9c, 0xbb], &charlie, Some(10));
+		let transfer = sign(
+			Call::SSVM(pallet_ssvm::Call::call(erc20, input, 0.into(), 1_000_000, 2.into())),
+			Sr25519Keyring::Alice,
+			0,
+		);
+		let fee = TransactionPayment::compute_fee(
+			transfer.encode().len() as u32,
+			&transfer.function.get_dispatch_info(),
+			0,
+		);
+		assert_eq!(Executive::apply_extrinsic(transfer), Ok(Ok(())));
+		assert_eq!(erc20_balance(erc20, &charlie), 10.into());
+
+		// Alice paid the fee and the gas burnt by `pallet_ssvm`, and got the author share of
+		// both back. The rest went to the treasury, and nothing was minted.
+		let paid = alice_before - Balances::free_balance(&alice);
+		let received = Balances::free_balance(&treasury) - treasury_before;
+		assert_eq!(received, paid);
+		assert!(received > Perbill::from_percent(80) * fee);
+		assert!(paid <= fee + 2 * 1_000_000);
+		assert_eq!(Balances::total_issuance(), issuance);
+	});
+}
+
+#[test]
+fn only_gas_lost_by_its_payers_is_minted_back() {
+	new_test_ext().execute_with(|| {
+		initialize_block_authored_by_alice();
+		let alice = Sr25519Keyring::Alice.to_account_id();
+		let bob = Sr25519Keyring::Bob.to_account_id();
+		let treasury = FeeDistribution::treasury_account_id();
+		let treasury_before = Balances::free_balance(&treasury);
+		let issuance = Balances::total_issuance();
+		let burn = |who: &AccountId, amount| drop(<Balances as Currency<_>>::withdraw(
+			who,
+			amount,
+			WithdrawReasons::from(WithdrawReason::Fee),
+			ExistenceRequirement::KeepAlive,
+		).unwrap());
+
+		// Bob pays for 100_000 gas at a price of 2. More than that is burnt from him, and
+		// some of the balance of Alice is burnt as well.
+		let call = ssvm_call(2);
+		let info = call.get_dispatch_info();
+		let pre = ssvm::ChargeGas.pre_dispatch(&bob, &call, &info, 0).unwrap();
+		burn(&bob, 500_000);
+		burn(&alice, 300_000);
+		assert_ok!(ssvm::ChargeGas::post_dispatch(pre, &info, &Default::default(), 0, &Ok(())));
+
+		// Only the cost of the gas limit of Bob is minted back.
+		assert_eq!(Balances::free_balance(&alice), (1 << 60) - 300_000 + 40_000);
+		assert_eq!(Balances::free_balance(&treasury), treasury_before + 160_000);
+		assert_eq!(Balances::total_issuance(), issuance - 600_000);
+	});
+}
+
+#[test]
+fn ssvm_gas_must_be_affordable() {
+	new_test_ext().execute_with(|| {
+		let charlie = Sr25519Keyring::Charlie.to_account_id();
+		let call = ssvm_call(1);
+
+		assert!(ssvm::ChargeGas.validate(&charlie, &call, &call.get_dispatch_info(), 0).is_err());
+		assert!(ssvm::ChargeGas.pre_dispatch(&charlie, &call, &call.get_dispatch_info(), 0).is_err());
+	});
+}
+
+#[test]
+fn transaction_fees_are_not_burned() {
+	new_test_ext().execute_with(|| {
+		initialize_block_authored_by_alice();
+		let issuance = Balances::total_issuance();
+		let transfer = sign(
+			Call::Balances(BalancesCall::transfer(Sr25519Keyring::Alice.to_account_id().into(), 1_000)),
+			Sr25519Keyring::Bob,
+			0,
+		);
+
+		assert_ok!(Executive::apply_extrinsic(transfer));
+		assert_eq!(Balances::total_issuance(), issuance);
+	});
+}
+
+#[test]
+fn ssvm_calls_stop_at_block_gas_limit() {
+	new_test_ext().execute_with(|| {
+		let bob = Sr25519Keyring::Bob.to_account_id();
+		let check = || pallet_block_gas::CheckBlockGas::<Runtime>::new();
+		let call = ssvm_call(1);
+		let transfer = Call::Balances(BalancesCall::transfer(bob.clone().into(), 1_000));
+
+		// The limit of 250_000 gas has room for two calls of 100_000 gas.
+		assert_ok!(check().pre_dispatch(&bob, &call, &call.get_dispatch_info(), 0));
+		assert_ok!(check().pre_dispatch(&bob, &call, &call.get_dispatch_info(), 0));
+		assert_eq!(
+			check().pre_dispatch(&bob, &call, &call.get_dispatch_info(), 0),
+			Err(InvalidTransaction::ExhaustsResources.into()),
+		);
+		// Other extrinsics still fit in the block.
+		assert_ok!(check().pre_dispatch(&bob, &transfer, &transfer.get_dispatch_info(), 0));
+	});
+}
+
+#[test]
+fn replay_reports_outcome_and_events_of_extrinsic() {
+	new_test_ext().execute_with(|| {
+		initialize_block_authored_by_alice();
+		let alice = Sr25519Keyring::Alice.to_account_id();
+		let bob = Sr25519Keyring::Bob.to_account_id();
+		let transfer = |amount, nonce| sign(
+			Call::Balances(BalancesCall::transfer(alice.clone().into(), amount)),
+			Sr25519Keyring::Bob,
+			nonce,
+		);
+		assert_ok!(Executive::apply_extrinsic(transfer(1_000, 0)));
+
+		// Only the events of the replayed extrinsic are reported.
+		let replay = <Runtime as apis::runtime_decl_for_ReplayApi::ReplayApi<Block>>::replay_extrinsic(transfer(2_000, 1));
+
+		assert_eq!(replay.result, Ok(Ok(())));
+		assert!(replay.events.contains(
+			&Event::pallet_balances(pallet_balances::RawEvent::Transfer(bob.clone(), alice.clone(), 2_000)),
+		));
+		assert!(!replay.events.iter().any(|event| match event {
+			Event::pallet_balances(pallet_balances::RawEvent::Transfer(_, _, amount)) => *amount == 1_000,
+			_ => false,
+		}));
+		assert!(replay.ssvm_calls.is_empty());
+	});
+}
+
+#[test]
+fn replay_reports_the_ssvm_calls_that_were_dispatched() {
+	new_test_ext().execute_with(|| {
+		initialize_block_authored_by_alice();
+		let alice = Sr25519Keyring::Alice.to_account_id();
+		let bob = Sr25519Keyring::Bob.to_account_id();
+		let replay = |xt| <Runtime as apis::runtime_decl_for_ReplayApi::ReplayApi<Block>>::replay_extrinsic(xt);
+		let name = b"bob.validator".to_vec();
+		assert_ok!(NameService::register(Origin::signed(bob.clone()), name.clone(), 1));
+
+		// Extrinsics signed with a name are dispatched from the account it resolves to.
+		let mut xt = sign(ssvm_call(1), Sr25519Keyring::Bob, 0);
+		xt.signature.as_mut().unwrap().0 = Address::Raw(name);
+		let call = match ssvm_call(1) {
+			Call::SSVM(call) => call,
+			_ => unreachable!(),
+		};
+		let replayed = replay(xt);
+		assert_eq!(replayed.signer, Some(bob.clone()));
+		assert_eq!(replayed.ssvm_calls, vec![(bob.clone(), call)]);
+
+		// The first approval of a 2-of-2 multisig does not dispatch its call.
+		let approval = Call::Multisig(pallet_multisig::Call::as_multi(
+			2,
+			vec![alice],
+			None,
+			ssvm_call(1).encode(),
+			false,
+			1_000_000_000,
+		));
+		let replayed = replay(sign(approval, Sr25519Keyring::Bob, 1));
+		assert_eq!(replayed.result, Ok(Ok(())));
+		assert!(replayed.ssvm_calls.is_empty());
+	});
+}
+
+/// Revert data of `revert("insufficient allowance")`.
+fn insufficient_allowance() -> Vec<u8> {
+	let reason = b"insufficient allowance";
+	let word = |value: usize| {
+		let mut word = [0u8; 32];
+		U256::from(value).to_big_endian(&mut word);
+		word
+	};
+	let mut data = pallet_contract_revert::ERROR_SELECTOR.to_vec();
+	data.extend(&word(32));
+	data.extend(&word(reason.len()));
+	data.extend(&reason[..]);
+	data.resize(4 + 32 * 3, 0);
+	data
+}
+
+#[test]
+fn failed_ssvm_calls_report_revert_reason() {
+	new_test_ext().execute_with(|| {
+		let bob = Sr25519Keyring::Bob.to_account_id();
+		let call = ssvm_call(1);
+		let info = call.get_dispatch_info();
+		let reverted = Event::pallet_contract_revert(pallet_contract_revert::Event::ContractReverted(
+			H160::repeat_byte(1),
+			b"insufficient allowance".to_vec(),
+		));
+
+		let pre = ssvm::ReportRevert.pre_dispatch(&bob, &call, &info, 0).unwrap();
+		System::deposit_event(pallet_ssvm::Event::Output(insufficient_allowance()));
+		assert_ok!(ssvm::ReportRevert::post_dispatch(pre, &info, &Default::default(), 0, &Ok(())));
+		assert!(!System::events().iter().any(|record| record.event == reverted));
+
+		let pre = ssvm::ReportRevert.pre_dispatch(&bob, &call, &info, 0).unwrap();
+		System::deposit_event(pallet_ssvm::Event::Output(insufficient_allowance()));
+		let result = Err(DispatchError::Other("reverted"));
+		assert_ok!(ssvm::ReportRevert::post_dispatch(pre, &info, &Default::default(), 0, &result));
+		assert_eq!(System::events().pop().unwrap().event, reverted);
+	});
+}
+
+#[test]
+fn failed_nested_ssvm_calls_report_revert_reason() {
+	new_test_ext().execute_with(|| {
+		let bob = Sr25519Keyring::Bob.to_account_id();
+		let call_to = |target| Call::SSVM(pallet_ssvm::Call::call(target, vec![], 0.into(), 100_000, 1.into()));
+		let proxied = Call::Proxy(pallet_proxy::Call::proxy(
+			Sr25519Keyring::Alice.to_account_id(),
+			None,
+			Box::new(call_to(H160::repeat_byte(2))),
+		));
+		let batch = Call::Utility(pallet_utility::Call::batch(vec![
+			call_to(H160::repeat_byte(1)),
+			proxied,
+			call_to(H160::repeat_byte(3)),
+		]));
+		let info = batch.get_dispatch_info();
+
+		// The first call succeeds, the proxied one reverts and the batch goes on.
+		let pre = ssvm::ReportRevert.pre_dispatch(&bob, &batch, &info, 0).unwrap();
+		System::deposit_event(pallet_ssvm::Event::Output(vec![1]));
+		System::deposit_event(pallet_ssvm::Event::Output(insufficient_allowance()));
+		System::deposit_event(pallet_proxy::Event::<Runtime>::ProxyExecuted(Err(DispatchError::Other("reverted"))));
+		System::deposit_event(pallet_ssvm::Event::Output(vec![3]));
+		System::deposit_event(pallet_utility::Event::BatchCompleted);
+		assert_ok!(ssvm::ReportRevert::post_dispatch(pre, &info, &Default::default(), 0, &Ok(())));
+
+		let reverted = System::events().into_iter().filter_map(|record| match record.event {
+			Event::pallet_contract_revert(pallet_contract_revert::Event::ContractReverted(target, reason)) =>
+				Some((target, reason)),
+			_ => None,
+		}).collect::<Vec<_>>();
+		assert_eq!(reverted, vec![(H160::repeat_byte(2), b"insufficient allowance".to_vec())]);
+
+		// The last call reverts and interrupts the batch.
+		let pre = ssvm::ReportRevert.pre_dispatch(&bob, &batch, &info, 0).unwrap();
+		System::deposit_event(pallet_ssvm::Event::Output(vec![1]));
+		System::deposit_event(pallet_ssvm::Event::Output(vec![2]));
+		System::deposit_event(pallet_proxy::Event::<Runtime>::ProxyExecuted(Ok(())));
+		System::deposit_event(pallet_ssvm::Event::Output(vec![3]));
+		System::deposit_event(pallet_utility::Event::BatchInterrupted(2, DispatchError::Other("reverted")));
+		assert_ok!(ssvm::ReportRevert::post_dispatch(pre, &info, &Default::default(), 0, &Ok(())));
+
+		assert_eq!(
+			System::events().pop().unwrap().event,
+			Event::pallet_contract_revert(pallet_contract_revert::Event::ContractReverted(H160::repeat_byte(3), vec![3])),
+		);
+	});
+}
+
+#[test]
+fn outcomes_of_nested_ssvm_calls_follow_their_dispatch() {
+	let alice = Sr25519Keyring::Alice.to_account_id();
+	let bob = Sr25519Keyring::Bob.to_account_id();
+	let call_to = |target| Call::SSVM(pallet_ssvm::Call::call(target, vec![], 0.into(), 100_000, 1.into()));
+	let approval = Call::Multisig(pallet_multisig::Call::as_multi(
+		2,
+		vec![alice.clone()],
+		None,
+		call_to(H160::repeat_byte(2)).encode(),
+		false,
+		1_000_000_000,
+	));
+	let batch = Call::Utility(pallet_utility::Call::batch(vec![
+		call_to(H160::repeat_byte(1)),
+		approval,
+		call_to(H160::repeat_byte(3)),
+		call_to(H160::repeat_byte(4)),
+	]));
+
+	// The approval does not reach the threshold and the third call interrupts the batch.
+	let events = vec![
+		Event::pallet_ssvm(pallet_ssvm::Event::Output(vec![1])),
+		Event::pallet_multisig(pallet_multisig::RawEvent::NewMultisig(
+			bob.clone(),
+			ssvm::multisig_account(&[alice, bob], 2),
+			[0; 32],
+		)),
+		Event::pallet_ssvm(pallet_ssvm::Event::Output(vec![3])),
+		Event::pallet_utility(pallet_utility::Event::BatchInterrupted(2, DispatchError::Other("reverted"))),
+	];
+	assert_eq!(ssvm::ssvm_outcomes(&batch, &events, false), vec![
+		ssvm::SsvmOutcome::Succeeded,
+		ssvm::SsvmOutcome::NotDispatched,
+		ssvm::SsvmOutcome::Failed(vec![3]),
+		ssvm::SsvmOutcome::NotDispatched,
+	]);
+
+	// Nothing a failed extrinsic dispatched is kept.
+	assert_eq!(
+		ssvm::ssvm_outcomes(&call_to(H160::repeat_byte(1)), &[], true),
+		vec![ssvm::SsvmOutcome::Failed(vec![])],
+	);
+}
+
+#[test]
+fn ssvm_deployers_register_contract_metadata() {
+	new_test_ext().execute_with(|| {
+		let bob = Sr25519Keyring::Bob.to_account_id();
+		let alice = Sr25519Keyring::Alice.to_account_id();
+		let address = H160::repeat_byte(2);
+		let call = Call::SSVM(pallet_ssvm::Call::create(vec![], 0.into(), 100_000, 1.into()));
+		let info = call.get_dispatch_info();
+
+		let pre = ssvm::NoteDeployer.pre_dispatch(&bob, &call, &info, 0).unwrap();
+		System::deposit_event(pallet_ssvm::Event::Create(address));
+		assert_ok!(ssvm::NoteDeployer::post_dispatch(pre, &info, &Default::default(), 0, &Ok(())));
+		assert_eq!(ContractRegistry::deployer(address), Some(bob.clone()));
+
+		let register = |who: &AccountId| ContractRegistry::register(
+			Origin::signed(who.clone()),
+			address,
+			Default::default(),
+			b"https://example.com/erc20.sol".to_vec(),
+			b"0.5.17".to_vec(),
+		);
+		assert!(register(&alice).is_err());
+		assert_ok!(register(&bob));
+		assert_eq!(ContractRegistry::metadata(address).unwrap().owner, bob);
+		assert_eq!(Balances::reserved_balance(&bob), ContractMetadataDeposit::get());
+	});
+}
+
+#[test]
+fn raw_addresses_resolve_registered_names() {
+	new_test_ext().execute_with(|| {
+		use sp_runtime::traits::{LookupError, StaticLookup};
+		type Lookup = <Runtime as frame_system::Trait>::Lookup;
+
+		let alice = Sr25519Keyring::Alice.to_account_id();
+		let bob = Sr25519Keyring::Bob.to_account_id();
+		let name = b"bob.validator".to_vec();
+		assert_eq!(Lookup::lookup(Address::Raw(name.clone())), Err(LookupError));
+		assert_eq!(Lookup::unlookup(bob.clone()), Address::Id(bob.clone()));
+
+		assert_ok!(NameService::register(Origin::signed(bob.clone()), name.clone(), 1));
+		assert_eq!(Lookup::lookup(Address::Raw(name.clone())), Ok(bob.clone()));
+		assert_eq!(Lookup::unlookup(bob.clone()), Address::Id(bob.clone()));
+
+		// Transfers can be made to the name instead of the account.
+		let before = Balances::free_balance(&bob);
+		assert_ok!(Balances::transfer(Origin::signed(alice), Address::Raw(name), 1_000));
+		assert_eq!(Balances::free_balance(&bob), before + 1_000);
+	});
+}
+
+#[test]
+fn batched_ssvm_calls_must_afford_their_gas() {
+	new_test_ext().execute_with(|| {
+		use pallet_block_gas::GasLimitOf;
+
+		let charlie = Sr25519Keyring::Charlie.to_account_id();
+		// 100_000 gas at a price of 2, then at a price of 3.
+		let batch = Call::AtomicBatch(pallet_atomic_batch::Call::batch_all(vec![
+			ssvm_call(2),
+			Call::Utility(pallet_utility::Call::batch(vec![ssvm_call(3)])),
+		]));
+
+		assert_eq!(ssvm::GasParams::gas_limit(&charlie, &batch), Some(200_000));
+		assert_eq!(ssvm::GasParams::costs(&charlie, &batch), Some(vec![(charlie.clone(), 500_000)]));
+		let _ = Balances::deposit_creating(&charlie, 499_999);
+		assert!(ssvm::ChargeGas.validate(&charlie, &batch, &batch.get_dispatch_info(), 0).is_err());
+		let _ = Balances::deposit_creating(&charlie, 1);
+		assert_ok!(ssvm::ChargeGas.validate(&charlie, &batch, &batch.get_dispatch_info(), 0));
+	});
+}
+
+#[test]
+fn nested_ssvm_calls_are_found() {
+	let create = pallet_ssvm::Call::create(vec![0; 4], 0.into(), 100_000, 1.into());
+	let proxied = Call::Proxy(pallet_proxy::Call::proxy(
+		Sr25519Keyring::Alice.to_account_id(),
+		None,
+		Box::new(Call::Utility(pallet_utility::Call::batch(vec![
+			Call::SSVM(create.clone()),
+			Call::System(frame_system::Call::remark(vec![])),
+			ssvm_call(1),
+		]))),
+	));
+
+	let calls = ssvm::ssvm_calls(&proxied);
+	assert_eq!(calls.len(), 2);
+	assert_eq!(calls[0], create);
+	assert!(matches!(calls[1], pallet_ssvm::Call::call(..)));
+	assert!(ssvm::ssvm_calls(&Call::System(frame_system::Call::remark(vec![]))).is_empty());
+
+	// Proxied calls are dispatched from the proxied account, whoever signs the proxy call.
+	let alice = Sr25519Keyring::Alice.to_account_id();
+	let calls = ssvm::ssvm_calls_from(&Sr25519Keyring::Bob.to_account_id(), &proxied);
+	assert_eq!(calls.len(), 2);
+	assert!(calls.iter().all(|(caller, _)| *caller == alice));
+}
+
+#[test]
+fn failed_atomic_batch_rolls_back_ssvm_calls() {
+	new_test_ext().execute_with(|| {
+		let bob = Sr25519Keyring::Bob.to_account_id();
+		let charlie = Sr25519Keyring::Charlie.to_account_id();
+		let transfer = |value| Call::Balances(BalancesCall::transfer(charlie.clone().into(), value));
+		let value_call = Call::SSVM(pallet_ssvm::Call::call(
+			H160::repeat_byte(1),
+			vec![],
+			1_000.into(),
+			100_000,
+			0.into(),
+		));
+
+		// The last transfer fails, so neither the SSVM call nor the first transfer happen,
+		// and no event is left behind.
+		let root = sp_io::storage::root();
+		let batch = vec![value_call, transfer(1_000), transfer(1 << 61)];
+		assert!(AtomicBatch::batch_all(Origin::signed(bob), batch).is_err());
+		assert_eq!(sp_io::storage::root(), root);
+		assert_eq!(Balances::free_balance(&charlie), 0);
+		assert!(System::events().is_empty());
+	});
+}
+
+/// The ERC20 contract of the tutorials, which mints 1000 tokens to its deployer.
+const ERC20: &[u8] = include_bytes!("../../docs/erc20/erc20.wasm");
+
+/// Deploy the ERC20 contract from the origin of `deployer` and return its address.
+fn deploy_erc20(deployer: &AccountId) -> H160 {
+	let create = Call::SSVM(pallet_ssvm::Call::create(ERC20.to_vec(), 0.into(), 50_000_000, 0.into()));
+	assert_ok!(create.dispatch(Origin::signed(deployer.clone())));
+	System::events().into_iter().rev().find_map(|record| match record.event {
+		Event::pallet_ssvm(pallet_ssvm::Event::Create(address)) => Some(address),
+		_ => None,
+	}).unwrap()
+}
+
+/// ABI encoding of a call to `selector` with the given address and amount arguments.
+fn erc20_input(selector: [u8; 4], account: &AccountId, amount: Option<u64>) -> Vec<u8> {
+	let mut input = selector.to_vec();
+	input.extend(H256::from(ssvm::address_of(account)).as_bytes());
+	if let Some(amount) = amount {
+		let mut word = [0u8; 32];
+		U256::from(amount).to_big_endian(&mut word);
+		input.extend(&word);
+	}
+	input
+}
+
+/// Tokens held by `account` according to `balanceOf(address)`.
+fn erc20_balance(erc20: H160, account: &AccountId) -> U256 {
+	let input = erc20_input([0x70, 0xa0, 0x82, 0x31], account, None);
+	let result = ssvm::dry_run(account.clone(), erc20, input, 0.into(), 1_000_000, 0.into());
+	assert_eq!(result.error, None);
+	U256::from_big_endian(&result.output)
+}
+
+#[test]
+fn failed_atomic_batch_rolls_back_contract_storage() {
+	new_test_ext().execute_with(|| {
+		let alice = Sr25519Keyring::Alice.to_account_id();
+		let charlie = Sr25519Keyring::Charlie.to_account_id();
+		let erc20 = deploy_erc20(&alice);
+		assert_eq!(erc20_balance(erc20, &alice), 1_000.into());
+
+		// `transfer(address,uint256)` of 10 tokens to Charlie.
+		let input = erc20_input([0xa9, 0x05, 0x9c, 0xbb], &charlie, Some(10));
+		let transfer_tokens = Call::SSVM(pallet_ssvm::Call::call(erc20, input, 0.into(), 1_000_000, 0.into()));
+
+		// The token transfer writes to the storage of the contract before the balance
+		// transfer fails, and is rolled back with it.
+		let root = sp_io::storage::root();
+		let batch = vec![
+			transfer_tokens.clone(),
+			Call::Balances(BalancesCall::transfer(charlie.clone().into(), 1 << 61)),
+		];
+		assert!(AtomicBatch::batch_all(Origin::signed(alice.clone()), batch).is_err());
+		assert_eq!(sp_io::storage::root(), root);
+		assert_eq!(erc20_balance(erc20, &alice), 1_000.into());
+		assert_eq!(erc20_balance(erc20, &charlie), 0.into());
+
+		// On its own, the token transfer goes through.
+		assert_ok!(AtomicBatch::batch_all(Origin::signed(alice.clone()), vec![transfer_tokens]));
+		assert_eq!(erc20_balance(erc20, &alice), 990.into());
+		assert_eq!(erc20_balance(erc20, &charlie), 10.into());
+	});
+}
+
+#[test]
+fn ssvm_proxies_only_dispatch_ssvm_calls() {
+	new_test_ext().execute_with(|| {
+		use frame_support::traits::InstanceFilter;
+		use ssvm::ProxyType;
+
+		let alice = Sr25519Keyring::Alice.to_account_id();
+		let bob = Sr25519Keyring::Bob.to_account_id();
+		let charlie = Sr25519Keyring::Charlie.to_account_id();
+		let transfer = Call::Balances(BalancesCall::transfer(charlie.clone().into(), 1_000));
+		let batch = Call::Utility(pallet_utility::Call::batch(vec![ssvm_call(1)]));
+
+		assert!(ProxyType::SSVM.filter(&ssvm_call(1)));
+		assert!(ProxyType::SSVM.filter(&batch));
+		assert!(!ProxyType::SSVM.filter(&transfer));
+		assert!(ProxyType::Any.filter(&transfer));
+		assert!(ProxyType::Any.is_superset(&ProxyType::SSVM));
+		assert!(!ProxyType::SSVM.is_superset(&ProxyType::Any));
+
+		assert_ok!(Proxy::add_proxy(Origin::signed(bob.clone()), alice.clone(), ProxyType::SSVM, 0));
+		let proxied = |call| Call::Proxy(pallet_proxy::Call::proxy(bob.clone(), None, Box::new(call)));
+
+		// The transfer is filtered out, and batches cannot smuggle it either.
+		let batched_transfer = Call::Utility(pallet_utility::Call::batch(vec![transfer.clone()]));
+		for call in vec![transfer, batched_transfer] {
+			assert_ok!(proxied(call).dispatch(Origin::signed(alice.clone())));
+		}
+		assert_eq!(Balances::free_balance(&charlie), 0);
+
+		// The SSVM calls the proxy dispatches for Bob are made from Bob's SSVM address, so Bob
+		// pays for their gas.
+		assert_eq!(ssvm::GasParams::costs(&alice, &proxied(batch)), Some(vec![(bob.clone(), 100_000)]));
+	});
+}
+
+#[test]
+fn multisig_accounts_deploy_contracts_from_their_ssvm_address() {
+	new_test_ext().execute_with(|| {
+		use pallet_block_gas::GasLimitOf;
+
+		let alice = Sr25519Keyring::Alice.to_account_id();
+		let bob = Sr25519Keyring::Bob.to_account_id();
+		let charlie = Sr25519Keyring::Charlie.to_account_id();
+		let multisig = ssvm::multisig_account(&[charlie.clone(), alice.clone(), bob.clone()], 2);
+		assert_eq!(multisig, ssvm::multisig_account(&[alice.clone(), bob.clone(), charlie.clone()], 2));
+		assert_ne!(ssvm::address_of(&multisig), ssvm::address_of(&bob));
+
+		// Bob approves the creation of a contract with its whole call.
+		let create = Call::SSVM(pallet_ssvm::Call::create(vec![], 0.into(), 100_000, 2.into()));
+		let sorted = |mut others: Vec<AccountId>| {
+			others.sort();
+			others
+		};
+		let call = Call::Multisig(pallet_multisig::Call::as_multi(
+			2,
+			sorted(vec![alice.clone(), charlie.clone()]),
+			None,
+			create.encode(),
+			false,
+			1_000_000_000,
+		));
+		let info = call.get_dispatch_info();
+
+		// As the first approval, it does not reach the threshold of the 2-of-3 multisig, so
+		// it does not create anything and no gas is paid.
+		assert_eq!(ssvm::GasParams::costs(&bob, &call), Some(vec![]));
+		assert_eq!(ssvm::GasParams::gas_limit(&bob, &call), None);
+
+		// Once Alice approved the hash of the creation, the approval of Bob reaches the
+		// threshold and the multisig account pays for the gas.
+		assert_ok!(Multisig::approve_as_multi(
+			Origin::signed(alice.clone()),
+			2,
+			sorted(vec![bob.clone(), charlie.clone()]),
+			None,
+			sp_io::hashing::blake2_256(&create.encode()),
+			0,
+		));
+		assert_eq!(ssvm::GasParams::costs(&bob, &call), Some(vec![(multisig.clone(), 200_000)]));
+		assert_eq!(ssvm::GasParams::gas_limit(&bob, &call), Some(100_000));
+
+		// The multisig account cannot afford the gas yet.
+		assert!(ssvm::ChargeGas.validate(&bob, &call, &info, 0).is_err());
+		let _ = Balances::deposit_creating(&multisig, 200_000);
+		assert_ok!(ssvm::ChargeGas.validate(&bob, &call, &info, 0));
+
+		// The multisig account, not Bob, is the deployer of the contract.
+		let address = H160::repeat_byte(3);
+		let pre = ssvm::NoteDeployer.pre_dispatch(&bob, &call, &info, 0).unwrap();
+		System::deposit_event(pallet_ssvm::Event::Create(address));
+		assert_ok!(ssvm::NoteDeployer::post_dispatch(pre, &info, &Default::default(), 0, &Ok(())));
+		assert_eq!(ContractRegistry::deployer(address), Some(multisig));
+	});
+}
+
+#[test]
+fn accounts_schedule_recurring_ssvm_calls() {
+	new_test_ext().execute_with(|| {
+		use frame_support::traits::OnInitialize;
+
+		let bob = Sr25519Keyring::Bob.to_account_id();
+		let transfer = Call::Balances(BalancesCall::transfer(bob.clone().into(), 1_000));
+		assert!(ContractScheduler::schedule(Origin::signed(bob.clone()), 5, None, Box::new(transfer)).is_err());
+
+		// A release call every 1,000 blocks, three times, at 100_000 gas for a price of 2.
+		let release = ssvm_call(2);
+		assert_ok!(ContractScheduler::schedule(Origin::signed(bob.clone()), 1_000, Some((1_000, 3)), Box::new(release)));
+		let deposit = ScheduledTaskDeposit::get();
+		assert_eq!(Balances::reserved_balance(&bob), 3 * 200_000 + deposit);
+
+		// The execution counts against the block gas limit.
+		System::set_block_number(1_000);
+		Scheduler::on_initialize(1_000);
+		assert_eq!(ContractScheduler::task(0).unwrap().remaining, 2);
+		assert_eq!(Balances::reserved_balance(&bob), 2 * 200_000 + deposit);
+		assert_eq!(BlockGas::block_gas_used(), 100_000);
+
+		assert_ok!(ContractScheduler::cancel(Origin::signed(bob.clone()), 0));
+		assert_eq!(Balances::reserved_balance(&bob), 0);
+		System::set_block_number(2_000);
+		Scheduler::on_initialize(2_000);
+		assert_eq!(ContractScheduler::task(0), None);
+	});
+}
+
+#[test]
+fn scheduled_ssvm_calls_use_the_block_gas_of_their_block() {
+	new_test_ext().execute_with(|| {
+		use sp_runtime::traits::Header as _;
+
+		let bob = Sr25519Keyring::Bob.to_account_id();
+		let initialize_block = |number| Executive::initialize_block(
+			&Header::new(number, Default::default(), Default::default(), Default::default(), Default::default()),
+		);
+		assert_ok!(ContractScheduler::schedule(Origin::signed(bob.clone()), 5, Some((10, 2)), Box::new(ssvm_call(2))));
+
+		// The gas used by the previous block is released when it is finalized, and the
+		// execution is not wiped from the gas used by its block.
+		assert_ok!(BlockGas::reserve(200_000));
+		BlockGas::on_finalize(4);
+		initialize_block(5);
+		assert_eq!(BlockGas::block_gas_used(), 100_000);
+		assert_eq!(ContractScheduler::task(0).unwrap().remaining, 1);
+		BlockGas::on_finalize(5);
+
+		// Without gas left in its block, the next execution is postponed, keeping its reserve.
+		assert_ok!(BlockGas::set_block_gas_limit(Origin::root(), 50_000));
+		initialize_block(15);
+		assert_eq!(BlockGas::block_gas_used(), 0);
+		assert!(System::events().iter().any(|record| record.event ==
+			Event::pallet_contract_scheduler(pallet_contract_scheduler::RawEvent::Postponed(0, 16))));
+		assert_eq!(ContractScheduler::task(0).unwrap().remaining, 1);
+		assert_eq!(Balances::reserved_balance(&bob), 200_000 + ScheduledTaskDeposit::get());
+		BlockGas::on_finalize(15);
+
+		assert_ok!(BlockGas::set_block_gas_limit(Origin::root(), 250_000));
+		initialize_block(16);
+		assert_eq!(BlockGas::block_gas_used(), 100_000);
+		assert_eq!(ContractScheduler::task(0), None);
+		assert_eq!(Balances::reserved_balance(&bob), 0);
+	});
+}
+
+#[test]
+fn keepers_submit_signed_upkeeps_of_registered_contracts() {
+	let keystore = sp_core::testing::KeyStore::new();
+	keystore.write()
+		.sr25519_generate_new(pallet_contract_keeper::KEY_TYPE, Some("//Alice"))
+		.unwrap();
+	let mut ext = new_test_ext();
+	ext.register_extension(sp_core::traits::KeystoreExt(keystore));
+
+	ext.execute_with(|| {
+		use frame_system::offchain::CreateSignedTransaction;
+		use sp_runtime::traits::Extrinsic;
+
+		let alice = Sr25519Keyring::Alice.to_account_id();
+		let bob = Sr25519Keyring::Bob.to_account_id();
+		let address = H160::repeat_byte(2);
+		let register_upkeep = |who: &AccountId| ContractKeeper::register_upkeep(
+			Origin::signed(who.clone()),
+			address,
+			vec![],
+			vec![],
+			100_000,
+			1.into(),
+		);
+
+		// Only the owner of the metadata of a contract registers its upkeep.
+		ContractRegistry::note_deployed(address, bob.clone());
+		assert_ok!(ContractRegistry::register(
+			Origin::signed(bob.clone()),
+			address,
+			Default::default(),
+			vec![],
+			vec![],
+		));
+		assert!(register_upkeep(&alice).is_err());
+		assert_ok!(register_upkeep(&bob));
+
+		// Alice's keeper key signs the upkeep, which is not needed since no contract
+		// returns `true` at that address. Bob pays for the gas of the check, which leaves
+		// no other trace.
+		initialize_block_authored_by_alice();
+		let bob_before = Balances::free_balance(&bob);
+		let call = Call::ContractKeeper(pallet_contract_keeper::Call::perform_upkeep(address));
+		let (call, signature) = Runtime::create_transaction::<pallet_contract_keeper::crypto::KeeperId>(
+			call,
+			Sr25519Keyring::Alice.public().into(),
+			alice.clone(),
+			0,
+		).unwrap();
+		let xt = UncheckedExtrinsic::new(call, Some(signature)).unwrap();
+		assert_ok!(Executive::apply_extrinsic(xt).unwrap());
+		assert!(System::events().iter().all(|record| !matches!(record.event, Event::pallet_ssvm(_))));
+		let not_needed = Event::pallet_contract_keeper(pallet_contract_keeper::RawEvent::UpkeepNotNeeded(address));
+		assert!(System::events().iter().any(|record| record.event == not_needed));
+		assert_eq!(Balances::free_balance(&bob), bob_before - 100_000);
+		assert_eq!(BlockGas::block_gas_used(), 100_000);
+		let upkeep = ContractKeeper::upkeep(address).unwrap();
+		assert_eq!((upkeep.last_checked, upkeep.last_performed), (Some(System::block_number()), None));
+	});
+}
+
+#[test]
+fn validators_feed_the_randomness_beacon() {
+	new_test_ext().execute_with(|| {
+		use frame_support::traits::Randomness;
+
+		let alice = Sr25519Keyring::Alice.to_account_id();
+		let bob = Sr25519Keyring::Bob.to_account_id();
+		let charlie = Sr25519Keyring::Charlie.to_account_id();
+		let secret = sp_core::H256::repeat_byte(1);
+		let commit = |who: &AccountId| RandomnessBeacon::commit(
+			Origin::signed(who.clone()),
+			RandomnessBeacon::commitment_of(who, &secret),
+		);
+		let treasury = FeeDistribution::treasury_account_id();
+		let treasury_before = Balances::free_balance(&treasury);
+		let seed = RandomnessBeacon::random_seed();
+
+		assert!(commit(&charlie).is_err());
+		assert_ok!(commit(&alice));
+		assert_ok!(commit(&bob));
+
+		System::set_block_number(RandomnessRoundLength::get() / 2);
+		assert_ok!(RandomnessBeacon::reveal(Origin::signed(alice), secret));
+		RandomnessBeacon::on_finalize(RandomnessRoundLength::get() - 1);
+
+		// The block builder seed comes from the beacon, and Bob, who did not reveal, paid
+		// for it.
+		assert_ne!(RandomnessBeacon::random_seed(), seed);
+		assert_eq!(Balances::free_balance(&treasury), treasury_before + RandomnessCommitDeposit::get());
+	});
+}
+
+#[test]
+fn template_chains_are_migrated_to_the_validator_set() {
+	let authorities = vec![Sr25519Keyring::Alice, Sr25519Keyring::Bob];
+	let grandpa_keys = vec![Ed25519Keyring::Alice, Ed25519Keyring::Bob];
+	// A chain of the template: consensus authorities and none of the pallets added since.
+	let storage = GenesisConfig {
+		frame_system: Some(SystemConfig {
+			code: vec![],
+			changes_trie_config: Default::default(),
+		}),
+		pallet_balances: Some(BalancesConfig {
+			balances: authorities.iter().map(|k| (k.to_account_id(), 1 << 60)).collect(),
+		}),
+		pallet_validator_set: None,
+		pallet_session: None,
+		pallet_aura: Some(AuraConfig {
+			authorities: authorities.iter().map(|k| k.public().into()).collect(),
+		}),
+		pallet_grandpa: Some(GrandpaConfig {
+			authorities: grandpa_keys.iter().map(|k| (k.public().into(), 1)).collect(),
+		}),
+		pallet_fee_distribution: None,
+		pallet_block_gas: None,
+		pallet_sudo: Some(SudoConfig {
+			key: Sr25519Keyring::Alice.to_account_id(),
+		}),
+		pallet_ssvm: None,
+	}.build_storage().unwrap();
+
+	sp_io::TestExternalities::new(storage).execute_with(|| {
+		frame_support::storage::migration::put_storage_value(
+			b"RandomnessCollectiveFlip", b"RandomMaterial", &[], vec![Hash::default(); 81],
+		);
+
+		migrations::FromTemplate::on_runtime_upgrade();
+
+		let accounts = authorities.iter().map(|k| k.to_account_id()).collect::<Vec<_>>();
+		assert_eq!(ValidatorSet::validators(), accounts);
+		assert_eq!(Session::validators(), accounts);
+		assert_eq!(
+			Session::queued_keys().into_iter().map(|(account, _)| account).collect::<Vec<_>>(),
+			accounts,
+		);
+		assert_eq!(
+			Session::load_keys(&Sr25519Keyring::Bob.to_account_id()),
+			Some(opaque::SessionKeys {
+				aura: Sr25519Keyring::Bob.public().into(),
+				grandpa: Ed25519Keyring::Bob.public().into(),
+			}),
+		);
+		assert_eq!(FeeDistribution::author_share(), migrations::AUTHOR_SHARE);
+		assert_eq!(BlockGas::block_gas_limit(), migrations::BLOCK_GAS_LIMIT);
+		assert!(!frame_support::storage::migration::have_storage_value(
+			b"RandomnessCollectiveFlip", b"RandomMaterial", &[],
+		));
+
+		// Chains with validators are left as they are.
+		assert_ok!(BlockGas::set_block_gas_limit(Origin::root(), 1));
+		migrations::FromTemplate::on_runtime_upgrade();
+		assert_eq!(BlockGas::block_gas_limit(), 1);
+	});
+}